@@ -0,0 +1,359 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use super::{Template, mobileconfig};
+use crate::finder::{Condition, DedupePolicy, Filter, Glob, Selector, SortKey, SortOrder};
+
+/// Manage macOS Finder sidebar favorites
+#[derive(Debug, Parser)]
+#[command(name = "favkit", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file [default: $FAVKIT_CONFIG, or favkit/config.toml in the config directory]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Never mount volumes while resolving favorites (default)
+    #[arg(long, global = true, overrides_with = "mount")]
+    pub no_mount: bool,
+
+    /// Mount network volumes while resolving favorites
+    #[arg(long, global = true, overrides_with = "no_mount")]
+    pub mount: bool,
+
+    /// Never show dialogs while resolving favorites (default)
+    #[arg(long, global = true, overrides_with = "allow_ui")]
+    pub no_ui: bool,
+
+    /// Allow authentication and other dialogs while resolving favorites
+    #[arg(long, global = true, overrides_with = "no_ui")]
+    pub allow_ui: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Show current favorites (default)
+    List {
+        /// Format each item, e.g. '{index}\t{label}\t{path|kind}'
+        ///
+        /// Fields: index, kind, label, path, url, hidden, managed, id. `{a|b|"text"}` uses the first
+        /// non-empty alternative; `\t`, `\n`, `\\`, `\{` and `\}` are escapes.
+        #[arg(long)]
+        template: Option<Template>,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Remove one sidebar item, or every item matching a filter
+    Remove {
+        /// Item label, path, or `id:<n>`
+        #[arg(required_unless_present_any = ["conditions", "pattern", "all"])]
+        selector: Option<Selector>,
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Only remove items favkit added
+        #[arg(long)]
+        managed_only: bool,
+        /// With --managed-only, remove every item favkit added
+        #[arg(long, requires = "managed_only", conflicts_with = "selector")]
+        all: bool,
+        /// Print what would be removed without changing the sidebar
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Hide a sidebar item without removing it
+    Hide {
+        /// Item label, path, or `id:<n>`
+        selector: Selector,
+    },
+    /// Show a previously hidden sidebar item
+    Show {
+        /// Item label, path, or `id:<n>`
+        selector: Selector,
+    },
+    /// Check favorites for broken, duplicate and unreachable targets
+    ///
+    /// Exits with status 2 when errors are found.
+    Doctor {
+        /// Treat warnings as failures too
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Remove favorites whose targets no longer exist
+    Prune {
+        /// Print what would be removed without changing the sidebar
+        #[arg(long)]
+        dry_run: bool,
+        /// Leave items on unmounted network volumes alone
+        #[arg(long)]
+        keep_network: bool,
+        /// Only remove items favkit added
+        #[arg(long)]
+        managed_only: bool,
+    },
+    /// Remove favorites pointing at the same target, keeping one of each
+    Dedupe {
+        /// Which duplicate to keep
+        #[arg(long, value_enum, default_value_t)]
+        keep: Keep,
+        /// Keep the duplicate with this label, falling back to the first
+        #[arg(long, value_name = "LABEL", conflicts_with = "keep")]
+        prefer: Option<String>,
+        /// Print what would be removed without changing the sidebar
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Reorder the sidebar with as few moves as possible
+    Sort {
+        /// How to order items not named by --priority
+        #[arg(long, value_enum, default_value_t)]
+        by: SortBy,
+        /// Comma-separated labels to put first, in this order
+        #[arg(long, value_name = "LABELS", value_delimiter = ',')]
+        priority: Vec<String>,
+        /// Print the moves without changing the sidebar
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Apply operations piped on stdin as one unit, rolling back if any fails
+    ///
+    /// One operation per line (`add <label> <path|url> [first|last|after <item>]`,
+    /// `remove <item>`, `move <item> first|last|after <item>`, `rename <item> <label>`), or JSON
+    /// objects like `{"op": "move", "item": "Projects", "to": "first"}`.
+    Batch {
+        /// Validate the operations and print them without changing the sidebar
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Speak JSON-RPC 2.0 for editors and GUIs, one message per line
+    ///
+    /// Methods: list, add, remove, move, rename, watch, unwatch. After `watch`, `changed`
+    /// notifications report every change to the sidebar.
+    Serve {
+        /// Serve over stdin and stdout
+        #[arg(long, required = true)]
+        stdio: bool,
+        /// How often to check for changes made by others while idle, in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 1000)]
+        poll: u64,
+    },
+    /// Keep the sidebar in memory and serve it to local clients over a Unix socket
+    ///
+    /// Speaks the same JSON-RPC as `serve`, answering `list` from memory. Methods: list, seed,
+    /// subscribe, unsubscribe, add, remove, move, rename, shutdown. After `subscribe`, `changed`
+    /// notifications list the items added, removed, moved and updated.
+    Daemon {
+        /// Socket to listen on [default: favkit/favkit.sock in the runtime or cache directory]
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+        /// How often to check the sidebar for changes, in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 1000)]
+        poll: u64,
+    },
+    /// Bring the sidebar in line with a manifest
+    ///
+    /// Missing items are added, items not in the manifest removed, and order and visibility set
+    /// as listed. Does nothing when the sidebar already matches.
+    Apply {
+        /// TOML manifest to apply, or `-` for stdin [default: manifest.path from the config]
+        #[arg(long, value_name = "PATH")]
+        manifest: Option<PathBuf>,
+        /// Leave items favkit did not add alone unless the manifest lists them
+        #[arg(long)]
+        managed_only: bool,
+        /// Print the changes without making them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Activate a manifest from a nix-darwin or home-manager activation script
+    ///
+    /// Adds and orders the manifest's items like `apply`, but leaves items added by hand alone:
+    /// only items added by an earlier activation, recorded by target in a state file, are
+    /// removed once they leave the manifest. Prints nothing when the sidebar already matches.
+    Activate {
+        /// TOML manifest to activate, or `-` for stdin [default: manifest.path from the config]
+        #[arg(long, value_name = "PATH")]
+        manifest: Option<PathBuf>,
+        /// File recording the items favkit added [default: favkit/activation.json in the state
+        /// directory]
+        #[arg(long, value_name = "PATH")]
+        state: Option<PathBuf>,
+        /// Print the changes without making them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check a manifest for mistakes, reporting the line and column of each
+    ///
+    /// Exits with status 2 when it finds problems.
+    Validate {
+        /// TOML manifest to check, or `-` for stdin [default: manifest.path from the config]
+        #[arg(value_name = "PATH")]
+        manifest: Option<PathBuf>,
+        /// Print the JSON Schema of the manifest format instead, for editors
+        #[arg(long, conflicts_with = "manifest")]
+        schema: bool,
+    },
+    /// Save and switch between named sidebar layouts
+    Profile {
+        /// Directory holding the profiles [default: manifest.profiles from the config, or
+        /// favkit/profiles in the config directory]
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+        #[command(subcommand)]
+        action: ProfileCommand,
+    },
+    /// Write the sidebar as a manifest or a configuration profile
+    Export {
+        /// Output format
+        #[arg(long = "as", value_enum, default_value_t)]
+        format: ExportFormat,
+        /// Export this manifest (`-` for stdin) instead of the current sidebar
+        #[arg(long, value_name = "PATH")]
+        manifest: Option<PathBuf>,
+        /// Preference domain and profile identifier for mobileconfig
+        #[arg(long, value_name = "ID", default_value = mobileconfig::DEFAULT_IDENTIFIER)]
+        identifier: String,
+    },
+    /// Converge one item for configuration management, with JSON in and out
+    ///
+    /// Reads Ansible-style arguments such as `{"state": "present", "path": "~/Projects",
+    /// "position": "after:Applications"}` and prints `{"changed": bool}`, with `diff` in diff
+    /// mode. Honours `_ansible_check_mode` and `_ansible_diff`.
+    Module {
+        /// File holding the JSON arguments, as Ansible passes to binary modules [default: stdin]
+        args: Option<PathBuf>,
+    },
+    /// Revert the latest change favkit made to the sidebar
+    Undo {
+        /// Undo even if the sidebar was changed by something else since
+        #[arg(long)]
+        force: bool,
+    },
+    /// Re-apply the latest undone change
+    Redo {
+        /// Redo even if the sidebar was changed by something else since
+        #[arg(long)]
+        force: bool,
+    },
+    /// Run a `mysides`-compatible command: list, add <name> <uri>, remove <name>
+    ///
+    /// Output and exit codes match the original `mysides`. Invoking favkit through a link named
+    /// `mysides` does the same.
+    Mysides {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self::List {
+            template: None,
+            filter: FilterArgs::default(),
+        }
+    }
+}
+
+/// Item selection shared by `list`, `remove` and other bulk commands.
+#[derive(Debug, Clone, Default, Args)]
+pub struct FilterArgs {
+    /// Only items matching KEY=VALUE; repeat to narrow further
+    ///
+    /// Keys: kind (airdrop, recents, applications, custom, network, unresolved), label and path
+    /// (globs), under (a directory), hidden (true/false), managed (true/false), id.
+    #[arg(long = "where", value_name = "KEY=VALUE")]
+    pub conditions: Vec<Condition>,
+
+    /// Only items whose label matches this glob, e.g. 'Proj*'
+    #[arg(long = "match", value_name = "GLOB")]
+    pub pattern: Option<Glob>,
+}
+
+impl FilterArgs {
+    /// Combines `--where` conditions with the `--match` pattern.
+    pub fn filter(&self) -> Filter {
+        self.conditions
+            .iter()
+            .cloned()
+            .chain(self.pattern.clone().map(Condition::Label))
+            .collect()
+    }
+}
+
+/// What `favkit profile` does.
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum ProfileCommand {
+    /// Save the current sidebar as a profile, replacing one with the same name
+    Save {
+        /// Profile name, e.g. client-a
+        name: String,
+    },
+    /// Bring the sidebar in line with a profile, like `apply`
+    Use {
+        /// Profile name
+        name: String,
+        /// Print the changes without making them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List the saved profiles, marking the one the sidebar matches
+    List,
+}
+
+/// What `export` writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// TOML manifest for `apply`
+    #[default]
+    Manifest,
+    /// Configuration profile embedding the manifest and a login script applying it
+    Mobileconfig,
+}
+
+/// Ordering `sort` applies after the priority labels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// Alphabetically by label
+    #[default]
+    Label,
+    /// By path or URL, built-in items first
+    Path,
+    /// Built-in items first, the rest unchanged
+    Special,
+    /// Keep the current order
+    Current,
+}
+
+impl SortBy {
+    /// Combines `--by` with the `--priority` labels into a sort order.
+    pub fn order(self, priority: Vec<String>) -> SortOrder {
+        let key = match self {
+            Self::Label => SortKey::Label,
+            Self::Path => SortKey::Path,
+            Self::Special => SortKey::SpecialFirst,
+            Self::Current => SortKey::Current,
+        };
+        SortOrder::new(key).with_priority(priority.iter().map(|label| label.trim()))
+    }
+}
+
+/// Position of the duplicate `dedupe` keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Keep {
+    #[default]
+    First,
+    Last,
+}
+
+impl Keep {
+    /// Combines `--keep` with an optional `--prefer` label into a dedupe policy.
+    pub fn policy(self, prefer: Option<String>) -> DedupePolicy {
+        match (prefer, self) {
+            (Some(label), _) => DedupePolicy::PreferLabel(label),
+            (None, Self::First) => DedupePolicy::First,
+            (None, Self::Last) => DedupePolicy::Last,
+        }
+    }
+}
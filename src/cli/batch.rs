@@ -0,0 +1,211 @@
+//! Reading `favkit batch` input, either one operation per line or JSON.
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::finder::{Destination, Operation, Selector, batch};
+
+#[derive(Debug, Error)]
+pub enum BatchInputError {
+    #[error(transparent)]
+    Lines(#[from] batch::BatchError),
+    #[error("invalid JSON batch: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("operation {number}: use either \"to\" or \"after\", not both")]
+    ConflictingDestination { number: usize },
+    #[error("operation {number}: unknown destination '{to}', expected first or last")]
+    UnknownDestination { number: usize, to: String },
+    #[error("operation {number}: missing \"to\" or \"after\"")]
+    MissingDestination { number: usize },
+}
+
+/// One JSON operation, e.g. `{"op": "move", "item": "Projects", "after": "Applications"}`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+enum Record {
+    Add {
+        label: String,
+        location: String,
+        to: Option<String>,
+        after: Option<String>,
+    },
+    Remove {
+        item: String,
+    },
+    Move {
+        item: String,
+        to: Option<String>,
+        after: Option<String>,
+    },
+    Rename {
+        item: String,
+        label: String,
+    },
+}
+
+/// Either a single operation (JSON lines) or an array of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Records {
+    One(Record),
+    Many(Vec<Record>),
+}
+
+/// Parses a batch, treating input starting with `{` or `[` as JSON.
+pub fn parse(input: &str) -> Result<Vec<Operation>, BatchInputError> {
+    if !input.trim_start().starts_with(['{', '[']) {
+        return Ok(batch::parse_lines(input)?);
+    }
+
+    let mut records = Vec::new();
+    for chunk in serde_json::Deserializer::from_str(input).into_iter::<Records>() {
+        match chunk? {
+            Records::One(record) => records.push(record),
+            Records::Many(many) => records.extend(many),
+        }
+    }
+
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| operation(index + 1, record))
+        .collect()
+}
+
+fn operation(number: usize, record: Record) -> Result<Operation, BatchInputError> {
+    let operation = match record {
+        Record::Add {
+            label,
+            location,
+            to,
+            after,
+        } => Operation::add(
+            &label,
+            &location,
+            destination(number, to, after)?.unwrap_or_default(),
+        ),
+        Record::Remove { item } => Operation::Remove(selector(&item)),
+        Record::Move { item, to, after } => Operation::Move {
+            item: selector(&item),
+            to: destination(number, to, after)?
+                .ok_or(BatchInputError::MissingDestination { number })?,
+        },
+        Record::Rename { item, label } => Operation::Rename {
+            item: selector(&item),
+            label,
+        },
+    };
+    Ok(operation)
+}
+
+fn destination(
+    number: usize,
+    to: Option<String>,
+    after: Option<String>,
+) -> Result<Option<Destination>, BatchInputError> {
+    match (to.as_deref(), after) {
+        (Some(_), Some(_)) => Err(BatchInputError::ConflictingDestination { number }),
+        (Some("first"), None) => Ok(Some(Destination::First)),
+        (Some("last"), None) => Ok(Some(Destination::Last)),
+        (Some(to), None) => Err(BatchInputError::UnknownDestination {
+            number,
+            to: to.to_string(),
+        }),
+        (None, Some(after)) => Ok(Some(Destination::After(selector(&after)))),
+        (None, None) => Ok(None),
+    }
+}
+
+fn selector(word: &str) -> Selector {
+    let Ok(selector) = word.parse();
+    selector
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::Target;
+
+    fn label(label: &str) -> Selector {
+        Selector::Label(label.to_string())
+    }
+
+    #[test]
+    fn should_parse_json_array() {
+        // Arrange
+        let input = r#"[
+            {"op": "add", "label": "NAS", "location": "smb://nas.local/share", "after": "Projects"},
+            {"op": "move", "item": "Downloads", "to": "first"}
+        ]"#;
+
+        // Act
+        let operations = parse(input).unwrap();
+
+        // Assert
+        assert_eq!(
+            operations,
+            vec![
+                Operation::Add {
+                    target: Target::network("NAS", "smb://nas.local/share"),
+                    to: Destination::After(label("Projects")),
+                },
+                Operation::Move {
+                    item: label("Downloads"),
+                    to: Destination::First,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_json_lines() {
+        // Arrange
+        let input = "{\"op\": \"remove\", \"item\": \"id:3\"}\n\
+                     {\"op\": \"rename\", \"item\": \"Projects\", \"label\": \"Code\"}\n";
+
+        // Act
+        let operations = parse(input).unwrap();
+
+        // Assert
+        assert_eq!(
+            operations,
+            vec![
+                Operation::Remove("id:3".parse().unwrap()),
+                Operation::Rename {
+                    item: label("Projects"),
+                    label: "Code".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_lines_when_not_json() {
+        assert_eq!(
+            parse("remove Projects\n").unwrap(),
+            vec![Operation::Remove(label("Projects"))]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_destinations() {
+        assert!(matches!(
+            parse(r#"{"op": "move", "item": "Projects"}"#),
+            Err(BatchInputError::MissingDestination { number: 1 })
+        ));
+        assert!(matches!(
+            parse(r#"[{"op": "remove", "item": "A"}, {"op": "move", "item": "B", "to": "top"}]"#),
+            Err(BatchInputError::UnknownDestination { number: 2, .. })
+        ));
+        assert!(matches!(
+            parse(r#"{"op": "move", "item": "A", "to": "first", "after": "B"}"#),
+            Err(BatchInputError::ConflictingDestination { number: 1 })
+        ));
+        assert!(matches!(
+            parse(r#"{"op": "copy", "item": "A"}"#),
+            Err(BatchInputError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+use std::io::Write;
+
+use crate::{
+    Finder,
+    cli::Result,
+    finder::{Manifest, Target},
+    system::activation::StateFile,
+};
+
+/// Activates the manifest for nix-darwin and home-manager activation scripts: prints one
+/// line per change and nothing at all when the sidebar already matches.
+pub fn activate(
+    finder: &Finder,
+    manifest: &Manifest,
+    state: &StateFile,
+    dry_run: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let owned = state.load()?;
+    let mut saved = owned.clone();
+    let activation = if dry_run {
+        finder.plan_activation(manifest, &owned)?
+    } else {
+        // Record the targets about to be added first, so that a run failing halfway through
+        // still owns whatever it added and the next one can remove it again.
+        let planned = finder.plan_activation(manifest, &owned)?;
+        let claimed = claim(&owned, &planned.owned);
+        if claimed != saved {
+            state.save(&claimed)?;
+            saved = claimed;
+        }
+        finder.activate(manifest, &owned)?
+    };
+
+    for change in &activation.convergence.changes {
+        if dry_run {
+            writeln!(out, "Would {}", change)?;
+        } else {
+            writeln!(out, "{}", change)?;
+        }
+    }
+    if !dry_run && activation.owned != saved {
+        state.save(&activation.owned)?;
+    }
+    Ok(())
+}
+
+/// The targets owned before, followed by those the activation will own for the first time.
+fn claim(owned: &[Target], planned: &[Target]) -> Vec<Target> {
+    let mut claimed = owned.to_vec();
+    claimed.extend(
+        planned
+            .iter()
+            .filter(|target| !owned.contains(target))
+            .cloned(),
+    );
+    claimed
+}
@@ -0,0 +1,33 @@
+use std::io::Write;
+
+use crate::{
+    Finder,
+    cli::Result,
+    finder::{Manifest, Scope},
+};
+
+pub fn apply(
+    finder: &Finder,
+    manifest: &Manifest,
+    scope: Scope,
+    dry_run: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let changes = if dry_run {
+        finder.apply_changes(manifest, scope)?
+    } else {
+        finder.apply(manifest, scope)?
+    };
+
+    if changes.is_empty() {
+        writeln!(out, "Nothing to do")?;
+    } else if dry_run {
+        for change in &changes {
+            writeln!(out, "Would {}", change)?;
+        }
+    } else {
+        let plural = if changes.len() == 1 { "" } else { "s" };
+        writeln!(out, "Applied {} change{}", changes.len(), plural)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::io::{BufRead, Write};
+
+use crate::{
+    Finder,
+    cli::{CliError, Result, batch},
+};
+
+pub fn batch(
+    finder: &Finder,
+    input: &mut dyn BufRead,
+    dry_run: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let mut text = String::new();
+    input.read_to_string(&mut text).map_err(CliError::Input)?;
+
+    let operations = batch::parse(&text)?;
+    if operations.is_empty() {
+        writeln!(out, "Nothing to do")?;
+        return Ok(());
+    }
+
+    if dry_run {
+        finder.plan(&operations)?;
+        for operation in &operations {
+            writeln!(out, "Would {}", operation)?;
+        }
+    } else {
+        finder.batch(&operations)?;
+        let plural = if operations.len() == 1 { "" } else { "s" };
+        writeln!(out, "Applied {} operation{}", operations.len(), plural)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    net::Shutdown,
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::{self, Scope},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Finder,
+    cli::{
+        CliError, Result,
+        daemon::{ClientId, Daemon},
+    },
+};
+
+/// How long to wait for requests before looking for new connections.
+const TICK: Duration = Duration::from_millis(50);
+
+/// `favkit/favkit.sock` in the user's runtime directory, or their cache directory without one.
+///
+/// The temporary directory is only used when neither is known.
+pub fn default_socket() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("favkit")
+        .join("favkit.sock")
+}
+
+/// Removes `socket` if it is a socket, leaving anything else at that path alone.
+fn remove_socket(socket: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(socket) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(socket),
+        Ok(_) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Binds `socket` so only the current user can connect, creating its directory just for them.
+fn bind(socket: &Path) -> io::Result<UnixListener> {
+    if let Some(dir) = socket.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+    // Left behind by a daemon that did not shut down cleanly
+    remove_socket(socket)?;
+    let listener = UnixListener::bind(socket)?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+enum Event {
+    Line(ClientId, io::Result<String>),
+    Closed(ClientId),
+}
+
+/// Connected clients, written to from the daemon loop and read on a thread each.
+struct Clients {
+    streams: HashMap<ClientId, UnixStream>,
+    next: ClientId,
+}
+
+impl Clients {
+    fn accept<'scope>(
+        &mut self,
+        scope: &'scope Scope<'scope, '_>,
+        listener: &UnixListener,
+        events: &Sender<Event>,
+    ) -> io::Result<()> {
+        loop {
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(error) => return Err(error),
+            };
+            stream.set_nonblocking(false)?;
+            let client = self.next;
+            self.next += 1;
+            self.streams.insert(client, stream.try_clone()?);
+
+            let events = events.clone();
+            scope.spawn(move || {
+                for line in BufReader::new(stream).lines() {
+                    if events.send(Event::Line(client, line)).is_err() {
+                        return;
+                    }
+                }
+                let _ = events.send(Event::Closed(client));
+            });
+        }
+    }
+
+    /// Sends each message to its client, dropping clients that cannot be written to.
+    fn send(&mut self, daemon: &mut Daemon, messages: Vec<(ClientId, serde_json::Value)>) {
+        for (client, message) in messages {
+            let Some(stream) = self.streams.get_mut(&client) else {
+                continue;
+            };
+            if writeln!(stream, "{}", message).is_err() {
+                self.close(daemon, client);
+            }
+        }
+    }
+
+    fn close(&mut self, daemon: &mut Daemon, client: ClientId) {
+        if let Some(stream) = self.streams.remove(&client) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        daemon.disconnect(client);
+    }
+
+    /// Disconnects everyone, which ends the reader threads.
+    fn close_all(&mut self) {
+        for (_, stream) in self.streams.drain() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Serves JSON-RPC requests from clients connecting to `socket` until one asks for `shutdown`.
+///
+/// The sidebar is checked every `poll` for changes and subscribers are sent what changed.
+pub fn daemon(finder: &Finder, socket: &Path, poll: Duration, out: &mut dyn Write) -> Result<()> {
+    let listen_error = |source| CliError::Listen {
+        path: socket.to_path_buf(),
+        source,
+    };
+    if UnixStream::connect(socket).is_ok() {
+        return Err(listen_error(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "another daemon is already listening",
+        )));
+    }
+    let mut daemon = Daemon::new(finder)?;
+
+    let listener = bind(socket).map_err(listen_error)?;
+    writeln!(out, "Listening on {}", socket.display())?;
+    out.flush()?;
+
+    let mut clients = Clients {
+        streams: HashMap::new(),
+        next: 0,
+    };
+    let result = thread::scope(|scope| {
+        let result = serve_clients(scope, &listener, &mut daemon, &mut clients, poll);
+        clients.close_all();
+        result
+    });
+    let _ = remove_socket(socket);
+    result.map_err(listen_error)
+}
+
+fn serve_clients<'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    listener: &UnixListener,
+    daemon: &mut Daemon,
+    clients: &mut Clients,
+    poll: Duration,
+) -> io::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut checked = Instant::now();
+
+    while daemon.is_running() {
+        clients.accept(scope, listener, &sender)?;
+
+        let messages = match receiver.recv_timeout(TICK) {
+            Ok(Event::Line(_, Ok(line))) if line.trim().is_empty() => Vec::new(),
+            Ok(Event::Line(client, Ok(line))) => daemon.handle(client, &line),
+            Ok(Event::Line(client, Err(_)) | Event::Closed(client)) => {
+                clients.close(daemon, client);
+                Vec::new()
+            }
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => Vec::new(),
+        };
+        clients.send(daemon, messages);
+
+        // Checked on every pass, so a steady stream of requests cannot hold back notifications
+        if daemon.is_running() && checked.elapsed() >= poll {
+            checked = Instant::now();
+            let changes = daemon.refresh();
+            clients.send(daemon, changes);
+        }
+    }
+    Ok(())
+}
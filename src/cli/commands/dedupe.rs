@@ -0,0 +1,25 @@
+use std::io::Write;
+
+use crate::{Finder, cli::Result, finder::DedupePolicy};
+
+pub fn dedupe(
+    finder: &Finder,
+    policy: &DedupePolicy,
+    dry_run: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let duplicates = if dry_run {
+        finder.duplicates(policy)?
+    } else {
+        finder.dedupe(policy)?
+    };
+    if duplicates.is_empty() {
+        writeln!(out, "No duplicates found")?;
+    }
+
+    let action = if dry_run { "Would remove" } else { "Removed" };
+    for item in duplicates {
+        writeln!(out, "{} {}", action, item)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use std::{io::Write, process::ExitCode};
+
+use crate::{
+    Finder,
+    cli::{PROBLEMS_FOUND, Result},
+    finder::doctor::Severity,
+};
+
+pub fn doctor(finder: &Finder, strict: bool, out: &mut dyn Write) -> Result<ExitCode> {
+    let report = finder.doctor()?;
+    write!(out, "{}", report)?;
+
+    let failing = if strict {
+        Severity::Warning
+    } else {
+        Severity::Error
+    };
+    let code = match report.worst() {
+        Some(worst) if worst >= failing => ExitCode::from(PROBLEMS_FOUND),
+        _ => ExitCode::SUCCESS,
+    };
+    Ok(code)
+}
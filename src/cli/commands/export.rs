@@ -0,0 +1,29 @@
+use std::io::Write;
+
+use crate::{
+    Finder,
+    cli::{ExportFormat, Result, manifest, mobileconfig},
+    finder::Manifest,
+};
+
+/// Writes the manifest, or the current sidebar when there is none, in the requested format.
+pub fn export(
+    finder: &Finder,
+    manifest: Option<&Manifest>,
+    format: ExportFormat,
+    identifier: &str,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let items = match manifest {
+        Some(manifest) => manifest.items().to_vec(),
+        None => finder.get_favorites_list()?,
+    };
+
+    match format {
+        ExportFormat::Manifest => write!(out, "{}", manifest::to_toml(&Manifest::new(items)))?,
+        ExportFormat::Mobileconfig => {
+            write!(out, "{}", mobileconfig::profile(&items, identifier)?)?
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use std::io::Write;
+
+use crate::{Finder, cli::Result};
+
+pub fn undo(finder: &Finder, force: bool, out: &mut dyn Write) -> Result<()> {
+    match finder.undo(force)? {
+        Some(entry) => writeln!(out, "Undid {}", entry.operation)?,
+        None => writeln!(out, "Nothing to undo")?,
+    }
+    Ok(())
+}
+
+pub fn redo(finder: &Finder, force: bool, out: &mut dyn Write) -> Result<()> {
+    match finder.redo(force)? {
+        Some(entry) => writeln!(out, "Redid {}", entry.operation)?,
+        None => writeln!(out, "Nothing to redo")?,
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::io::Write;
+
+use crate::{
+    Finder,
+    cli::{Result, Template},
+    finder::{Filter, SidebarItem, Target, batch},
+};
+
+/// Prints the items the filter selects, numbered by their position in the whole sidebar.
+///
+/// With `abbreviate_home`, paths in the home directory are printed as `~/…`.
+pub fn list(
+    finder: &Finder,
+    template: Option<&Template>,
+    filter: &Filter,
+    abbreviate_home: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let items = finder.get_favorites_list()?;
+    let selected = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| filter.matches(item));
+
+    for (index, item) in selected {
+        let item = if abbreviate_home {
+            abbreviated(item)
+        } else {
+            item.clone()
+        };
+        match template {
+            Some(template) => writeln!(out, "{}", template.render(index + 1, &item))?,
+            None => writeln!(out, "{}", item)?,
+        }
+    }
+    Ok(())
+}
+
+fn abbreviated(item: &SidebarItem) -> SidebarItem {
+    let Target::Custom { label, .. } = item.target() else {
+        return item.clone();
+    };
+    let Some(location) = batch::location(item.target()) else {
+        return item.clone();
+    };
+    let abbreviated = SidebarItem::new(Target::custom(label, location))
+        .with_visibility(item.visibility())
+        .with_ownership(item.ownership());
+    match item.id() {
+        Some(id) => abbreviated.with_id(id),
+        None => abbreviated,
+    }
+}
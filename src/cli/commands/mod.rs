@@ -0,0 +1,37 @@
+mod activate;
+mod apply;
+mod batch;
+mod daemon;
+mod dedupe;
+mod doctor;
+mod export;
+mod history;
+mod list;
+mod module;
+mod mysides;
+mod profile;
+mod prune;
+mod remove;
+mod serve;
+mod sort;
+mod validate;
+mod visibility;
+
+pub use activate::activate;
+pub use apply::apply;
+pub use batch::batch;
+pub use daemon::{daemon, default_socket};
+pub use dedupe::dedupe;
+pub use doctor::doctor;
+pub use export::export;
+pub use history::{redo, undo};
+pub use list::list;
+pub use module::module;
+pub use mysides::mysides;
+pub use profile::profile;
+pub use prune::prune;
+pub use remove::remove;
+pub use serve::serve;
+pub use sort::sort;
+pub use validate::{schema, validate};
+pub use visibility::{hide, show};
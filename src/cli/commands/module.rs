@@ -0,0 +1,45 @@
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use serde_json::json;
+
+use crate::{
+    Finder,
+    cli::{CliError, Result, module},
+};
+
+/// Runs the module with arguments from `args` or `input` and prints its JSON result.
+///
+/// Module failures are reported the way config management tools expect, as
+/// `{"failed": true, "msg": ...}` on stdout with exit status 1.
+pub fn module(
+    finder: &Finder,
+    args: Option<&Path>,
+    input: &mut dyn BufRead,
+    out: &mut dyn Write,
+) -> Result<ExitCode> {
+    let arguments = match args {
+        Some(path) => fs::read_to_string(path).map_err(CliError::Input)?,
+        None => {
+            let mut text = String::new();
+            input.read_to_string(&mut text).map_err(CliError::Input)?;
+            text
+        }
+    };
+
+    match module::execute(finder, &arguments) {
+        Ok(result) => {
+            writeln!(out, "{}", json!(result))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(error) => {
+            let failure = json!({"changed": false, "failed": true, "msg": error.to_string()});
+            writeln!(out, "{}", failure)?;
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Drop-in replacement for the `mysides` tool.
+//!
+//! Accepts exactly the arguments `mysides` did and reproduces its output and exit codes, so
+//! scripts written against it keep working unchanged.
+
+use std::{io::Write, process::ExitCode};
+
+use crate::{
+    Finder,
+    cli::Result,
+    finder::{Position, Selector, SidebarItem},
+    system::favorites::MacOsUrl,
+};
+
+const USAGE: &str = "Usage: mysides [list|add <name> <uri>|remove <name>]";
+
+/// What `mysides` printed for items it could not resolve to a URL.
+const NULL_URL: &str = "(null)";
+
+pub fn mysides(finder: &Finder, args: &[String], out: &mut dyn Write) -> Result<ExitCode> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        ["list"] => list(finder, out),
+        ["add", name, uri] => add(finder, name, uri, out),
+        ["remove", name] => remove(finder, name, out),
+        _ => {
+            writeln!(out, "{}", USAGE)?;
+            return Ok(ExitCode::FAILURE);
+        }
+    }?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn list(finder: &Finder, out: &mut dyn Write) -> Result<()> {
+    for item in finder.get_favorites_list()? {
+        let url = MacOsUrl::try_from(item.target())
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| NULL_URL.to_string());
+        writeln!(out, "{} -> {}", item.target().label(), url)?;
+    }
+    Ok(())
+}
+
+fn add(finder: &Finder, name: &str, uri: &str, out: &mut dyn Write) -> Result<()> {
+    let target = MacOsUrl::from(uri).into_target(name);
+    finder.add(target, Position::Last)?;
+    writeln!(out, "Added sidebar item with name: {}", name)?;
+    Ok(())
+}
+
+/// Removes every item with the given name, like `mysides` did.
+fn remove(finder: &Finder, name: &str, out: &mut dyn Write) -> Result<()> {
+    let ids: Vec<_> = finder
+        .get_favorites_list()?
+        .iter()
+        .filter(|item| item.target().label() == name)
+        .filter_map(SidebarItem::id)
+        .collect();
+
+    for id in ids {
+        finder.remove(&Selector::Id(id))?;
+        writeln!(out, "Removed sidebar item with name: {}", name)?;
+    }
+    Ok(())
+}
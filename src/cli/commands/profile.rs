@@ -0,0 +1,55 @@
+use std::io::Write;
+
+use super::apply;
+use crate::{
+    Finder,
+    cli::{ProfileCommand, ProfileError, Profiles, Result, profile::is_unresolved},
+    finder::{Change, Manifest, Scope},
+};
+
+pub fn profile(
+    finder: &Finder,
+    profiles: &Profiles,
+    command: ProfileCommand,
+    out: &mut dyn Write,
+) -> Result<()> {
+    match command {
+        ProfileCommand::Save { name } => {
+            let path = profiles.save(&name, &finder.get_favorites_list()?)?;
+            writeln!(out, "Saved profile {} to {}", name, path.display())?;
+        }
+        ProfileCommand::Use { name, dry_run } => {
+            let manifest = Manifest::new(profiles.load(&name)?);
+            // Unresolved items cannot be matched to the profile, which would remove them
+            let removes_unresolved = finder
+                .apply_changes(&manifest, Scope::All)?
+                .into_iter()
+                .find_map(|change| match change {
+                    Change::Removed { item } if is_unresolved(&item) => Some(item),
+                    _ => None,
+                });
+            if let Some(item) = removes_unresolved {
+                let label = item.target().label().to_string();
+                return Err(ProfileError::Unresolved(label).into());
+            }
+            apply(finder, &manifest, Scope::All, dry_run, out)?;
+        }
+        ProfileCommand::List => {
+            let names = profiles.names()?;
+            if names.is_empty() {
+                writeln!(out, "No profiles in {}", profiles.dir().display())?;
+                return Ok(());
+            }
+            let current = finder.get_favorites_list()?;
+            for name in names {
+                let manifest = Manifest::new(profiles.load(&name)?);
+                if manifest.changes(&current, Scope::All).is_empty() {
+                    writeln!(out, "{} (current)", name)?;
+                } else {
+                    writeln!(out, "{}", name)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
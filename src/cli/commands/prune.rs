@@ -0,0 +1,35 @@
+use std::io::Write;
+
+use crate::{Finder, cli::Result, finder::PruneOptions};
+
+pub fn prune(
+    finder: &Finder,
+    dry_run: bool,
+    keep_network: bool,
+    managed_only: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let options = PruneOptions::default();
+    let options = if dry_run { options.dry_run() } else { options };
+    let options = if keep_network {
+        options.keep_network()
+    } else {
+        options
+    };
+    let options = if managed_only {
+        options.managed_only()
+    } else {
+        options
+    };
+
+    let stale = finder.prune(options)?;
+    if stale.is_empty() {
+        writeln!(out, "Nothing to prune")?;
+    }
+
+    let action = if dry_run { "Would remove" } else { "Removed" };
+    for item in stale {
+        writeln!(out, "{} {}", action, item)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use std::io::Write;
+
+use crate::{
+    Finder,
+    cli::Result,
+    finder::{Filter, Selector},
+};
+
+/// Removes the item named by `selector` if given, further narrowed by the filter, or else every
+/// item the filter selects.
+pub fn remove(
+    finder: &Finder,
+    selector: Option<&Selector>,
+    filter: &Filter,
+    dry_run: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let removed = match (selector, dry_run) {
+        (Some(selector), _) => {
+            let item = finder.find_item(selector)?;
+            let selected = filter.matches(&item);
+            if selected && !dry_run {
+                finder.remove(selector)?;
+            }
+            selected.then_some(item).into_iter().collect()
+        }
+        (None, true) => finder.find_items(filter)?,
+        (None, false) => finder.remove_matching(filter)?,
+    };
+
+    if removed.is_empty() {
+        writeln!(out, "No matching items")?;
+    }
+
+    let action = if dry_run { "Would remove" } else { "Removed" };
+    for item in removed {
+        writeln!(out, "{} {}", action, item)?;
+    }
+    Ok(())
+}
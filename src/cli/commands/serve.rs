@@ -0,0 +1,54 @@
+use std::{
+    io::{BufRead, Write},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    Finder,
+    cli::{CliError, Result, serve::Server},
+};
+
+/// Serves JSON-RPC requests read line by line until the input ends.
+///
+/// Input is read on a separate thread so that, while idle, the sidebar is checked every
+/// `poll` for changes made by others and watching clients are notified. The thread is detached:
+/// failing to write a reply returns at once instead of waiting for the input to end, and the
+/// thread stops after the next line it cannot hand over.
+pub fn serve(
+    finder: &Finder,
+    input: impl BufRead + Send + 'static,
+    poll: Duration,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let mut server = Server::new(finder);
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in input.lines() {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        let messages = match receiver.recv_timeout(poll) {
+            Ok(line) => {
+                let line = line.map_err(CliError::Input)?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                server.handle(&line)
+            }
+            Err(RecvTimeoutError::Timeout) => server.poll().into_iter().collect(),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+
+        for message in messages {
+            writeln!(out, "{}", message)?;
+        }
+        out.flush()?;
+    }
+}
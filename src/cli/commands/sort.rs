@@ -0,0 +1,20 @@
+use std::io::Write;
+
+use crate::{Finder, cli::Result, finder::SortOrder};
+
+pub fn sort(finder: &Finder, order: &SortOrder, dry_run: bool, out: &mut dyn Write) -> Result<()> {
+    let moves = if dry_run {
+        finder.sort_moves(order)?
+    } else {
+        finder.sort(order)?
+    };
+    if moves.is_empty() {
+        writeln!(out, "Already sorted")?;
+    }
+
+    let action = if dry_run { "Would move" } else { "Moved" };
+    for step in moves {
+        writeln!(out, "{} {}", action, step)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use std::{
+    io::{BufRead, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use crate::cli::{PROBLEMS_FOUND, Result, SCHEMA, expand::Variables, manifest, validate};
+
+pub fn validate(path: &Path, input: &mut dyn BufRead, out: &mut dyn Write) -> Result<ExitCode> {
+    let text = manifest::read_text(path, input)?;
+    let diagnostics = validate::validate(&text, path, &Variables::from_env());
+    if diagnostics.is_empty() {
+        writeln!(out, "{} is valid", path.display())?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    for diagnostic in &diagnostics {
+        writeln!(out, "{}", diagnostic)?;
+    }
+    let plural = if diagnostics.len() == 1 { "" } else { "s" };
+    writeln!(out, "{} problem{} found", diagnostics.len(), plural)?;
+    Ok(ExitCode::from(PROBLEMS_FOUND))
+}
+
+pub fn schema(out: &mut dyn Write) -> Result<()> {
+    write!(out, "{}", SCHEMA)?;
+    Ok(())
+}
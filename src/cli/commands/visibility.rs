@@ -0,0 +1,9 @@
+use crate::{Finder, cli::Result, finder::Selector};
+
+pub fn hide(finder: &Finder, selector: &Selector) -> Result<()> {
+    Ok(finder.hide(selector)?)
+}
+
+pub fn show(finder: &Finder, selector: &Selector) -> Result<()> {
+    Ok(finder.show(selector)?)
+}
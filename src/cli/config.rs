@@ -0,0 +1,497 @@
+//! favkit's configuration file, and how it combines with environment variables and flags.
+//!
+//! The file lives at `favkit/config.toml` in `$XDG_CONFIG_HOME`, or in `~/.config` when that is
+//! not set; `--config` or `FAVKIT_CONFIG` name another one. Every setting is optional:
+//!
+//! ```toml
+//! [output]
+//! template = "{label}\t{path|url|kind}"   # default `list` template
+//! abbreviate-home = true                  # list paths in the home directory as ~/…
+//!
+//! [resolution]
+//! mount = true                            # like --mount; false is like --no-mount
+//! ui = false                              # like --no-ui; true is like --allow-ui
+//!
+//! [journal]
+//! dir = "~/Backups/favkit"                # where the undo journal backs up each change
+//! retention = 200                         # entries to keep
+//!
+//! [manifest]
+//! path = "~/.config/favkit/sidebar.toml"  # default for apply and activate
+//! profiles = "~/Sync/favkit/profiles"     # where profiles are stored
+//! ```
+//!
+//! Each setting is taken from the first layer that has it: command-line flags, then `FAVKIT_*`
+//! environment variables (`FAVKIT_TEMPLATE`, `FAVKIT_ABBREVIATE_HOME`, `FAVKIT_MOUNT`,
+//! `FAVKIT_UI`, `FAVKIT_JOURNAL_DIR`, `FAVKIT_JOURNAL_RETENTION`, `FAVKIT_MANIFEST`,
+//! `FAVKIT_PROFILES`), then the file, then the built-in defaults.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{Cli, Template};
+use crate::{
+    finder::{ResolutionOptions, path::expand_home},
+    system::FileJournal,
+};
+
+/// Environment variable naming the config file.
+pub const CONFIG_ENV: &str = "FAVKIT_CONFIG";
+
+/// Prefix of the environment variables that override settings.
+const ENV_PREFIX: &str = "FAVKIT_";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConfigError {
+    #[error("failed to read config {path}: {reason}")]
+    Io { path: PathBuf, reason: String },
+    #[error("invalid config {path}: {reason}")]
+    Invalid { path: PathBuf, reason: String },
+    #[error("invalid value for {name}: {reason}")]
+    Env { name: String, reason: String },
+}
+
+/// favkit settings; unset ones fall back to the next layer and finally to the defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub template: Option<Template>,
+    pub abbreviate_home: Option<bool>,
+    pub mount: Option<bool>,
+    pub ui: Option<bool>,
+    pub journal_dir: Option<PathBuf>,
+    pub journal_retention: Option<usize>,
+    pub manifest: Option<PathBuf>,
+    pub profiles: Option<PathBuf>,
+}
+
+/// `favkit` in `$XDG_CONFIG_HOME`, or in `~/.config` when it is not set.
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .map(|dir| dir.join("favkit"))
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Combines the layers: flags from `cli`, then the environment `vars`, then the config file
+    /// named by `--config` or `FAVKIT_CONFIG`, or else the default one if it exists.
+    pub fn load(
+        cli: &Cli,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let vars: Vec<(String, String)> = vars.into_iter().collect();
+        let named = cli.config.clone().or_else(|| {
+            vars.iter()
+                .find(|(name, _)| name == CONFIG_ENV)
+                .map(|(_, value)| PathBuf::from(value))
+        });
+        let file = match named {
+            Some(path) => Self::read(&path)?,
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::read(&path)?,
+                _ => Self::default(),
+            },
+        };
+        Ok(file.merge(Self::from_env(vars)?).merge(Self::from(cli)))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|error: io::Error| ConfigError::Io {
+            path: path.to_path_buf(),
+            reason: error.to_string(),
+        })?;
+        Self::parse(&text, path)
+    }
+
+    /// Parses a config file; `path` only names it in errors.
+    pub fn parse(text: &str, path: &Path) -> Result<Self, ConfigError> {
+        let invalid = |reason: String| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let record: ConfigRecord =
+            toml::from_str(text).map_err(|error| invalid(error.message().to_string()))?;
+        let template = record
+            .output
+            .template
+            .map(|template| template.parse())
+            .transpose()
+            .map_err(|error| invalid(format!("output.template: {}", error)))?;
+
+        Ok(Self {
+            template,
+            abbreviate_home: record.output.abbreviate_home,
+            mount: record.resolution.mount,
+            ui: record.resolution.ui,
+            journal_dir: record
+                .journal
+                .dir
+                .as_deref()
+                .map(expand_home)
+                .map(PathBuf::from),
+            journal_retention: record.journal.retention,
+            manifest: record
+                .manifest
+                .path
+                .as_deref()
+                .map(expand_home)
+                .map(PathBuf::from),
+            profiles: record
+                .manifest
+                .profiles
+                .as_deref()
+                .map(expand_home)
+                .map(PathBuf::from),
+        })
+    }
+
+    /// Reads the `FAVKIT_*` variables, ignoring every other one.
+    pub fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let invalid = |reason: String| ConfigError::Env {
+                name: name.clone(),
+                reason,
+            };
+            match key {
+                "TEMPLATE" => {
+                    config.template = Some(
+                        value
+                            .parse()
+                            .map_err(|error| invalid(format!("{}", error)))?,
+                    )
+                }
+                "ABBREVIATE_HOME" => config.abbreviate_home = Some(flag(&value).map_err(invalid)?),
+                "MOUNT" => config.mount = Some(flag(&value).map_err(invalid)?),
+                "UI" => config.ui = Some(flag(&value).map_err(invalid)?),
+                "JOURNAL_DIR" => config.journal_dir = Some(expand_home(&value).into()),
+                "JOURNAL_RETENTION" => {
+                    config.journal_retention = Some(
+                        value
+                            .parse()
+                            .map_err(|_| invalid(format!("expected a number, got '{}'", value)))?,
+                    )
+                }
+                "MANIFEST" => config.manifest = Some(expand_home(&value).into()),
+                "PROFILES" => config.profiles = Some(expand_home(&value).into()),
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+
+    /// Layers `over` on top: its settings win, the ones it leaves unset come from `self`.
+    pub fn merge(self, over: Self) -> Self {
+        Self {
+            template: over.template.or(self.template),
+            abbreviate_home: over.abbreviate_home.or(self.abbreviate_home),
+            mount: over.mount.or(self.mount),
+            ui: over.ui.or(self.ui),
+            journal_dir: over.journal_dir.or(self.journal_dir),
+            journal_retention: over.journal_retention.or(self.journal_retention),
+            manifest: over.manifest.or(self.manifest),
+            profiles: over.profiles.or(self.profiles),
+        }
+    }
+
+    /// Never mounts volumes or shows UI unless configured otherwise, like the flags.
+    pub fn resolution(&self) -> ResolutionOptions {
+        let options = ResolutionOptions::default();
+        let options = if self.ui.unwrap_or(false) {
+            options
+        } else {
+            options.without_user_interaction()
+        };
+        if self.mount.unwrap_or(false) {
+            options
+        } else {
+            options.without_mounting()
+        }
+    }
+
+    pub fn abbreviates_home(&self) -> bool {
+        self.abbreviate_home.unwrap_or(false)
+    }
+
+    /// The journal file in the configured directory, or in the default one.
+    pub fn journal_path(&self) -> Option<PathBuf> {
+        match &self.journal_dir {
+            Some(dir) => Some(dir.join("journal.jsonl")),
+            None => FileJournal::default_path(),
+        }
+    }
+
+    pub fn profiles_dir(&self) -> Option<PathBuf> {
+        self.profiles
+            .clone()
+            .or_else(|| config_dir().map(|dir| dir.join("profiles")))
+    }
+}
+
+/// Settings given as global flags.
+impl From<&Cli> for Config {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            mount: if cli.mount {
+                Some(true)
+            } else {
+                cli.no_mount.then_some(false)
+            },
+            ui: if cli.allow_ui {
+                Some(true)
+            } else {
+                cli.no_ui.then_some(false)
+            },
+            ..Self::default()
+        }
+    }
+}
+
+fn flag(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(format!("expected true or false, got '{}'", value)),
+    }
+}
+
+/// Serialized form of a [`Config`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigRecord {
+    output: OutputRecord,
+    resolution: ResolutionRecord,
+    journal: JournalRecord,
+    manifest: ManifestRecord,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct OutputRecord {
+    template: Option<String>,
+    abbreviate_home: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ResolutionRecord {
+    mount: Option<bool>,
+    ui: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct JournalRecord {
+    dir: Option<String>,
+    retention: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ManifestRecord {
+    path: Option<String>,
+    profiles: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn file() -> Config {
+        Config::parse(
+            r#"
+                [output]
+                template = "{label}"
+                abbreviate-home = true
+
+                [resolution]
+                mount = false
+
+                [journal]
+                dir = "/var/backups/favkit"
+                retention = 10
+
+                [manifest]
+                path = "/etc/favkit/sidebar.toml"
+            "#,
+            Path::new("config.toml"),
+        )
+        .unwrap()
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        Config::from_env(
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        )
+    }
+
+    fn cli(args: &[&str]) -> Cli {
+        Cli::parse_from(std::iter::once("favkit").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn should_parse_every_section() {
+        assert_eq!(
+            file(),
+            Config {
+                template: Some("{label}".parse().unwrap()),
+                abbreviate_home: Some(true),
+                mount: Some(false),
+                ui: None,
+                journal_dir: Some(PathBuf::from("/var/backups/favkit")),
+                journal_retention: Some(10),
+                manifest: Some(PathBuf::from("/etc/favkit/sidebar.toml")),
+                profiles: None,
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_keys_and_bad_templates() {
+        for text in [
+            "[output]\ncolour = true\n",
+            "[output]\ntemplate = \"{nope}\"\n",
+        ] {
+            assert!(matches!(
+                Config::parse(text, Path::new("config.toml")),
+                Err(ConfigError::Invalid { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn should_read_favkit_variables_only() {
+        // Act
+        let config = env(&[
+            ("FAVKIT_UI", "1"),
+            ("FAVKIT_JOURNAL_RETENTION", "5"),
+            ("FAVKIT_PROFILES", "/profiles"),
+            ("HOME", "/Users/user"),
+        ]);
+
+        // Assert
+        assert_eq!(
+            config,
+            Ok(Config {
+                ui: Some(true),
+                journal_retention: Some(5),
+                profiles: Some(PathBuf::from("/profiles")),
+                ..Config::default()
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_variables() {
+        assert_eq!(
+            env(&[("FAVKIT_MOUNT", "maybe")]),
+            Err(ConfigError::Env {
+                name: "FAVKIT_MOUNT".to_string(),
+                reason: "expected true or false, got 'maybe'".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn should_prefer_flags_then_environment_then_file() {
+        // Arrange
+        let env = env(&[
+            ("FAVKIT_MOUNT", "true"),
+            ("FAVKIT_UI", "true"),
+            ("FAVKIT_JOURNAL_RETENTION", "3"),
+        ])
+        .unwrap();
+
+        // Act
+        let config = file().merge(env).merge(Config::from(&cli(&["--no-ui"])));
+
+        // Assert
+        assert_eq!(config.mount, Some(true));
+        assert_eq!(config.ui, Some(false));
+        assert_eq!(config.journal_retention, Some(3));
+        assert_eq!(config.abbreviate_home, Some(true));
+        assert_eq!(
+            config.resolution(),
+            ResolutionOptions::default().without_user_interaction()
+        );
+    }
+
+    #[test]
+    fn should_leave_settings_to_lower_layers_without_flags() {
+        // Act
+        let config = file().merge(Config::from(&cli(&["list"])));
+
+        // Assert
+        assert_eq!(config, file());
+        assert_eq!(config.resolution(), ResolutionOptions::non_interactive());
+    }
+
+    #[test]
+    fn should_default_like_the_flags() {
+        // Arrange
+        let config = Config::default();
+
+        // Assert
+        assert_eq!(
+            config.resolution(),
+            Config::from(&cli(&["list"])).resolution()
+        );
+        assert!(!config.abbreviates_home());
+        assert_eq!(config.journal_path(), FileJournal::default_path());
+    }
+
+    #[test]
+    fn should_read_file_named_on_command_line() {
+        // Arrange
+        let dir = TempDir::new("config");
+        let path = dir.join("config.toml");
+        fs::write(&path, "[journal]\nretention = 7\n").unwrap();
+        let cli = cli(&["--config", path.to_str().unwrap(), "list"]);
+
+        // Act
+        let config = Config::load(&cli, [("FAVKIT_TEMPLATE".to_string(), "{id}".to_string())]);
+
+        // Assert
+        assert_eq!(
+            config,
+            Ok(Config {
+                template: Some("{id}".parse().unwrap()),
+                journal_retention: Some(7),
+                ..Config::default()
+            })
+        );
+    }
+
+    #[test]
+    fn should_fail_for_missing_named_file() {
+        // Act
+        let config = Config::load(
+            &cli(&["list"]),
+            [(
+                CONFIG_ENV.to_string(),
+                "/nonexistent/favkit.toml".to_string(),
+            )],
+        );
+
+        // Assert
+        assert!(matches!(config, Err(ConfigError::Io { .. })));
+    }
+}
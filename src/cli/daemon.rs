@@ -0,0 +1,173 @@
+//! Long-running JSON-RPC service sharing one cached copy of the sidebar between clients.
+//!
+//! Listing the sidebar resolves every item's display name and URL, while reading its seed only
+//! copies the snapshot. The daemon therefore lists the sidebar once, re-lists it only when the
+//! seed changes, and answers queries from memory:
+//!
+//! - `list {where?, match?}` returns cached items, like `serve`
+//! - `seed` returns the cached seed
+//! - `subscribe` returns the cached `{seed, items}` and turns on `changed` notifications
+//!   carrying `{seed, changes}`, where each change is `added {index, item}`, `removed {item}`,
+//!   `moved {index, item}` or `updated {before, after}`; `unsubscribe` turns them off
+//! - `add`, `remove`, `move` and `rename` change the sidebar, like `serve`
+//! - `shutdown` stops the daemon
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use super::serve::{
+    ItemView, ListParams, RpcError, edit, filter, items, notification, parse, request, response,
+};
+use crate::{
+    Finder,
+    finder::{Change, FinderError, Seed, SidebarItem, diff},
+};
+
+/// Identifies a connected client.
+pub type ClientId = usize;
+
+/// How a change is sent to subscribers.
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+enum ChangeView<'a> {
+    Added {
+        index: usize,
+        item: ItemView<'a>,
+    },
+    Removed {
+        item: ItemView<'a>,
+    },
+    Moved {
+        index: usize,
+        item: ItemView<'a>,
+    },
+    Updated {
+        before: ItemView<'a>,
+        after: ItemView<'a>,
+    },
+}
+
+impl<'a> From<&'a Change> for ChangeView<'a> {
+    fn from(change: &'a Change) -> Self {
+        match change {
+            Change::Added { index, item } => Self::Added {
+                index: *index,
+                item: item.into(),
+            },
+            Change::Removed { item } => Self::Removed { item: item.into() },
+            Change::Moved { index, item } => Self::Moved {
+                index: *index,
+                item: item.into(),
+            },
+            Change::Updated { before, after } => Self::Updated {
+                before: before.into(),
+                after: after.into(),
+            },
+        }
+    }
+}
+
+/// Handles requests from any number of clients against a cached sidebar.
+pub struct Daemon<'a> {
+    finder: &'a Finder,
+    items: Vec<SidebarItem>,
+    seed: Seed,
+    subscribers: BTreeSet<ClientId>,
+    running: bool,
+}
+
+impl<'a> Daemon<'a> {
+    /// Lists the sidebar once to fill the cache.
+    pub fn new(finder: &'a Finder) -> Result<Self, FinderError> {
+        let seed = finder.seed()?;
+        let items = finder.get_favorites_list()?;
+        Ok(Self {
+            finder,
+            items,
+            seed,
+            subscribers: BTreeSet::new(),
+            running: true,
+        })
+    }
+
+    /// Whether `shutdown` has been requested.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Handles one request from a client and returns the messages to send, each with its
+    /// recipient: the response, then `changed` notifications if the request modified the sidebar.
+    pub fn handle(&mut self, client: ClientId, line: &str) -> Vec<(ClientId, Value)> {
+        let request = match request(line) {
+            Ok(request) => request,
+            Err(failure) => return vec![(client, failure)],
+        };
+
+        let result = self.call(client, &request.method, request.params);
+        let response = response(request.id, result).map(|response| (client, response));
+        response.into_iter().chain(self.refresh()).collect()
+    }
+
+    /// Re-lists the sidebar if its seed changed, returning a `changed` notification for each
+    /// subscriber.
+    ///
+    /// Errors reading the sidebar keep the cache as it is; the next refresh tries again.
+    pub fn refresh(&mut self) -> Vec<(ClientId, Value)> {
+        let Ok(seed) = self.finder.seed() else {
+            return Vec::new();
+        };
+        if seed == self.seed {
+            return Vec::new();
+        }
+        let Ok(items) = self.finder.get_favorites_list() else {
+            return Vec::new();
+        };
+
+        let changes = diff::changes(&self.items, &items);
+        self.items = items;
+        self.seed = seed;
+        if changes.is_empty() {
+            return Vec::new();
+        }
+
+        let changes: Vec<_> = changes.iter().map(ChangeView::from).collect();
+        let message = notification(
+            "changed",
+            json!({"seed": u32::from(seed), "changes": changes}),
+        );
+        self.subscribers
+            .iter()
+            .map(|&subscriber| (subscriber, message.clone()))
+            .collect()
+    }
+
+    /// Forgets a client that went away.
+    pub fn disconnect(&mut self, client: ClientId) {
+        self.subscribers.remove(&client);
+    }
+
+    fn call(&mut self, client: ClientId, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "list" => {
+                let params: ListParams = parse(params)?;
+                Ok(items(&filter(params)?.apply(&self.items)))
+            }
+            "seed" => Ok(json!(u32::from(self.seed))),
+            "subscribe" => {
+                self.subscribers.insert(client);
+                Ok(json!({"seed": u32::from(self.seed), "items": items(&self.items)}))
+            }
+            "unsubscribe" => {
+                self.subscribers.remove(&client);
+                Ok(Value::Null)
+            }
+            "shutdown" => {
+                self.running = false;
+                Ok(Value::Null)
+            }
+            _ => edit(self.finder, method, params),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+use super::{
+    batch::BatchInputError, config::ConfigError, manifest::ManifestError, profile::ProfileError,
+};
+use crate::{
+    finder::{BatchError, FinderError},
+    system::activation::StateError,
+};
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Finder(#[from] FinderError),
+    #[error(transparent)]
+    Batch(#[from] BatchError),
+    #[error(transparent)]
+    BatchInput(#[from] BatchInputError),
+    #[error(transparent)]
+    Manifest(#[from] ManifestError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Profile(#[from] ProfileError),
+    #[error(transparent)]
+    State(#[from] StateError),
+    #[error("no state directory for activation, pass --state")]
+    NoStateDir,
+    #[error("no config directory for profiles, pass --dir")]
+    NoConfigDir,
+    #[error("no transport given for serve, pass --stdio")]
+    NoTransport,
+    #[error("no manifest given, pass --manifest or set manifest.path in the config")]
+    NoManifest,
+    #[error("invalid identifier {0:?}, expected a reverse-DNS name such as com.example.sidebar")]
+    InvalidIdentifier(String),
+    #[error("failed to listen on {path}: {source}")]
+    Listen { path: PathBuf, source: io::Error },
+    #[error("failed to read input: {0}")]
+    Input(io::Error),
+    #[error("failed to write output: {0}")]
+    Output(#[from] io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, CliError>;
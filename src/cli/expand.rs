@@ -0,0 +1,278 @@
+//! Variables and conditions in manifests.
+//!
+//! Labels, locations and include paths may refer to variables as `${NAME}`, with
+//! `${NAME:-fallback}` for ones that may be unset and `$$` for a literal `$`. `HOSTNAME` is the
+//! machine's host name; every other name is looked up in the environment.
+//!
+//! Items and includes can be limited to some machines with a `when` condition comparing
+//! `hostname`, `user`, `home` or `env.NAME` to a quoted value:
+//!
+//! ```text
+//! hostname matches "build-*"
+//! env.COMPANY == "acme"
+//! user != "ci"
+//! ```
+//!
+//! Both steps are pure functions of the text and the [`Variables`], so they happen before any
+//! item reaches [`Finder`](crate::Finder).
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::finder::Glob;
+
+/// Variable naming the machine's host name.
+const HOSTNAME: &str = "HOSTNAME";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ExpandError {
+    #[error("undefined variable '{0}', use ${{{0}:-fallback}} for optional ones")]
+    Undefined(String),
+    #[error("unterminated variable in '{0}'")]
+    Unterminated(String),
+    #[error("invalid variable name '{0}'")]
+    InvalidName(String),
+    #[error("invalid condition '{condition}': {reason}")]
+    InvalidCondition { condition: String, reason: String },
+}
+
+/// Values that manifests can refer to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variables {
+    hostname: String,
+    values: HashMap<String, String>,
+}
+
+impl Variables {
+    pub fn new(
+        hostname: impl Into<String>,
+        values: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        Self {
+            hostname: hostname.into(),
+            values: values.into_iter().collect(),
+        }
+    }
+
+    /// This machine's host name and environment, with `HOME` filled in when unset.
+    pub fn from_env() -> Self {
+        let hostname = hostname::get()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut variables = Self::new(hostname, std::env::vars());
+        if let Some(home) = dirs::home_dir() {
+            variables
+                .values
+                .entry("HOME".to_string())
+                .or_insert_with(|| home.to_string_lossy().into_owned());
+        }
+        variables
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        if name == HOSTNAME {
+            return Some(&self.hostname);
+        }
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// Replaces the variables in `text`.
+pub fn expand(text: &str, variables: &Variables) -> Result<String, ExpandError> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        if let Some(after) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let Some(body) = rest.strip_prefix('{') else {
+            // A `$` that starts no variable is kept as it is
+            expanded.push('$');
+            continue;
+        };
+        let Some(end) = body.find('}') else {
+            return Err(ExpandError::Unterminated(text.to_string()));
+        };
+
+        let (name, fallback) = match body[..end].split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (&body[..end], None),
+        };
+        if !is_name(name) {
+            return Err(ExpandError::InvalidName(name.to_string()));
+        }
+        match variables.get(name).or(fallback) {
+            Some(value) => expanded.push_str(value),
+            None => return Err(ExpandError::Undefined(name.to_string())),
+        }
+        rest = &body[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Writes `text` so that [`expand`] gives it back unchanged.
+pub fn escape(text: &str) -> String {
+    text.replace('$', "$$")
+}
+
+/// Decides whether a `when` condition holds.
+pub fn evaluate(condition: &str, variables: &Variables) -> Result<bool, ExpandError> {
+    let invalid = |reason: &str| ExpandError::InvalidCondition {
+        condition: condition.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut parts = condition.trim().splitn(3, char::is_whitespace);
+    let (Some(operand), Some(operator), Some(value)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid("expected OPERAND OPERATOR \"VALUE\""));
+    };
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(|| invalid("the value must be quoted"))?;
+
+    let actual = match operand {
+        "hostname" => variables.get(HOSTNAME),
+        "user" => variables.get("USER"),
+        "home" => variables.get("HOME"),
+        _ => match operand.strip_prefix("env.") {
+            Some(name) if is_name(name) => variables.get(name),
+            _ => return Err(invalid("expected hostname, user, home or env.NAME")),
+        },
+    }
+    // Unset variables compare as empty, so `env.CI == ""` holds outside CI
+    .unwrap_or_default();
+
+    match operator {
+        "matches" => Ok(Glob::new(value).matches(actual)),
+        "==" => Ok(actual == value),
+        "!=" => Ok(actual != value),
+        _ => Err(invalid("expected matches, == or !=")),
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn variables() -> Variables {
+        Variables::new(
+            "build-07",
+            [
+                ("HOME".to_string(), "/Users/user".to_string()),
+                ("USER".to_string(), "user".to_string()),
+                ("COMPANY".to_string(), "Acme".to_string()),
+            ],
+        )
+    }
+
+    #[test]
+    fn should_expand_environment_and_host_variables() {
+        // Act
+        let expanded = expand("${HOME}/Work/${COMPANY} on ${HOSTNAME}", &variables());
+
+        // Assert
+        assert_eq!(
+            expanded,
+            Ok("/Users/user/Work/Acme on build-07".to_string())
+        );
+    }
+
+    #[test]
+    fn should_use_fallback_for_unset_variables() {
+        assert_eq!(
+            expand("smb://${NAS:-nas.local}/share", &variables()),
+            Ok("smb://nas.local/share".to_string())
+        );
+    }
+
+    #[test]
+    fn should_report_undefined_variables() {
+        assert_eq!(
+            expand("~/Work/${CLIENT}", &variables()),
+            Err(ExpandError::Undefined("CLIENT".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_keep_escaped_and_lone_dollars() {
+        // Arrange
+        let text = "Costs $5 ${USER}";
+
+        // Act
+        let expanded = expand(&escape(text), &variables());
+
+        // Assert
+        assert_eq!(expanded, Ok(text.to_string()));
+        assert_eq!(expand(text, &variables()), Ok("Costs $5 user".to_string()));
+    }
+
+    #[test]
+    fn should_reject_malformed_variables() {
+        assert_eq!(
+            expand("${HOME", &variables()),
+            Err(ExpandError::Unterminated("${HOME".to_string()))
+        );
+        assert_eq!(
+            expand("${HOME DIR}", &variables()),
+            Err(ExpandError::InvalidName("HOME DIR".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_evaluate_conditions() {
+        // Arrange
+        let cases = [
+            (r#"hostname matches "build-*""#, true),
+            (r#"hostname matches "laptop-*""#, false),
+            (r#"env.COMPANY == "Acme""#, true),
+            (r#"user != "user""#, false),
+            (r#"env.CI == """#, true),
+            (r#"home == "/Users/user""#, true),
+        ];
+
+        // Act & Assert
+        for (condition, expected) in cases {
+            assert_eq!(
+                evaluate(condition, &variables()),
+                Ok(expected),
+                "{}",
+                condition
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_conditions() {
+        for condition in [
+            "hostname",
+            r#"hostname ~ "build-*""#,
+            "hostname == build",
+            r#"shell == "zsh""#,
+        ] {
+            assert!(
+                matches!(
+                    evaluate(condition, &variables()),
+                    Err(ExpandError::InvalidCondition { .. })
+                ),
+                "{}",
+                condition
+            );
+        }
+    }
+}
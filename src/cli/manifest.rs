@@ -0,0 +1,528 @@
+//! TOML manifest files describing a sidebar.
+//!
+//! ```toml
+//! [[include]]
+//! path = "base.toml"
+//!
+//! [[item]]
+//! label = "Applications"
+//! location = "file:///Applications/"
+//!
+//! [[item]]
+//! label = "Projects"
+//! location = "~/Work/${COMPANY}"
+//! hidden = true
+//!
+//! [[item]]
+//! label = "Builds"
+//! location = "smb://${NAS:-nas.local}/builds"
+//! when = 'hostname matches "build-*"'
+//! ```
+//!
+//! Locations are paths (`~` is expanded) or URLs, as accepted by `favkit batch`. Labels,
+//! locations and include paths may use variables, and items and includes may have a `when`
+//! condition, see [`expand`](super::expand). Included manifests are read relative to the file
+//! that includes them and their items come first.
+
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, Visitor},
+    forward_to_deserialize_any,
+};
+use thiserror::Error;
+
+use super::expand::{self, ExpandError, Variables};
+use crate::finder::{Manifest, SidebarItem, Visibility, batch};
+
+/// Path that stands for standard input.
+pub const STDIN: &str = "-";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ManifestError {
+    #[error("failed to read manifest {path}: {reason}")]
+    Io { path: PathBuf, reason: String },
+    #[error("invalid manifest {path}: {reason}")]
+    Invalid { path: PathBuf, reason: String },
+}
+
+/// Serialized form of a [`Manifest`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestRecord {
+    #[serde(default, rename = "include", skip_serializing_if = "Vec::is_empty")]
+    includes: Vec<IncludeRecord>,
+    #[serde(default, rename = "item")]
+    items: Vec<ItemRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IncludeRecord {
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    when: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ItemRecord {
+    label: String,
+    location: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    when: Option<String>,
+}
+
+/// Top-level tables of a manifest.
+pub fn sections() -> &'static [&'static str] {
+    fields::<ManifestRecord>()
+}
+
+/// Keys of an `[[include]]` table.
+pub fn include_keys() -> &'static [&'static str] {
+    fields::<IncludeRecord>()
+}
+
+/// Keys of an `[[item]]` table.
+pub fn item_keys() -> &'static [&'static str] {
+    fields::<ItemRecord>()
+}
+
+/// Names of the fields a record reads, as its derived `Deserialize` declares them.
+fn fields<T: for<'de> Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Deserializer that only notes the fields of the struct asked for, then gives up.
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("expected a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("fields noted"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+impl ItemRecord {
+    /// The item on this machine, if its condition holds.
+    fn expand(&self, variables: &Variables) -> Result<Option<SidebarItem>, ExpandError> {
+        if !holds(self.when.as_deref(), variables)? {
+            return Ok(None);
+        }
+        let label = expand::expand(&self.label, variables)?;
+        let location = expand::expand(&self.location, variables)?;
+        Ok(Some(
+            SidebarItem::new(batch::target(&label, &location))
+                .with_visibility(Visibility::from(self.hidden)),
+        ))
+    }
+}
+
+/// Parses a manifest for this machine; `path` names it in errors and anchors its includes.
+pub fn parse(text: &str, path: &Path) -> Result<Manifest, ManifestError> {
+    parse_with(text, path, &Variables::from_env())
+}
+
+/// Parses a manifest, expanding it with `variables` instead of this machine's.
+pub fn parse_with(
+    text: &str,
+    path: &Path,
+    variables: &Variables,
+) -> Result<Manifest, ManifestError> {
+    let mut items = Vec::new();
+    collect(text, path, variables, &mut vec![identity(path)], &mut items)?;
+    Ok(items.into_iter().collect())
+}
+
+/// Appends the items of a manifest and its includes; `including` lists the files being read, to
+/// catch includes that lead back to one of them.
+fn collect(
+    text: &str,
+    path: &Path,
+    variables: &Variables,
+    including: &mut Vec<PathBuf>,
+    items: &mut Vec<SidebarItem>,
+) -> Result<(), ManifestError> {
+    let invalid = |reason: String| ManifestError::Invalid {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let record: ManifestRecord =
+        toml::from_str(text).map_err(|error| invalid(error.message().to_string()))?;
+
+    for include in &record.includes {
+        if !holds(include.when.as_deref(), variables).map_err(|error| invalid(error.to_string()))? {
+            continue;
+        }
+        let location =
+            expand::expand(&include.path, variables).map_err(|error| invalid(error.to_string()))?;
+        let included = match read_include(path, &location, variables, including) {
+            Ok(included) => included,
+            Err(IncludeError::Cycle(included)) => {
+                return Err(invalid(format!("{} includes itself", included.display())));
+            }
+            Err(IncludeError::Unreadable(included, error)) => {
+                return Err(ManifestError::Io {
+                    path: included,
+                    reason: error.to_string(),
+                });
+            }
+        };
+
+        including.push(included.identity);
+        collect(&included.text, &included.path, variables, including, items)?;
+        including.pop();
+    }
+
+    for item in &record.items {
+        if let Some(item) = item
+            .expand(variables)
+            .map_err(|error| invalid(error.to_string()))?
+        {
+            items.push(item);
+        }
+    }
+    Ok(())
+}
+
+fn holds(when: Option<&str>, variables: &Variables) -> Result<bool, ExpandError> {
+    when.map_or(Ok(true), |condition| expand::evaluate(condition, variables))
+}
+
+/// A manifest read for an include.
+pub struct Included {
+    pub path: PathBuf,
+    /// What the manifest is known by while it is being read.
+    pub identity: PathBuf,
+    pub text: String,
+}
+
+/// Why an include cannot be read.
+#[derive(Debug)]
+pub enum IncludeError {
+    /// The include leads back to a manifest being read.
+    Cycle(PathBuf),
+    Unreadable(PathBuf, io::Error),
+}
+
+/// Reads the manifest an expanded include path names; `including` lists the files being read.
+pub fn read_include(
+    manifest: &Path,
+    include: &str,
+    variables: &Variables,
+    including: &[PathBuf],
+) -> Result<Included, IncludeError> {
+    let path = include_path(manifest, include, variables);
+    let identity = identity(&path);
+    if including.contains(&identity) {
+        return Err(IncludeError::Cycle(path));
+    }
+    match fs::read_to_string(&path) {
+        Ok(text) => Ok(Included {
+            path,
+            identity,
+            text,
+        }),
+        Err(error) => Err(IncludeError::Unreadable(path, error)),
+    }
+}
+
+/// Resolves an include against the directory of the manifest naming it, expanding a leading `~`.
+fn include_path(manifest: &Path, include: &str, variables: &Variables) -> PathBuf {
+    let include = match (include.strip_prefix("~/"), variables.get("HOME")) {
+        (Some(rest), Some(home)) => Path::new(home).join(rest),
+        _ => PathBuf::from(include),
+    };
+    match manifest.parent() {
+        Some(dir) if include.is_relative() => dir.join(include),
+        _ => include,
+    }
+}
+
+/// The path a manifest is known by when looking for include cycles.
+pub fn identity(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Reads a manifest from a file, or from `input` when the path is `-`.
+pub fn read(path: &Path, input: &mut dyn BufRead) -> Result<Manifest, ManifestError> {
+    parse(&read_text(path, input)?, path)
+}
+
+/// Reads the text of a manifest without parsing it.
+pub fn read_text(path: &Path, input: &mut dyn BufRead) -> Result<String, ManifestError> {
+    let io_error = |error: std::io::Error| ManifestError::Io {
+        path: path.to_path_buf(),
+        reason: error.to_string(),
+    };
+    if path == Path::new(STDIN) {
+        let mut text = String::new();
+        input.read_to_string(&mut text).map_err(io_error)?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).map_err(io_error)
+    }
+}
+
+/// Writes a manifest as TOML, with paths under the home directory shortened to `~` and `$`
+/// escaped so nothing reads as a variable.
+///
+/// Items that could not be resolved have no location and are left out.
+pub fn to_toml(manifest: &Manifest) -> String {
+    let items = manifest
+        .items()
+        .iter()
+        .filter_map(|item| {
+            Some(ItemRecord {
+                label: expand::escape(item.target().label()),
+                location: expand::escape(&batch::location(item.target())?),
+                hidden: item.is_hidden(),
+                when: None,
+            })
+        })
+        .collect();
+    toml::to_string(&ManifestRecord {
+        includes: Vec::new(),
+        items,
+    })
+    .expect("manifest records serialize to TOML")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        finder::{ResolutionFailure, Target},
+        testing::TempDir,
+    };
+
+    fn manifest() -> Manifest {
+        Manifest::new([
+            SidebarItem::new(Target::Applications),
+            SidebarItem::new(Target::custom("Projects", "/Users/user/Projects"))
+                .with_visibility(Visibility::Hidden),
+            SidebarItem::new(Target::network("NAS", "smb://nas.local/share")),
+        ])
+    }
+
+    #[test]
+    fn should_parse_items_in_order() {
+        // Arrange
+        let text = r#"
+            [[item]]
+            label = "Applications"
+            location = "file:///Applications/"
+
+            [[item]]
+            label = "Projects"
+            location = "/Users/user/Projects"
+            hidden = true
+
+            [[item]]
+            label = "NAS"
+            location = "smb://nas.local/share"
+        "#;
+
+        // Act
+        let parsed = parse(text, Path::new("manifest.toml"));
+
+        // Assert
+        assert_eq!(parsed, Ok(manifest()));
+    }
+
+    #[test]
+    fn should_round_trip_through_toml() {
+        // Act
+        let text = to_toml(&manifest());
+
+        // Assert
+        assert_eq!(parse(&text, Path::new("-")), Ok(manifest()));
+    }
+
+    #[test]
+    fn should_round_trip_labels_with_dollar_signs() {
+        // Arrange
+        let manifest = Manifest::new([SidebarItem::new(Target::custom(
+            "${HOME} $5",
+            "/Users/user/${Budget}",
+        ))]);
+
+        // Act
+        let text = to_toml(&manifest);
+
+        // Assert
+        assert_eq!(parse(&text, Path::new("-")), Ok(manifest));
+    }
+
+    #[test]
+    fn should_expand_variables_and_skip_items_for_other_hosts() {
+        // Arrange
+        let variables = Variables::new(
+            "build-07",
+            [
+                ("HOME".to_string(), "/Users/user".to_string()),
+                ("COMPANY".to_string(), "Acme".to_string()),
+            ],
+        );
+        let text = r#"
+            [[item]]
+            label = "${COMPANY}"
+            location = "${HOME}/Work/${COMPANY}"
+
+            [[item]]
+            label = "Builds"
+            location = "smb://${NAS:-nas.local}/builds"
+            when = 'hostname matches "build-*"'
+
+            [[item]]
+            label = "Photos"
+            location = "/Users/user/Photos"
+            when = 'hostname == "laptop"'
+        "#;
+
+        // Act
+        let parsed = parse_with(text, Path::new("manifest.toml"), &variables);
+
+        // Assert
+        assert_eq!(
+            parsed,
+            Ok(Manifest::new([
+                SidebarItem::new(Target::custom("Acme", "/Users/user/Work/Acme")),
+                SidebarItem::new(Target::network("Builds", "smb://nas.local/builds")),
+            ]))
+        );
+    }
+
+    #[test]
+    fn should_report_undefined_variables_with_manifest_path() {
+        // Act
+        let parsed = parse_with(
+            "[[item]]\nlabel = \"Client\"\nlocation = \"~/Work/${CLIENT}\"\n",
+            Path::new("manifest.toml"),
+            &Variables::default(),
+        );
+
+        // Assert
+        assert_eq!(
+            parsed,
+            Err(ManifestError::Invalid {
+                path: PathBuf::from("manifest.toml"),
+                reason: ExpandError::Undefined("CLIENT".to_string()).to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn should_put_included_items_first() {
+        // Arrange
+        let dir = TempDir::new("manifest");
+        fs::write(
+            dir.join("base.toml"),
+            "[[item]]\nlabel = \"Applications\"\nlocation = \"file:///Applications/\"\n",
+        )
+        .unwrap();
+        let text = r#"
+            [[include]]
+            path = "base.toml"
+
+            [[include]]
+            path = "build.toml"
+            when = 'hostname matches "build-*"'
+
+            [[item]]
+            label = "NAS"
+            location = "smb://nas.local/share"
+        "#;
+
+        // Act
+        let parsed = parse_with(text, &dir.join("laptop.toml"), &Variables::default());
+
+        // Assert
+        assert_eq!(
+            parsed,
+            Ok(Manifest::new([
+                SidebarItem::new(Target::Applications),
+                SidebarItem::new(Target::network("NAS", "smb://nas.local/share")),
+            ]))
+        );
+    }
+
+    #[test]
+    fn should_name_the_keys_of_each_table() {
+        assert_eq!(sections(), ["include", "item"]);
+        assert_eq!(include_keys(), ["path", "when"]);
+        assert_eq!(item_keys(), ["label", "location", "hidden", "when"]);
+    }
+
+    #[test]
+    fn should_reject_include_cycles() {
+        // Arrange
+        let dir = TempDir::new("manifest");
+        let path = dir.join("sidebar.toml");
+        fs::write(&path, "[[include]]\npath = \"sidebar.toml\"\n").unwrap();
+
+        // Act
+        let parsed = read(&path, &mut "".as_bytes());
+
+        // Assert
+        assert!(matches!(
+            parsed,
+            Err(ManifestError::Invalid { reason, .. }) if reason.ends_with("includes itself")
+        ));
+    }
+
+    #[test]
+    fn should_leave_out_unresolved_items() {
+        // Arrange
+        let manifest = Manifest::new([SidebarItem::new(Target::unresolved(
+            "Gone",
+            ResolutionFailure::NotFound,
+        ))]);
+
+        // Act
+        let text = to_toml(&manifest);
+
+        // Assert
+        assert_eq!(parse(&text, Path::new("-")), Ok(Manifest::default()));
+    }
+
+    #[test]
+    fn should_reject_unknown_keys() {
+        // Act
+        let parsed = parse(
+            "[[item]]\nlabel = \"A\"\nlocation = \"/a\"\npinned = true\n",
+            Path::new("manifest.toml"),
+        );
+
+        // Assert
+        assert!(matches!(parsed, Err(ManifestError::Invalid { .. })));
+    }
+}
@@ -0,0 +1,273 @@
+//! Configuration profiles that deliver a sidebar manifest through MDM.
+//!
+//! The profile carries one managed-preferences payload for the `identifier` domain holding the
+//! manifest as TOML (`Manifest`) and a shell script (`LoginScript`) that applies it with
+//! `favkit apply --managed-only`, leaving the favorites users added themselves alone. MDM tools
+//! that can only ship profiles and scripts run the script at login. The profile is written
+//! unsigned; sign it with `security cms -S` if the MDM requires it.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{CliError, Result, manifest};
+use crate::finder::{Manifest, SidebarItem};
+
+/// Preference domain the manifest is delivered in unless another is asked for.
+pub const DEFAULT_IDENTIFIER: &str = "nl.happygopher.favkit";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Profile<'a> {
+    payload_content: Vec<Payload<'a>>,
+    payload_display_name: &'static str,
+    payload_identifier: &'a str,
+    payload_scope: &'static str,
+    payload_type: &'static str,
+    #[serde(rename = "PayloadUUID")]
+    payload_uuid: String,
+    payload_version: u32,
+}
+
+/// Managed-preferences (`com.apple.ManagedClient.preferences`) payload.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Payload<'a> {
+    payload_content: BTreeMap<&'a str, Domain>,
+    payload_display_name: &'static str,
+    payload_identifier: String,
+    payload_type: &'static str,
+    #[serde(rename = "PayloadUUID")]
+    payload_uuid: String,
+    payload_version: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Domain {
+    forced: Vec<Forced>,
+}
+
+#[derive(Debug, Serialize)]
+struct Forced {
+    mcx_preference_settings: Settings,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Settings {
+    manifest: String,
+    login_script: String,
+}
+
+/// Builds an XML configuration profile delivering the items as a manifest in the
+/// `identifier` preference domain.
+///
+/// Payload UUIDs are derived from the content, so exporting the same sidebar twice yields the
+/// same profile. The identifier must be a reverse-DNS name, as it ends up in the login script.
+pub fn profile(items: &[SidebarItem], identifier: &str) -> Result<String> {
+    if !is_reverse_dns(identifier) {
+        return Err(CliError::InvalidIdentifier(identifier.to_string()));
+    }
+    let manifest = manifest::to_toml(&Manifest::new(items.iter().cloned()));
+    let login_script = login_script(identifier);
+    let uuid_source = format!("{}\n{}", identifier, manifest);
+
+    let profile = Profile {
+        payload_content: vec![Payload {
+            payload_content: BTreeMap::from([(
+                identifier,
+                Domain {
+                    forced: vec![Forced {
+                        mcx_preference_settings: Settings {
+                            manifest,
+                            login_script,
+                        },
+                    }],
+                },
+            )]),
+            payload_display_name: "Finder sidebar manifest",
+            payload_identifier: format!("{}.manifest", identifier),
+            payload_type: "com.apple.ManagedClient.preferences",
+            payload_uuid: uuid(&format!("payload\n{}", uuid_source)),
+            payload_version: 1,
+        }],
+        payload_display_name: "Finder sidebar",
+        payload_identifier: identifier,
+        payload_scope: "User",
+        payload_type: "Configuration",
+        payload_uuid: uuid(&format!("profile\n{}", uuid_source)),
+        payload_version: 1,
+    };
+
+    let mut xml = Vec::new();
+    plist::to_writer_xml(&mut xml, &profile).expect("profile serializes to a property list");
+    xml.push(b'\n');
+    Ok(String::from_utf8(xml).expect("property list XML is UTF-8"))
+}
+
+/// Whether `identifier` is two or more dot-separated labels of ASCII letters, digits and
+/// hyphens, none starting or ending with a hyphen.
+fn is_reverse_dns(identifier: &str) -> bool {
+    identifier.split('.').count() >= 2
+        && identifier.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        })
+}
+
+/// Script that reads the delivered manifest for the logged-in user and applies it to the items
+/// favkit manages.
+fn login_script(identifier: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+# Brings the Finder sidebar in line with the manifest delivered in {identifier}.
+PATH="/opt/homebrew/bin:/usr/local/bin:$PATH"
+prefs="/Library/Managed Preferences/$(/usr/bin/id -un)/{identifier}"
+/usr/bin/defaults read "$prefs" Manifest | favkit apply --managed-only --manifest -
+"#
+    )
+}
+
+/// UUID-formatted 128-bit FNV-1a hash of `text` (RFC 9562 version 8, custom).
+fn uuid(text: &str) -> String {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    let hash = text.bytes().fold(OFFSET, |hash, byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+    });
+    let versioned = (hash & !(0xf << 76)) | (0x8 << 76);
+    let hash = (versioned & !(0x3 << 62)) | (0x2 << 62);
+    let hex = format!("{:032X}", hash);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use plist::Value;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::{Target, Visibility};
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::Applications).with_id(1),
+            SidebarItem::new(Target::custom("Projects", "/Users/user/Projects"))
+                .with_id(2)
+                .with_visibility(Visibility::Hidden),
+        ]
+    }
+
+    fn settings(profile: &Value) -> &plist::Dictionary {
+        profile.as_dictionary().unwrap()["PayloadContent"]
+            .as_array()
+            .unwrap()[0]
+            .as_dictionary()
+            .unwrap()["PayloadContent"]
+            .as_dictionary()
+            .unwrap()[DEFAULT_IDENTIFIER]
+            .as_dictionary()
+            .unwrap()["Forced"]
+            .as_array()
+            .unwrap()[0]
+            .as_dictionary()
+            .unwrap()["mcx_preference_settings"]
+            .as_dictionary()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_write_well_formed_property_list() {
+        // Act
+        let xml = profile(&sidebar(), DEFAULT_IDENTIFIER).unwrap();
+
+        // Assert
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist"));
+        let profile = Value::from_reader_xml(xml.as_bytes()).expect("profile is a valid plist");
+        let profile = profile.as_dictionary().unwrap();
+        assert_eq!(profile["PayloadType"].as_string(), Some("Configuration"));
+        assert_eq!(
+            profile["PayloadIdentifier"].as_string(),
+            Some(DEFAULT_IDENTIFIER)
+        );
+        assert_eq!(profile["PayloadVersion"].as_unsigned_integer(), Some(1));
+    }
+
+    #[test]
+    fn should_embed_manifest_and_login_script() {
+        // Act
+        let xml = profile(&sidebar(), DEFAULT_IDENTIFIER).unwrap();
+
+        // Assert
+        let profile = Value::from_reader_xml(xml.as_bytes()).unwrap();
+        let settings = settings(&profile);
+        let manifest = manifest::parse(settings["Manifest"].as_string().unwrap(), Path::new("-"));
+        assert_eq!(manifest, Ok(Manifest::new(sidebar())));
+        let script = settings["LoginScript"].as_string().unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("| favkit apply --managed-only --manifest -\n"));
+    }
+
+    #[test]
+    fn should_derive_stable_distinct_uuids() {
+        // Act
+        let first =
+            Value::from_reader_xml(profile(&sidebar(), "com.example").unwrap().as_bytes()).unwrap();
+        let again =
+            Value::from_reader_xml(profile(&sidebar(), "com.example").unwrap().as_bytes()).unwrap();
+        let other =
+            Value::from_reader_xml(profile(&[], "com.example").unwrap().as_bytes()).unwrap();
+
+        // Assert
+        let uuid = |profile: &Value| {
+            profile.as_dictionary().unwrap()["PayloadUUID"]
+                .as_string()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(uuid(&first), uuid(&again));
+        assert_ne!(uuid(&first), uuid(&other));
+        assert_eq!(uuid(&first).len(), 36);
+        assert_eq!(&uuid(&first)[14..15], "8");
+    }
+
+    #[test]
+    fn should_refuse_identifiers_that_are_not_reverse_dns() {
+        for identifier in [
+            "",
+            "favkit",
+            "com..example",
+            "-com.example",
+            "com.example-",
+            "com.-example",
+            "com.example\"; rm -rf ~",
+            "$(id).example",
+            "a/b",
+        ] {
+            // Act
+            let result = profile(&sidebar(), identifier);
+
+            // Assert
+            assert!(
+                matches!(result, Err(CliError::InvalidIdentifier(ref rejected)) if rejected == identifier),
+                "{:?} was accepted",
+                identifier
+            );
+        }
+    }
+}
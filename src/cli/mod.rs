@@ -0,0 +1,207 @@
+//! Command-line interface built on top of [`Finder`].
+//!
+//! The binary only parses arguments and wires up the real macOS API; everything else lives here
+//! so that commands can be exercised against a mocked [`Finder`].
+
+mod args;
+mod batch;
+mod commands;
+mod config;
+mod daemon;
+mod errors;
+mod expand;
+mod manifest;
+mod mobileconfig;
+mod module;
+mod profile;
+mod serve;
+mod template;
+mod validate;
+
+use std::{
+    ffi::OsString,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+pub use args::{Cli, Command, ExportFormat, FilterArgs, Keep, ProfileCommand, SortBy};
+pub use batch::BatchInputError;
+pub use config::{CONFIG_ENV, Config, ConfigError, config_dir};
+pub use daemon::{ClientId, Daemon};
+pub use errors::{CliError, Result};
+pub use manifest::ManifestError;
+pub use module::{ModuleDiff, ModuleError, ModuleResult};
+pub use profile::{ProfileError, Profiles};
+pub use serve::Server;
+pub use template::{Template, TemplateError};
+pub use validate::{Diagnostic, SCHEMA};
+
+use crate::{
+    Finder,
+    finder::{Condition, Scope},
+    system::StateFile,
+};
+
+/// Exit code of commands that ran but found problems, e.g. `doctor`.
+pub const PROBLEMS_FOUND: u8 = 2;
+
+/// Program name that turns favkit into a `mysides` replacement.
+pub const MYSIDES: &str = "mysides";
+
+/// Rewrites `mysides <args>` into `favkit mysides <args>` when invoked under that name.
+///
+/// Lets favkit be installed as a `mysides` link so existing scripts keep working.
+pub fn personality_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args: Vec<OsString> = args.into_iter().collect();
+    let invoked_as_mysides = args
+        .first()
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|name| name == MYSIDES);
+
+    if invoked_as_mysides {
+        args.insert(1, OsString::from(MYSIDES));
+    }
+    args
+}
+
+/// Executes the parsed command, reading piped input from `input` and writing user-facing output
+/// to `out`. `input` is owned because `serve` keeps reading it on a thread of its own.
+///
+/// Returns the process exit code; failures to run the command at all are reported as errors.
+pub fn run(
+    cli: Cli,
+    finder: &Finder,
+    input: impl BufRead + Send + 'static,
+    out: &mut dyn Write,
+) -> Result<ExitCode> {
+    run_with_config(cli, &Config::default(), finder, input, out)
+}
+
+/// Like [`run`], with defaults for what the command line leaves open taken from `config`.
+pub fn run_with_config(
+    cli: Cli,
+    config: &Config,
+    finder: &Finder,
+    mut input: impl BufRead + Send + 'static,
+    out: &mut dyn Write,
+) -> Result<ExitCode> {
+    let success = |()| ExitCode::SUCCESS;
+    let manifest_path = |manifest: Option<PathBuf>| {
+        manifest
+            .or_else(|| config.manifest.clone())
+            .ok_or(CliError::NoManifest)
+    };
+
+    match cli.command.unwrap_or_default() {
+        Command::List { template, filter } => {
+            let template = template.or_else(|| config.template.clone());
+            commands::list(
+                finder,
+                template.as_ref(),
+                &filter.filter(),
+                config.abbreviates_home(),
+                out,
+            )
+            .map(success)
+        }
+        Command::Remove {
+            selector,
+            filter,
+            managed_only,
+            all: _,
+            dry_run,
+        } => {
+            // `--all` only stands in for a selector or filter when parsing
+            let filter = filter.filter();
+            let filter = if managed_only {
+                filter.with(Condition::Managed(true))
+            } else {
+                filter
+            };
+            commands::remove(finder, selector.as_ref(), &filter, dry_run, out).map(success)
+        }
+        Command::Hide { selector } => commands::hide(finder, &selector).map(success),
+        Command::Show { selector } => commands::show(finder, &selector).map(success),
+        Command::Doctor { strict } => commands::doctor(finder, strict, out),
+        Command::Prune {
+            dry_run,
+            keep_network,
+            managed_only,
+        } => commands::prune(finder, dry_run, keep_network, managed_only, out).map(success),
+        Command::Dedupe {
+            keep,
+            prefer,
+            dry_run,
+        } => commands::dedupe(finder, &keep.policy(prefer), dry_run, out).map(success),
+        Command::Sort {
+            by,
+            priority,
+            dry_run,
+        } => commands::sort(finder, &by.order(priority), dry_run, out).map(success),
+        Command::Batch { dry_run } => {
+            commands::batch(finder, &mut input, dry_run, out).map(success)
+        }
+        Command::Serve { stdio: false, .. } => Err(CliError::NoTransport),
+        Command::Serve { stdio: true, poll } => {
+            commands::serve(finder, input, Duration::from_millis(poll), out).map(success)
+        }
+        Command::Daemon { socket, poll } => {
+            let socket = socket.unwrap_or_else(commands::default_socket);
+            commands::daemon(finder, &socket, Duration::from_millis(poll), out).map(success)
+        }
+        Command::Apply {
+            manifest,
+            managed_only,
+            dry_run,
+        } => {
+            let manifest = manifest::read(&manifest_path(manifest)?, &mut input)?;
+            let scope = if managed_only {
+                Scope::Managed
+            } else {
+                Scope::All
+            };
+            commands::apply(finder, &manifest, scope, dry_run, out).map(success)
+        }
+        Command::Activate {
+            manifest,
+            state,
+            dry_run,
+        } => {
+            let state = state
+                .or_else(StateFile::default_path)
+                .ok_or(CliError::NoStateDir)?;
+            let manifest = manifest::read(&manifest_path(manifest)?, &mut input)?;
+            commands::activate(finder, &manifest, &StateFile::new(state), dry_run, out).map(success)
+        }
+        Command::Validate { manifest, schema } => {
+            if schema {
+                commands::schema(out).map(success)
+            } else {
+                commands::validate(&manifest_path(manifest)?, &mut input, out)
+            }
+        }
+        Command::Profile { dir, action } => {
+            let dir = dir
+                .or_else(|| config.profiles_dir())
+                .ok_or(CliError::NoConfigDir)?;
+            commands::profile(finder, &Profiles::new(dir), action, out).map(success)
+        }
+        Command::Export {
+            format,
+            manifest,
+            identifier,
+        } => {
+            let manifest = match manifest {
+                Some(path) => Some(manifest::read(&path, &mut input)?),
+                None => None,
+            };
+            commands::export(finder, manifest.as_ref(), format, &identifier, out).map(success)
+        }
+        Command::Module { args } => commands::module(finder, args.as_deref(), &mut input, out),
+        Command::Undo { force } => commands::undo(finder, force, out).map(success),
+        Command::Redo { force } => commands::redo(finder, force, out).map(success),
+        Command::Mysides { args } => commands::mysides(finder, &args, out),
+    }
+}
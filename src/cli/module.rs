@@ -0,0 +1,227 @@
+//! Configuration-management module interface: JSON arguments in, JSON result out.
+//!
+//! Arguments follow Ansible conventions:
+//!
+//! ```json
+//! {"state": "present", "path": "~/Projects", "label": "Projects", "position": "after:Applications"}
+//! ```
+//!
+//! - `state` is `present` (default) or `absent`
+//! - `path` is a path (`~` is expanded) or URL
+//! - `label` is used when the item is added and defaults to the last path component
+//! - `position` is `first`, `last` or `after:<item>`; without it existing items stay put
+//! - `hidden` shows or hides the item
+//! - `_ansible_check_mode` plans without changing anything and `_ansible_diff` adds the sidebar
+//!   before and after to the result; other `_ansible_*` keys are ignored
+//!
+//! The result is `{"changed": bool}` plus `"diff": {"before", "after"}` in diff mode.
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    Finder,
+    finder::{DesiredItem, Destination, FinderError, Presence, SidebarItem, Visibility, batch},
+};
+
+/// Prefix of the internal arguments Ansible adds to every module call.
+const ANSIBLE_PREFIX: &str = "_ansible_";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ModuleError {
+    #[error("invalid module arguments: {0}")]
+    Arguments(String),
+    #[error("unsupported parameters: {0}")]
+    Unsupported(String),
+    #[error("invalid position '{0}', expected first, last or after:<item>")]
+    Position(String),
+    #[error(transparent)]
+    Finder(#[from] FinderError),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum State {
+    #[default]
+    Present,
+    Absent,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModuleArgs {
+    #[serde(default)]
+    state: State,
+    path: String,
+    label: Option<String>,
+    position: Option<String>,
+    hidden: Option<bool>,
+    #[serde(default, rename = "_ansible_check_mode")]
+    check_mode: bool,
+    #[serde(default, rename = "_ansible_diff")]
+    diff: bool,
+    #[serde(flatten)]
+    other: BTreeMap<String, Value>,
+}
+
+impl ModuleArgs {
+    fn desired(&self) -> Result<DesiredItem, ModuleError> {
+        let label = self.label.clone().unwrap_or_else(|| {
+            let path = Path::new(self.path.trim_end_matches('/'));
+            path.file_name().map_or_else(
+                || self.path.clone(),
+                |name| name.to_string_lossy().into_owned(),
+            )
+        });
+        let presence = match self.state {
+            State::Present => Presence::Present {
+                position: self.position.as_deref().map(position).transpose()?,
+                visibility: self.hidden.map(Visibility::from),
+            },
+            State::Absent => Presence::Absent,
+        };
+        Ok(DesiredItem {
+            target: batch::target(&label, &self.path),
+            presence,
+        })
+    }
+}
+
+fn position(text: &str) -> Result<Destination, ModuleError> {
+    match text {
+        "first" => Ok(Destination::First),
+        "last" => Ok(Destination::Last),
+        _ => match text.strip_prefix("after:") {
+            Some(item) if !item.is_empty() => {
+                let Ok(selector) = item.parse();
+                Ok(Destination::After(selector))
+            }
+            _ => Err(ModuleError::Position(text.to_string())),
+        },
+    }
+}
+
+/// Successful module result.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ModuleResult {
+    pub changed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<ModuleDiff>,
+}
+
+/// The sidebar before and after, one `list` line per item.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ModuleDiff {
+    pub before: String,
+    pub after: String,
+}
+
+fn listing(items: &[SidebarItem]) -> String {
+    items.iter().map(|item| format!("{}\n", item)).collect()
+}
+
+/// Parses the arguments and converges the sidebar, or only plans it in check mode.
+pub fn execute(finder: &Finder, arguments: &str) -> Result<ModuleResult, ModuleError> {
+    let args: ModuleArgs = serde_json::from_str(arguments)
+        .map_err(|error| ModuleError::Arguments(error.to_string()))?;
+    let unsupported: Vec<&str> = args
+        .other
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !key.starts_with(ANSIBLE_PREFIX))
+        .collect();
+    if !unsupported.is_empty() {
+        return Err(ModuleError::Unsupported(unsupported.join(", ")));
+    }
+
+    let desired = args.desired()?;
+    let convergence = if args.check_mode {
+        finder.plan_convergence(&desired)?
+    } else {
+        finder.converge(&desired)?
+    };
+    Ok(ModuleResult {
+        changed: convergence.is_changed(),
+        diff: args.diff.then(|| ModuleDiff {
+            before: listing(&convergence.before),
+            after: listing(&convergence.after),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::{Selector, Target};
+
+    fn args(json: &str) -> ModuleArgs {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn should_default_to_present_with_label_from_path() {
+        // Act
+        let desired = args(r#"{"path": "/Users/user/Projects/"}"#).desired();
+
+        // Assert
+        assert_eq!(
+            desired,
+            Ok(DesiredItem {
+                target: Target::custom("Projects", "/Users/user/Projects/"),
+                presence: Presence::Present {
+                    position: None,
+                    visibility: None
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_read_position_and_visibility() {
+        // Act
+        let desired = args(
+            r#"{"path": "/Users/user/Projects", "label": "Code", "position": "after:Applications", "hidden": true}"#,
+        )
+        .desired();
+
+        // Assert
+        assert_eq!(
+            desired,
+            Ok(DesiredItem {
+                target: Target::custom("Code", "/Users/user/Projects"),
+                presence: Presence::Present {
+                    position: Some(Destination::After(Selector::Label("Applications".into()))),
+                    visibility: Some(Visibility::Hidden),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_position() {
+        // Act
+        let desired = args(r#"{"path": "/a", "position": "middle"}"#).desired();
+
+        // Assert
+        assert_eq!(desired, Err(ModuleError::Position("middle".into())));
+    }
+
+    #[test]
+    fn should_read_absent_state() {
+        // Act
+        let desired = args(r#"{"path": "smb://nas.local/share", "state": "absent"}"#).desired();
+
+        // Assert
+        assert_eq!(
+            desired,
+            Ok(DesiredItem {
+                target: Target::network("share", "smb://nas.local/share"),
+                presence: Presence::Absent,
+            })
+        );
+    }
+}
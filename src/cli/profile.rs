@@ -0,0 +1,221 @@
+//! Named sidebar layouts, stored in favkit's config directory.
+//!
+//! Each profile is a `<name>.json` file holding the sidebar in the form the undo journal stores
+//! it, so labels, visibility and ownership come back exactly as they were saved.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    finder::{SidebarItem, Target},
+    system::journal,
+};
+
+/// Extension of profile files.
+const EXTENSION: &str = "json";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ProfileError {
+    #[error("invalid profile name '{0}', use letters, digits, '-', '_' and '.'")]
+    InvalidName(String),
+    #[error("no profile named '{0}', see `favkit profile list`")]
+    NotFound(String),
+    #[error("'{0}' could not be resolved, mount its volume and try again")]
+    Unresolved(String),
+    #[error("failed to access profiles in {path}: {reason}")]
+    Io { path: PathBuf, reason: String },
+    #[error("profile {path} is corrupt: {reason}")]
+    Corrupt { path: PathBuf, reason: String },
+}
+
+/// Directory holding one sidebar snapshot per profile.
+#[derive(Debug, Clone)]
+pub struct Profiles {
+    dir: PathBuf,
+}
+
+impl Profiles {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Stores the items as the named profile, replacing an earlier one.
+    ///
+    /// Refuses items that could not be resolved, as they could not be added back.
+    pub fn save(&self, name: &str, items: &[SidebarItem]) -> Result<PathBuf, ProfileError> {
+        let path = self.path(name)?;
+        if let Some(item) = items.iter().find(|item| is_unresolved(item)) {
+            return Err(ProfileError::Unresolved(item.target().label().to_string()));
+        }
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(&path, journal::write_snapshot(items)))
+            .map_err(|error| self.io_error(error))?;
+        Ok(path)
+    }
+
+    pub fn load(&self, name: &str) -> Result<Vec<SidebarItem>, ProfileError> {
+        let path = self.path(name)?;
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Err(ProfileError::NotFound(name.to_string()));
+            }
+            Err(error) => return Err(self.io_error(error)),
+        };
+        journal::read_snapshot(&text).map_err(|error| ProfileError::Corrupt {
+            path,
+            reason: error.to_string(),
+        })
+    }
+
+    /// Names of the stored profiles, sorted; none before the first is saved.
+    pub fn names(&self) -> Result<Vec<String>, ProfileError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(self.io_error(error)),
+        };
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|error| self.io_error(error))?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+                && let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+                && is_valid(name)
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, ProfileError> {
+        if !is_valid(name) {
+            return Err(ProfileError::InvalidName(name.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.{}", name, EXTENSION)))
+    }
+
+    fn io_error(&self, error: io::Error) -> ProfileError {
+        ProfileError::Io {
+            path: self.dir.clone(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+/// Whether the item is missing a location, e.g. a share on a volume that is not mounted.
+pub(crate) fn is_unresolved(item: &SidebarItem) -> bool {
+    matches!(item.target(), Target::Unresolved { .. })
+}
+
+/// Names become file names, so they must not leave the profile directory or be hidden files.
+fn is_valid(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        finder::{ResolutionFailure, Visibility},
+        testing::TempDir,
+    };
+
+    fn profiles(dir: &TempDir) -> Profiles {
+        Profiles::new(dir.join("profiles"))
+    }
+
+    fn items() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::Applications).with_id(1),
+            SidebarItem::new(Target::custom("Client A", "/Users/user/Clients/A"))
+                .with_id(2)
+                .with_visibility(Visibility::Hidden),
+        ]
+    }
+
+    #[test]
+    fn should_list_nothing_before_first_save() {
+        let dir = TempDir::new("profiles");
+        assert_eq!(profiles(&dir).names(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn should_load_saved_profiles_and_list_them_sorted() {
+        // Arrange
+        let dir = TempDir::new("profiles");
+        let profiles = profiles(&dir);
+
+        // Act
+        profiles.save("client-b", &[]).unwrap();
+        profiles.save("client-a", &items()).unwrap();
+
+        // Assert
+        assert_eq!(profiles.load("client-a"), Ok(items()));
+        assert_eq!(
+            profiles.names(),
+            Ok(vec!["client-a".to_string(), "client-b".to_string()])
+        );
+    }
+
+    #[test]
+    fn should_report_missing_profile() {
+        let dir = TempDir::new("profiles");
+        assert_eq!(
+            profiles(&dir).load("client-c"),
+            Err(ProfileError::NotFound("client-c".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_reject_names_outside_profile_directory() {
+        // Arrange
+        let dir = TempDir::new("profiles");
+        let profiles = profiles(&dir);
+
+        // Act & Assert
+        for name in ["", "../client", ".hidden", "a/b"] {
+            assert_eq!(
+                profiles.save(name, &items()),
+                Err(ProfileError::InvalidName(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn should_refuse_to_save_unresolved_items() {
+        // Arrange
+        let dir = TempDir::new("profiles");
+        let profiles = profiles(&dir);
+        let mut items = items();
+        items.push(SidebarItem::new(Target::unresolved(
+            "NAS",
+            ResolutionFailure::NotFound,
+        )));
+
+        // Act
+        let result = profiles.save("client-a", &items);
+
+        // Assert
+        assert_eq!(result, Err(ProfileError::Unresolved("NAS".to_string())));
+        assert_eq!(profiles.names(), Ok(Vec::new()));
+    }
+}
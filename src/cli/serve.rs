@@ -0,0 +1,346 @@
+//! JSON-RPC 2.0 server for editor and GUI integrations.
+//!
+//! Every line of input is one request; every line of output is one response or notification.
+//! Each method maps onto a [`Finder`] call:
+//!
+//! - `list {where?: ["kind=custom"], match?: "Proj*"}` returns items
+//! - `add {label, location, to?: "first" | "last", after?: selector}` returns the new item
+//! - `remove {item}` returns the removed item
+//! - `move {item, to?, after?}` and `rename {item, label}` return `null`
+//! - `watch` returns the current `{seed, items}` and turns on `changed` notifications with the
+//!   same shape whenever the sidebar changes; `unwatch` turns them off
+
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+use crate::{
+    Finder,
+    finder::{
+        BatchError, Condition, Destination, Filter, FinderError, Glob, Position, Seed, Selector,
+        SidebarItem, batch,
+    },
+    system::favorites::MacOsUrl,
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Start of the range JSON-RPC leaves to applications; used for failed `Finder` calls
+const FINDER_ERROR: i64 = -32000;
+
+#[derive(Debug, PartialEq)]
+pub(super) struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    pub(super) fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<FinderError> for RpcError {
+    fn from(error: FinderError) -> Self {
+        Self::new(FINDER_ERROR, error)
+    }
+}
+
+impl From<BatchError> for RpcError {
+    fn from(error: BatchError) -> Self {
+        Self::new(FINDER_ERROR, error)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Request {
+    jsonrpc: String,
+    /// Missing for notifications, which get no response; a `null` id is answered
+    #[serde(default, deserialize_with = "present")]
+    pub(super) id: Option<Value>,
+    pub(super) method: String,
+    #[serde(default)]
+    pub(super) params: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct ListParams {
+    #[serde(rename = "where")]
+    conditions: Vec<String>,
+    #[serde(rename = "match")]
+    pattern: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddParams {
+    label: String,
+    location: String,
+    to: Option<String>,
+    after: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ItemParams {
+    item: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MoveParams {
+    item: String,
+    to: Option<String>,
+    after: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RenameParams {
+    item: String,
+    label: String,
+}
+
+/// How items are sent to clients.
+#[derive(Debug, Serialize)]
+pub(super) struct ItemView<'a> {
+    id: Option<u32>,
+    kind: &'static str,
+    label: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    hidden: bool,
+}
+
+impl<'a> From<&'a SidebarItem> for ItemView<'a> {
+    fn from(item: &'a SidebarItem) -> Self {
+        let target = item.target();
+        Self {
+            id: item.id().map(u32::from),
+            kind: target.kind().name(),
+            label: target.label(),
+            path: target.path(),
+            url: MacOsUrl::try_from(target).ok().map(|url| url.to_string()),
+            hidden: item.is_hidden(),
+        }
+    }
+}
+
+pub(super) fn items(items: &[SidebarItem]) -> Value {
+    json!(items.iter().map(ItemView::from).collect::<Vec<_>>())
+}
+
+/// Handles requests one line at a time, remembering whether the client is watching.
+pub struct Server<'a> {
+    finder: &'a Finder,
+    /// Seed last reported to a watching client
+    watched: Option<Seed>,
+}
+
+impl<'a> Server<'a> {
+    pub fn new(finder: &'a Finder) -> Self {
+        Self {
+            finder,
+            watched: None,
+        }
+    }
+
+    /// Handles one request and returns the messages to send back: its response, if it has an
+    /// id, followed by a change notification when the request modified a watched sidebar.
+    pub fn handle(&mut self, line: &str) -> Vec<Value> {
+        let request = match request(line) {
+            Ok(request) => request,
+            Err(failure) => return vec![failure],
+        };
+
+        let result = self.call(&request.method, request.params);
+        response(request.id, result)
+            .into_iter()
+            .chain(self.poll())
+            .collect()
+    }
+
+    /// Returns a `changed` notification if the client is watching and the sidebar changed since
+    /// it was last told.
+    pub fn poll(&mut self) -> Option<Value> {
+        let watched = self.watched?;
+        let seed = self.finder.seed().ok()?;
+        if seed == watched {
+            return None;
+        }
+
+        let state = self.state().ok()?;
+        self.watched = Some(seed);
+        Some(notification("changed", state))
+    }
+
+    fn state(&self) -> Result<Value, RpcError> {
+        let seed = self.finder.seed()?;
+        let list = self.finder.get_favorites_list()?;
+        Ok(json!({"seed": u32::from(seed), "items": items(&list)}))
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "list" => {
+                let params: ListParams = parse(params)?;
+                let filter = filter(params)?;
+                Ok(items(&self.finder.find_items(&filter)?))
+            }
+            "watch" => {
+                let state = self.state()?;
+                self.watched = Some(self.finder.seed()?);
+                Ok(state)
+            }
+            "unwatch" => {
+                self.watched = None;
+                Ok(Value::Null)
+            }
+            _ => edit(self.finder, method, params),
+        }
+    }
+}
+
+/// Calls the methods that change the sidebar: `add`, `remove`, `move` and `rename`.
+pub(super) fn edit(finder: &Finder, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "add" => {
+            let params: AddParams = parse(params)?;
+            let target = batch::target(&params.label, &params.location);
+            let position = match destination(params.to, params.after)? {
+                None | Some(Destination::Last) => Position::Last,
+                Some(Destination::First) => Position::First,
+                Some(Destination::After(selector)) => finder
+                    .find_item(&selector)?
+                    .id()
+                    .map(Position::After)
+                    .ok_or(FinderError::NoMatch(selector))?,
+            };
+            let item = finder.add(target, position)?;
+            Ok(json!(ItemView::from(&item)))
+        }
+        "remove" => {
+            let params: ItemParams = parse(params)?;
+            let item = finder.remove(&selector(&params.item))?;
+            Ok(json!(ItemView::from(&item)))
+        }
+        "move" => {
+            let params: MoveParams = parse(params)?;
+            let to = destination(params.to, params.after)?
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "missing \"to\" or \"after\""))?;
+            finder.move_to(&selector(&params.item), to)?;
+            Ok(Value::Null)
+        }
+        "rename" => {
+            let params: RenameParams = parse(params)?;
+            finder.rename(&selector(&params.item), params.label)?;
+            Ok(Value::Null)
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method '{}'", method),
+        )),
+    }
+}
+
+/// Keeps a `null` id apart from a missing one, which `Option` alone would conflate.
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Parses one line into a request, or returns the error response to send instead.
+///
+/// Batches (arrays of requests) are not supported and get a single invalid request error.
+pub(super) fn request(line: &str) -> Result<Request, Value> {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(Value::Array(_)) => {
+            let error = RpcError::new(INVALID_REQUEST, "batch requests are not supported");
+            return Err(failure(Value::Null, error));
+        }
+        Ok(value) => serde_json::from_value::<Request>(value),
+        Err(error) => return Err(failure(Value::Null, RpcError::new(PARSE_ERROR, error))),
+    };
+    match request {
+        Ok(request) if request.jsonrpc != "2.0" => {
+            let error = RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"");
+            Err(failure(Value::Null, error))
+        }
+        Ok(Request {
+            id: Some(Value::Array(_) | Value::Object(_) | Value::Bool(_)),
+            ..
+        }) => {
+            let error = RpcError::new(INVALID_REQUEST, "id must be a string, number or null");
+            Err(failure(Value::Null, error))
+        }
+        Ok(request) => Ok(request),
+        Err(error) => Err(failure(Value::Null, RpcError::new(INVALID_REQUEST, error))),
+    }
+}
+
+/// Response to a request with the given id; notifications get none.
+pub(super) fn response(id: Option<Value>, result: Result<Value, RpcError>) -> Option<Value> {
+    id.map(|id| match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => failure(id, error),
+    })
+}
+
+pub(super) fn notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+fn failure(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": error.code, "message": error.message},
+    })
+}
+
+/// Reads method parameters; a missing `params` counts as an empty object.
+pub(super) fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|error| RpcError::new(INVALID_PARAMS, error))
+}
+
+pub(super) fn filter(params: ListParams) -> Result<Filter, RpcError> {
+    let conditions = params
+        .conditions
+        .iter()
+        .map(|condition| condition.parse::<Condition>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| RpcError::new(INVALID_PARAMS, error))?;
+    let pattern = params
+        .pattern
+        .map(|pattern| Condition::Label(Glob::new(pattern)));
+    Ok(conditions.into_iter().chain(pattern).collect())
+}
+
+fn destination(to: Option<String>, after: Option<String>) -> Result<Option<Destination>, RpcError> {
+    match (to.as_deref(), after) {
+        (Some(_), Some(_)) => Err(RpcError::new(
+            INVALID_PARAMS,
+            "use either \"to\" or \"after\", not both",
+        )),
+        (Some("first"), None) => Ok(Some(Destination::First)),
+        (Some("last"), None) => Ok(Some(Destination::Last)),
+        (Some(to), None) => Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("unknown destination '{}', expected first or last", to),
+        )),
+        (None, Some(after)) => Ok(Some(Destination::After(selector(&after)))),
+        (None, None) => Ok(None),
+    }
+}
+
+fn selector(word: &str) -> Selector {
+    let Ok(selector) = word.parse();
+    selector
+}
@@ -0,0 +1,371 @@
+//! Output templates for `favkit list --template`.
+//!
+//! A template is literal text with placeholders:
+//! - `{field}` inserts a field of the item: `index`, `kind`, `label`, `path`, `url`, `hidden`,
+//!   `managed` or `id`
+//! - `{path|url|"-"}` inserts the first alternative that isn't empty; alternatives are fields or
+//!   double-quoted text
+//! - `\t`, `\n`, `\\`, `\{` and `\}` insert a tab, a newline, a backslash and literal braces
+//!
+//! Fields that don't apply to an item are empty: built-in items have no path, unresolved items
+//! no URL. `index` counts from 1 in sidebar order; `hidden` and `managed` are `true` or `false`.
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{finder::SidebarItem, system::favorites::MacOsUrl};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    #[error("unknown field '{field}' at column {column}")]
+    UnknownField { field: String, column: usize },
+    #[error("unclosed '{{' at column {0}")]
+    Unclosed(usize),
+    #[error("unexpected '}}' at column {0}, write '\\}}' for a literal brace")]
+    UnexpectedBrace(usize),
+    #[error("unknown escape '\\{escape}' at column {column}")]
+    UnknownEscape { escape: char, column: usize },
+    #[error("unterminated text at column {0}")]
+    UnterminatedText(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Index,
+    Kind,
+    Label,
+    Path,
+    Url,
+    Hidden,
+    Managed,
+    Id,
+}
+
+impl Field {
+    const NAMES: [(Self, &'static str); 8] = [
+        (Self::Index, "index"),
+        (Self::Kind, "kind"),
+        (Self::Label, "label"),
+        (Self::Path, "path"),
+        (Self::Url, "url"),
+        (Self::Hidden, "hidden"),
+        (Self::Managed, "managed"),
+        (Self::Id, "id"),
+    ];
+
+    fn parse(name: &str, column: usize) -> Result<Self, TemplateError> {
+        Self::NAMES
+            .iter()
+            .find(|(_, known)| *known == name)
+            .map(|(field, _)| *field)
+            .ok_or_else(|| TemplateError::UnknownField {
+                field: name.to_string(),
+                column,
+            })
+    }
+
+    fn value(self, index: usize, item: &SidebarItem) -> String {
+        let target = item.target();
+        match self {
+            Self::Index => index.to_string(),
+            Self::Kind => target.kind().to_string(),
+            Self::Label => target.label().to_string(),
+            Self::Path => target.path().unwrap_or_default().to_string(),
+            Self::Url => MacOsUrl::try_from(target)
+                .map(|url| url.to_string())
+                .unwrap_or_default(),
+            Self::Hidden => item.is_hidden().to_string(),
+            Self::Managed => item.is_managed().to_string(),
+            Self::Id => item.id().map(|id| id.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Alternative {
+    Field(Field),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Placeholder(Vec<Alternative>),
+}
+
+/// A parsed output template; see the [module documentation](self) for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Renders one item; `index` is its 1-based position in the sidebar.
+    pub fn render(&self, index: usize, item: &SidebarItem) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Placeholder(alternatives) => alternatives
+                    .iter()
+                    .map(|alternative| match alternative {
+                        Alternative::Field(field) => field.value(index, item),
+                        Alternative::Text(text) => text.clone(),
+                    })
+                    .find(|value| !value.is_empty())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser::new(s).parse()
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => write!(f, "{}", escape(text))?,
+                Part::Placeholder(alternatives) => {
+                    let alternatives: Vec<_> = alternatives
+                        .iter()
+                        .map(|alternative| match alternative {
+                            Alternative::Field(field) => Field::NAMES
+                                .iter()
+                                .find(|(known, _)| known == field)
+                                .map_or(String::new(), |(_, name)| name.to_string()),
+                            Alternative::Text(text) => format!("\"{}\"", text),
+                        })
+                        .collect();
+                    write!(f, "{{{}}}", alternatives.join("|"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\t' => "\\t".to_string(),
+            '\n' => "\\n".to_string(),
+            '\\' | '{' | '}' => format!("\\{}", c),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Single-pass parser tracking 1-based columns for error messages.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::iter::Enumerate<std::str::Chars<'a>>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().enumerate().peekable(),
+        }
+    }
+
+    fn parse(mut self) -> Result<Template, TemplateError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        while let Some((position, c)) = self.chars.next() {
+            let column = position + 1;
+            match c {
+                '\\' => text.push(self.escape(column)?),
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Placeholder(self.placeholder(column)?));
+                }
+                '}' => return Err(TemplateError::UnexpectedBrace(column)),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Template { parts })
+    }
+
+    fn escape(&mut self, column: usize) -> Result<char, TemplateError> {
+        match self.chars.next() {
+            Some((_, 't')) => Ok('\t'),
+            Some((_, 'n')) => Ok('\n'),
+            Some((_, c @ ('\\' | '{' | '}' | '"'))) => Ok(c),
+            Some((_, escape)) => Err(TemplateError::UnknownEscape { escape, column }),
+            None => Err(TemplateError::UnknownEscape {
+                escape: ' ',
+                column,
+            }),
+        }
+    }
+
+    /// Parses the alternatives of a placeholder whose `{` is at `open`.
+    fn placeholder(&mut self, open: usize) -> Result<Vec<Alternative>, TemplateError> {
+        let mut alternatives = Vec::new();
+
+        loop {
+            let (position, c) = self
+                .chars
+                .peek()
+                .copied()
+                .ok_or(TemplateError::Unclosed(open))?;
+            let alternative = if c == '"' {
+                self.chars.next();
+                Alternative::Text(self.quoted(position + 1)?)
+            } else {
+                let name = self.name();
+                Alternative::Field(Field::parse(name.trim(), position + 1)?)
+            };
+            alternatives.push(alternative);
+
+            match self.chars.next() {
+                Some((_, '|')) => continue,
+                Some((_, '}')) => return Ok(alternatives),
+                _ => return Err(TemplateError::Unclosed(open)),
+            }
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c == '|' || c == '}' {
+                break;
+            }
+            name.push(c);
+            self.chars.next();
+        }
+        name
+    }
+
+    fn quoted(&mut self, start: usize) -> Result<String, TemplateError> {
+        let mut text = String::new();
+        while let Some((position, c)) = self.chars.next() {
+            match c {
+                '"' => return Ok(text),
+                '\\' => text.push(self.escape(position + 1)?),
+                c => text.push(c),
+            }
+        }
+        Err(TemplateError::UnterminatedText(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::{Ownership, Target, Visibility};
+
+    fn projects() -> SidebarItem {
+        SidebarItem::new(Target::custom("Projects", "/Users/user/Projects")).with_id(7)
+    }
+
+    fn render(template: &str, item: &SidebarItem) -> String {
+        template.parse::<Template>().unwrap().render(3, item)
+    }
+
+    #[test]
+    fn should_render_fields_with_escapes() {
+        assert_eq!(
+            render("{index}\\t{label}\\t{path}", &projects()),
+            "3\tProjects\t/Users/user/Projects"
+        );
+    }
+
+    #[test]
+    fn should_render_every_field() {
+        assert_eq!(
+            render("{kind} {url} {hidden} {id}", &projects()),
+            "custom file:///Users/user/Projects/ false 7"
+        );
+    }
+
+    #[test]
+    fn should_leave_inapplicable_fields_empty() {
+        let item = SidebarItem::new(Target::AirDrop);
+        assert_eq!(render("[{path}][{id}]", &item), "[][]");
+    }
+
+    #[test]
+    fn should_fall_back_to_next_alternative() {
+        let airdrop = SidebarItem::new(Target::AirDrop);
+        let share = SidebarItem::new(Target::network("NAS", "smb://nas.local/share"));
+
+        assert_eq!(render("{path|kind}", &airdrop), "airdrop");
+        assert_eq!(render("{path|url}", &share), "smb://nas.local/share");
+        assert_eq!(render("{id|\"-\"}", &airdrop), "-");
+    }
+
+    #[test]
+    fn should_render_hidden_flag() {
+        let item = projects().with_visibility(Visibility::Hidden);
+        assert_eq!(render("{hidden}", &item), "true");
+    }
+
+    #[test]
+    fn should_render_managed_flag() {
+        let item = projects().with_ownership(Ownership::Managed);
+        assert_eq!(render("{managed}", &item), "true");
+        assert_eq!(render("{managed}", &projects()), "false");
+    }
+
+    #[test]
+    fn should_keep_escaped_braces_literal() {
+        assert_eq!(render("\\{{label}\\}", &projects()), "{Projects}");
+    }
+
+    #[test]
+    fn should_reject_unknown_field() {
+        assert_eq!(
+            "{index} {name}".parse::<Template>(),
+            Err(TemplateError::UnknownField {
+                field: "name".to_string(),
+                column: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_malformed_templates() {
+        assert_eq!(
+            "{label".parse::<Template>(),
+            Err(TemplateError::Unclosed(1))
+        );
+        assert_eq!(
+            "label}".parse::<Template>(),
+            Err(TemplateError::UnexpectedBrace(6))
+        );
+        assert_eq!(
+            "\\x".parse::<Template>(),
+            Err(TemplateError::UnknownEscape {
+                escape: 'x',
+                column: 1,
+            })
+        );
+        assert_eq!(
+            "{label|\"-}".parse::<Template>(),
+            Err(TemplateError::UnterminatedText(8))
+        );
+    }
+
+    #[test]
+    fn should_format_template_back_to_source() {
+        let source = "{index}\\t{path|kind|\"-\"}\\n";
+        assert_eq!(source.parse::<Template>().unwrap().to_string(), source);
+    }
+}
@@ -0,0 +1,569 @@
+//! Checks manifests before they are applied, pointing at the line and column of each problem.
+//!
+//! Tables, keys and includes are read as [`manifest`](super::manifest) reads them. Beyond the
+//! shape described by [`SCHEMA`], locations are checked against the [`Target`] model:
+//! relative paths and unknown special targets are reported, as are labels used twice and
+//! includes that cannot be read.
+
+use std::{
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::{ImDocument, Item, Table};
+
+use super::{
+    expand::{self, Variables},
+    manifest::{self, IncludeError},
+};
+use crate::finder::{Target, batch};
+
+/// JSON Schema of the manifest format, for editors to complete and check manifests with.
+pub const SCHEMA: &str = include_str!("../../docs/manifest.schema.json");
+
+/// A problem in a manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub help: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )?;
+        if let Some(help) = &self.help {
+            write!(f, "\n  help: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks a manifest and the manifests it includes; `path` names it and anchors its includes.
+pub fn validate(text: &str, path: &Path, variables: &Variables) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        variables,
+        including: vec![manifest::identity(path)],
+        labelled: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    validator.manifest(&Source { text, path });
+    validator.diagnostics
+}
+
+/// A manifest being checked, to locate problems in.
+struct Source<'a> {
+    text: &'a str,
+    path: &'a Path,
+}
+
+impl Source<'_> {
+    fn diagnostic(
+        &self,
+        span: Option<Range<usize>>,
+        message: String,
+        help: Option<String>,
+    ) -> Diagnostic {
+        let offset = span.map_or(0, |span| span.start).min(self.text.len());
+        let before = self.text.get(..offset).unwrap_or_default();
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Diagnostic {
+            path: self.path.to_path_buf(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message,
+            help,
+        }
+    }
+}
+
+struct Validator<'a> {
+    variables: &'a Variables,
+    /// Manifests being read, to catch includes that lead back to one of them.
+    including: Vec<PathBuf>,
+    /// Items checked so far, in this manifest and the ones it includes.
+    labelled: Vec<Labelled>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Label of a checked item and where it is, to report labels used twice.
+struct Labelled {
+    label: String,
+    when: Option<String>,
+    path: PathBuf,
+    line: usize,
+}
+
+impl Validator<'_> {
+    fn report(
+        &mut self,
+        source: &Source,
+        span: Option<Range<usize>>,
+        message: String,
+        help: Option<String>,
+    ) {
+        self.diagnostics
+            .push(source.diagnostic(span, message, help));
+    }
+
+    fn manifest(&mut self, source: &Source) {
+        let document = match ImDocument::parse(source.text) {
+            Ok(document) => document,
+            Err(error) => {
+                let message = error.message().trim_end().to_string();
+                self.report(source, error.span(), message, None);
+                return;
+            }
+        };
+        let root = document.as_table();
+
+        self.keys(source, root, manifest::sections(), "section");
+        for include in self.tables(source, root, "include") {
+            self.include(source, include);
+        }
+        for item in self.tables(source, root, "item") {
+            self.item(source, item);
+        }
+    }
+
+    fn include(&mut self, source: &Source, table: &Table) {
+        self.keys(source, table, manifest::include_keys(), "key");
+        let when = self.condition(source, table);
+        let Some((path, span)) = self.string(source, table, "path") else {
+            return;
+        };
+        if when == Some(false) {
+            return;
+        }
+        let Some(path) = self.expanded(source, &path, span.clone()) else {
+            return;
+        };
+
+        match manifest::read_include(source.path, &path, self.variables, &self.including) {
+            Ok(included) => {
+                self.including.push(included.identity);
+                self.manifest(&Source {
+                    text: &included.text,
+                    path: &included.path,
+                });
+                self.including.pop();
+            }
+            Err(IncludeError::Cycle(included)) => {
+                let message = format!("`{}` includes itself", included.display());
+                self.report(source, span, message, None);
+            }
+            Err(IncludeError::Unreadable(included, error)) => {
+                let message = format!("cannot read `{}`: {}", included.display(), error);
+                let help = Some("include paths are relative to this manifest".to_string());
+                self.report(source, span, message, help);
+            }
+        }
+    }
+
+    fn item(&mut self, source: &Source, table: &Table) {
+        self.keys(source, table, manifest::item_keys(), "key");
+        self.condition(source, table);
+        if let Some(item) = table.get("hidden")
+            && item.as_bool().is_none()
+        {
+            let message = format!("`hidden` must be a boolean, not {}", item.type_name());
+            self.report(source, item.span(), message, None);
+        }
+        let label = self.string(source, table, "label");
+        let location = self.string(source, table, "location");
+        let (Some((label, label_span)), Some((location, location_span))) = (label, location) else {
+            return;
+        };
+        let (Some(label), Some(location)) = (
+            self.expanded(source, &label, label_span.clone()),
+            self.expanded(source, &location, location_span.clone()),
+        ) else {
+            return;
+        };
+
+        self.target(source, &label, &location, location_span);
+
+        // Items with different conditions may share a label, as they are never both present
+        let when = table.get("when").and_then(Item::as_str).map(str::to_string);
+        let line = source
+            .diagnostic(label_span.clone(), String::new(), None)
+            .line;
+        match self
+            .labelled
+            .iter()
+            .find(|other| other.label == label && other.when == when)
+        {
+            Some(first) => {
+                let place = if first.path == source.path {
+                    format!("line {}", first.line)
+                } else {
+                    format!("{}:{}", first.path.display(), first.line)
+                };
+                let message = format!("duplicate label `{}`, first used on {}", label, place);
+                let help =
+                    Some("rename one of them or give them different `when` conditions".to_string());
+                self.report(source, label_span, message, help);
+            }
+            None => self.labelled.push(Labelled {
+                label,
+                when,
+                path: source.path.to_path_buf(),
+                line,
+            }),
+        }
+    }
+
+    /// Checks a location against the targets a sidebar can hold.
+    fn target(&mut self, source: &Source, label: &str, location: &str, span: Option<Range<usize>>) {
+        match batch::target(label, location) {
+            Target::Custom { path, .. } if !path.starts_with('/') => {
+                let help = match special(location) {
+                    Some(url) => format!("did you mean `{}`?", url),
+                    None => format!("use an absolute path or `~/{}`", location),
+                };
+                let message = format!("relative path `{}`", location);
+                self.report(source, span, message, Some(help));
+            }
+            Target::Network { url, .. }
+                if built_in_urls().any(|built_in| scheme(&built_in) == scheme(&url)) =>
+            {
+                let urls: Vec<String> = built_in_urls().collect();
+                let help = suggest(&url, urls.iter().map(String::as_str))
+                    .map(|url| format!("did you mean `{}`?", url));
+                let message = format!("unknown special target `{}`", url);
+                self.report(source, span, message, help);
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks the `when` condition, telling whether it holds on this machine.
+    fn condition(&mut self, source: &Source, table: &Table) -> Option<bool> {
+        let (condition, span) = self.optional_string(source, table, "when")?;
+        match expand::evaluate(&condition, self.variables) {
+            Ok(holds) => Some(holds),
+            Err(error) => {
+                let help = Some(r#"e.g. `hostname matches "build-*"`"#.to_string());
+                self.report(source, span, error.to_string(), help);
+                None
+            }
+        }
+    }
+
+    fn expanded(
+        &mut self,
+        source: &Source,
+        text: &str,
+        span: Option<Range<usize>>,
+    ) -> Option<String> {
+        expand::expand(text, self.variables)
+            .map_err(|error| self.report(source, span, error.to_string(), None))
+            .ok()
+    }
+
+    /// Reports keys outside `allowed`, suggesting the one that was probably meant.
+    fn keys(&mut self, source: &Source, table: &Table, allowed: &[&str], kind: &str) {
+        for (key, _) in table.iter() {
+            if allowed.contains(&key) {
+                continue;
+            }
+            let span = table.get_key_value(key).and_then(|(key, _)| key.span());
+            let help = match suggest(key, allowed.iter().copied()) {
+                Some(allowed) => format!("did you mean `{}`?", allowed),
+                None => format!("expected one of: {}", allowed.join(", ")),
+            };
+            self.report(
+                source,
+                span,
+                format!("unknown {} `{}`", kind, key),
+                Some(help),
+            );
+        }
+    }
+
+    /// The `[[name]]` tables of a manifest.
+    fn tables<'t>(&mut self, source: &Source, root: &'t Table, name: &str) -> Vec<&'t Table> {
+        match root.get_key_value(name) {
+            None => Vec::new(),
+            Some((_, Item::ArrayOfTables(tables))) => tables.iter().collect(),
+            Some((key, _)) => {
+                let message = format!("`{}` must be a list of tables", name);
+                let help = Some(format!("write each one as a [[{}]] table", name));
+                self.report(source, key.span(), message, help);
+                Vec::new()
+            }
+        }
+    }
+
+    fn string(
+        &mut self,
+        source: &Source,
+        table: &Table,
+        key: &str,
+    ) -> Option<(String, Option<Range<usize>>)> {
+        if table.get(key).is_none() {
+            self.report(source, table.span(), format!("missing `{}`", key), None);
+            return None;
+        }
+        self.optional_string(source, table, key)
+    }
+
+    fn optional_string(
+        &mut self,
+        source: &Source,
+        table: &Table,
+        key: &str,
+    ) -> Option<(String, Option<Range<usize>>)> {
+        let item = table.get(key)?;
+        match item.as_str() {
+            Some(value) if !value.is_empty() => Some((value.to_string(), item.span())),
+            Some(_) => {
+                self.report(source, item.span(), format!("`{}` is empty", key), None);
+                None
+            }
+            None => {
+                let message = format!("`{}` must be a string, not {}", key, item.type_name());
+                self.report(source, item.span(), message, None);
+                None
+            }
+        }
+    }
+}
+
+/// URL of the special target a bare name like `airdrop` stands for.
+fn special(name: &str) -> Option<String> {
+    Target::BUILT_IN
+        .iter()
+        .find(|target| target.label().eq_ignore_ascii_case(name))
+        .and_then(batch::location)
+}
+
+/// URLs manifests name the items macOS provides with.
+fn built_in_urls() -> impl Iterator<Item = String> {
+    Target::BUILT_IN.iter().filter_map(batch::location)
+}
+
+/// The scheme of a URL, such as `nwnode` for AirDrop.
+fn scheme(url: &str) -> Option<&str> {
+    url.split_once("://").map(|(scheme, _)| scheme)
+}
+
+/// The candidate closest to `word`, if it is close enough to be a typo.
+fn suggest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (word.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (distance(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between two words, ignoring case.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn variables() -> Variables {
+        Variables::new("laptop", [("HOME".to_string(), "/Users/user".to_string())])
+    }
+
+    fn check(text: &str) -> Vec<String> {
+        validate(text, Path::new("sidebar.toml"), &variables())
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn should_accept_valid_manifest() {
+        // Arrange
+        let text = r#"
+[[item]]
+label = "AirDrop"
+location = "nwnode://domain-AirDrop"
+
+[[item]]
+label = "Projects"
+location = "${HOME}/Projects"
+hidden = true
+
+[[item]]
+label = "Builds"
+location = "smb://nas.local/builds"
+when = 'hostname matches "build-*"'
+"#;
+
+        // Act & Assert
+        assert_eq!(check(text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn should_locate_syntax_errors() {
+        assert_eq!(
+            check("[[item]]\nlabel = \"Projects\nlocation = \"~/Projects\"\n"),
+            vec!["sidebar.toml:2:18: invalid basic string".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_suggest_known_keys() {
+        assert_eq!(
+            check("[[item]]\nlabel = \"A\"\nlocation = \"/a\"\nhiden = true\n\n[[items]]\n"),
+            vec![
+                "sidebar.toml:6:3: unknown section `items`\n  help: did you mean `item`?"
+                    .to_string(),
+                "sidebar.toml:4:1: unknown key `hiden`\n  help: did you mean `hidden`?".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_missing_and_mistyped_values() {
+        assert_eq!(
+            check("[[item]]\nlabel = 42\nhidden = \"yes\"\n"),
+            vec![
+                "sidebar.toml:3:10: `hidden` must be a boolean, not string".to_string(),
+                "sidebar.toml:2:9: `label` must be a string, not integer".to_string(),
+                "sidebar.toml:1:1: missing `location`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_check_locations_against_targets() {
+        // Arrange
+        let text = r#"[[item]]
+label = "AirDrop"
+location = "airdrop"
+
+[[item]]
+label = "Projects"
+location = "Projects"
+
+[[item]]
+label = "Recents"
+location = "nwnode://domain-AirDorp"
+"#;
+
+        // Act & Assert
+        assert_eq!(
+            check(text),
+            vec![
+                "sidebar.toml:3:12: relative path `airdrop`\n  help: did you mean `nwnode://domain-AirDrop`?"
+                    .to_string(),
+                "sidebar.toml:7:12: relative path `Projects`\n  help: use an absolute path or `~/Projects`"
+                    .to_string(),
+                "sidebar.toml:11:12: unknown special target `nwnode://domain-AirDorp`\n  help: did you mean `nwnode://domain-AirDrop`?"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_duplicate_labels_unless_conditions_differ() {
+        // Arrange
+        let text = r#"[[item]]
+label = "Work"
+location = "~/Work"
+
+[[item]]
+label = "Work"
+location = "~/Work"
+when = 'hostname == "laptop"'
+
+[[item]]
+label = "Work"
+location = "~/Clients"
+"#;
+
+        // Act & Assert
+        assert_eq!(
+            check(text),
+            vec![
+                "sidebar.toml:11:9: duplicate label `Work`, first used on line 2\n  help: rename one of them or give them different `when` conditions"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_conditions_and_variables() {
+        assert_eq!(
+            check("[[item]]\nlabel = \"${CLIENT}\"\nlocation = \"/a\"\nwhen = \"host == 'a'\"\n"),
+            vec![
+                "sidebar.toml:4:8: invalid condition 'host == 'a'': the value must be quoted\n  help: e.g. `hostname matches \"build-*\"`"
+                    .to_string(),
+                "sidebar.toml:2:9: undefined variable 'CLIENT', use ${CLIENT:-fallback} for optional ones"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_unreadable_includes() {
+        assert_eq!(
+            check("[[include]]\npath = \"missing-base.toml\"\n"),
+            vec![
+                "sidebar.toml:2:8: cannot read `missing-base.toml`: No such file or directory (os error 2)\n  help: include paths are relative to this manifest"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_duplicate_labels_across_includes() {
+        // Arrange
+        let dir = TempDir::new("validate");
+        let base = dir.join("base.toml");
+        fs::write(&base, "[[item]]\nlabel = \"Work\"\nlocation = \"~/Work\"\n").unwrap();
+        let text = "[[include]]\npath = \"base.toml\"\n\n[[item]]\nlabel = \"Work\"\nlocation = \"~/Clients\"\n";
+        let path = dir.join("sidebar.toml");
+
+        // Act
+        let diagnostics = validate(text, &path, &variables());
+
+        // Assert
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(Diagnostic::to_string)
+                .collect::<Vec<_>>(),
+            vec![format!(
+                "{}:5:9: duplicate label `Work`, first used on {}:2\n  help: rename one of them or give them different `when` conditions",
+                path.display(),
+                base.display()
+            )]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Declarative activation that shares the sidebar with the user.
+//!
+//! Unlike [`Finder::apply`](super::Finder::apply), activating a manifest leaves items the user
+//! added alone. Only items favkit added in an earlier activation, recorded by target, are
+//! removed once they leave the manifest.
+
+use super::{
+    Convergence, Manifest, Ownership, SidebarItem, Target, dedupe::TargetKey,
+    sort::longest_increasing,
+};
+
+/// Outcome of activating a manifest: how the sidebar changes, and the targets favkit owns
+/// afterwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activation {
+    pub convergence: Convergence,
+    pub owned: Vec<Target>,
+}
+
+/// Plans activating `manifest` on the `current` sidebar, given the targets favkit `owned`
+/// after the previous activation.
+///
+/// Owned items no longer in the manifest are removed. Manifest items keep their place when
+/// already in manifest order; others move, or are added, right after the preceding manifest
+/// item, or at the top for the first one. Items the user added stay where they are. favkit
+/// owns the manifest items it added or already owned.
+pub fn plan(current: &[SidebarItem], manifest: &Manifest, owned: &[Target]) -> Activation {
+    let wanted: Vec<Option<TargetKey>> = manifest
+        .items()
+        .iter()
+        .map(|item| TargetKey::of(item.target()))
+        .collect();
+    let owned: Vec<TargetKey> = owned.iter().filter_map(TargetKey::of).collect();
+
+    let kept: Vec<SidebarItem> = current
+        .iter()
+        .filter(|item| match TargetKey::of(item.target()) {
+            Some(key) => !owned.contains(&key) || wanted.contains(&Some(key)),
+            None => true,
+        })
+        .cloned()
+        .collect();
+
+    let mut unmatched = vec![true; kept.len()];
+    let matched: Vec<Option<usize>> = wanted
+        .iter()
+        .map(|key| {
+            let index = kept.iter().enumerate().position(|(index, item)| {
+                unmatched[index] && key.is_some() && TargetKey::of(item.target()) == *key
+            })?;
+            unmatched[index] = false;
+            Some(index)
+        })
+        .collect();
+    let indices: Vec<usize> = matched.iter().flatten().copied().collect();
+    let mut in_order = longest_increasing(&indices).into_iter();
+    let staying: Vec<bool> = matched
+        .iter()
+        .map(|found| found.is_some() && in_order.next().unwrap_or(false))
+        .collect();
+
+    // Items that do not stay are placed in the group after the preceding staying manifest
+    // item; group 0 is the top of the sidebar and group `i + 1` follows kept item `i`.
+    let mut groups: Vec<Vec<SidebarItem>> = vec![Vec::new(); kept.len() + 1];
+    let mut replaced: Vec<Option<SidebarItem>> = vec![None; kept.len()];
+    let mut moved = vec![false; kept.len()];
+    let mut group = 0;
+    for ((wanted, found), stays) in manifest.items().iter().zip(&matched).zip(&staying) {
+        let item = match found {
+            Some(index) => kept[*index].clone(),
+            None => wanted.clone().with_ownership(Ownership::Managed),
+        }
+        .with_visibility(wanted.visibility());
+        match found {
+            Some(index) if *stays => {
+                replaced[*index] = Some(item);
+                group = index + 1;
+            }
+            Some(index) => {
+                moved[*index] = true;
+                groups[group].push(item);
+            }
+            None => groups[group].push(item),
+        }
+    }
+
+    let mut groups = groups.into_iter();
+    let mut items: Vec<SidebarItem> = groups.next().unwrap_or_default();
+    for (((item, replaced), moved), group) in kept.into_iter().zip(replaced).zip(moved).zip(groups)
+    {
+        if !moved {
+            items.push(replaced.unwrap_or(item));
+        }
+        items.extend(group);
+    }
+
+    let owned_after = manifest
+        .items()
+        .iter()
+        .zip(&matched)
+        .filter(|(item, found)| {
+            found.is_none() || TargetKey::of(item.target()).is_some_and(|key| owned.contains(&key))
+        })
+        .map(|(item, _)| item.target().clone())
+        .collect();
+
+    Activation {
+        convergence: Convergence::new(current.to_vec(), items),
+        owned: owned_after,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::Visibility;
+
+    fn custom(label: &str) -> Target {
+        Target::custom(label, format!("/Users/user/{}", label))
+    }
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            SidebarItem::new(custom("Downloads")).with_id(2),
+            SidebarItem::new(custom("Photos")).with_id(3),
+        ]
+    }
+
+    fn manifest(labels: &[&str]) -> Manifest {
+        labels
+            .iter()
+            .map(|label| SidebarItem::new(custom(label)))
+            .collect()
+    }
+
+    fn labels(items: &[SidebarItem]) -> Vec<&str> {
+        items.iter().map(|item| item.target().label()).collect()
+    }
+
+    #[test]
+    fn should_add_missing_items_and_own_them() {
+        // Act
+        let activation = plan(
+            &sidebar(),
+            &manifest(&["Projects", "Downloads", "Music"]),
+            &[],
+        );
+
+        // Assert
+        assert_eq!(
+            labels(&activation.convergence.after),
+            vec!["Projects", "AirDrop", "Downloads", "Music", "Photos"]
+        );
+        assert_eq!(activation.owned, vec![custom("Projects"), custom("Music")]);
+    }
+
+    #[test]
+    fn should_change_nothing_when_activated_again() {
+        // Arrange
+        let manifest = manifest(&["Projects", "Downloads", "Music"]);
+        let first = plan(&sidebar(), &manifest, &[]);
+
+        // Act
+        let again = plan(&first.convergence.after, &manifest, &first.owned);
+
+        // Assert
+        assert!(!again.convergence.is_changed());
+        assert_eq!(again.owned, first.owned);
+    }
+
+    #[test]
+    fn should_remove_only_owned_items_left_out_of_manifest() {
+        // Arrange
+        let mut current = sidebar();
+        current.push(SidebarItem::new(Target::custom("Code", "/Users/user/Projects/")).with_id(4));
+
+        // Act
+        let activation = plan(&current, &manifest(&[]), &[custom("Projects")]);
+
+        // Assert
+        assert_eq!(
+            labels(&activation.convergence.after),
+            vec!["AirDrop", "Downloads", "Photos"]
+        );
+        assert_eq!(activation.owned, Vec::new());
+    }
+
+    #[test]
+    fn should_not_own_items_the_user_added() {
+        // Act
+        let activation = plan(&sidebar(), &manifest(&["Downloads"]), &[]);
+        let dropped = plan(&sidebar(), &manifest(&[]), &activation.owned);
+
+        // Assert
+        assert_eq!(activation.owned, Vec::new());
+        assert!(!dropped.convergence.is_changed());
+    }
+
+    #[test]
+    fn should_move_items_out_of_manifest_order_and_set_visibility() {
+        // Arrange
+        let manifest = Manifest::new([
+            SidebarItem::new(custom("Photos")),
+            SidebarItem::new(custom("Downloads")).with_visibility(Visibility::Hidden),
+        ]);
+
+        // Act
+        let activation = plan(&sidebar(), &manifest, &[]);
+
+        // Assert
+        assert_eq!(
+            activation.convergence.after,
+            vec![
+                SidebarItem::new(custom("Photos")).with_id(3),
+                SidebarItem::new(Target::AirDrop).with_id(1),
+                SidebarItem::new(custom("Downloads"))
+                    .with_id(2)
+                    .with_visibility(Visibility::Hidden),
+            ]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! A [`Finder`] for Tokio services, enabled by the `async` feature.
+//!
+//! Core Services calls block, and resolving an item on an unmounted network volume can take
+//! seconds. [`AsyncFinder`] queues each call on a [`SharedFinder`]'s thread and awaits its reply,
+//! so neither the runtime's workers nor its blocking pool wait on it, and gives up after a
+//! timeout.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_core::Stream;
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{self, MissedTickBehavior},
+};
+
+use super::{Change, Finder, FinderError, Result, Seed, SharedFinder, SidebarItem, diff, shared};
+
+/// How long a call may take unless [`AsyncFinder::with_timeout`] sets another limit.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Handle to a [`Finder`] whose calls can be awaited.
+///
+/// ```no_run
+/// use favkit::{Finder, finder::AsyncFinder};
+///
+/// # async fn run() -> favkit::finder::Result<()> {
+/// let finder = AsyncFinder::spawn(Finder::default)?;
+/// for item in finder.get_favorites_list().await? {
+///     println!("{}", item);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncFinder {
+    finder: SharedFinder,
+    timeout: Duration,
+}
+
+/// What changed in the sidebar since the previous event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    /// Seed of the sidebar after the changes.
+    pub seed: Seed,
+    pub changes: Vec<Change>,
+}
+
+impl From<SharedFinder> for AsyncFinder {
+    fn from(finder: SharedFinder) -> Self {
+        Self {
+            finder,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl AsyncFinder {
+    /// Builds the [`Finder`] with `make` on a dedicated thread, like [`SharedFinder::spawn`].
+    pub fn spawn(make: impl FnOnce() -> Finder + Send + 'static) -> Result<Self> {
+        SharedFinder::spawn(make).map(Self::from)
+    }
+
+    /// Sets how long a call may take before it fails with [`FinderError::TimedOut`].
+    ///
+    /// The timeout only stops the wait: the Finder has a single thread, and a call that timed
+    /// out keeps it busy until the call returns. Calls made meanwhile queue behind it and may
+    /// time out in turn without having started, so pick a limit above the slowest expected call,
+    /// such as resolving an item on a network volume.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs `call` on the Finder's thread and waits for its result without blocking the runtime.
+    ///
+    /// A call that times out keeps running on the Finder's thread, and calls made after it wait
+    /// until it finishes.
+    pub async fn call<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&Finder) -> T + Send + 'static,
+    ) -> Result<T> {
+        let (reply, result) = oneshot::channel();
+        self.finder.submit(move |finder| {
+            // The caller may have timed out; the result is dropped then
+            let _ = reply.send(call(finder));
+        })?;
+        match time::timeout(self.timeout, result).await {
+            Ok(Ok(result)) => Ok(result),
+            // The thread stopped before replying
+            Ok(Err(_)) => Err(shared::stopped()),
+            Err(_) => Err(FinderError::TimedOut(self.timeout)),
+        }
+    }
+
+    pub async fn get_favorites_list(&self) -> Result<Vec<SidebarItem>> {
+        self.call(|finder| finder.get_favorites_list()).await?
+    }
+
+    pub async fn seed(&self) -> Result<Seed> {
+        self.call(|finder| finder.seed()).await?
+    }
+
+    /// Watches the sidebar for changes by anyone, checking its seed every `interval` and only
+    /// listing it again when the seed moved.
+    ///
+    /// The sidebar is read before this returns, so the first event reports what changed after
+    /// the call. Watching stops when the stream is dropped.
+    pub async fn changes(&self, interval: Duration) -> Result<Changes> {
+        let (mut seed, mut items) = self.snapshot().await?;
+        let (sender, events) = mpsc::channel(1);
+        let finder = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticks = time::interval(interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            while !sender.is_closed() {
+                ticks.tick().await;
+                let event = match finder.seed().await {
+                    Ok(current) if current == seed => continue,
+                    Ok(_) => finder.snapshot().await.map(|(current, listed)| {
+                        let changes = diff::changes(&items, &listed);
+                        (seed, items) = (current, listed);
+                        ChangeEvent {
+                            seed: current,
+                            changes,
+                        }
+                    }),
+                    Err(error) => Err(error),
+                };
+                if event.as_ref().is_ok_and(|event| event.changes.is_empty()) {
+                    continue;
+                }
+                if sender.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Changes { events })
+    }
+
+    /// The seed and items, read together.
+    async fn snapshot(&self) -> Result<(Seed, Vec<SidebarItem>)> {
+        self.call(|finder| Ok((finder.seed()?, finder.get_favorites_list()?)))
+            .await?
+    }
+}
+
+/// Stream of [`ChangeEvent`]s from [`AsyncFinder::changes`].
+///
+/// A failed check is yielded as an error and watching goes on.
+#[derive(Debug)]
+pub struct Changes {
+    events: mpsc::Receiver<Result<ChangeEvent>>,
+}
+
+impl Changes {
+    /// Waits for the next event, like [`Stream::poll_next`] without needing a stream extension.
+    pub async fn recv(&mut self) -> Option<Result<ChangeEvent>> {
+        self.events.recv().await
+    }
+}
+
+impl Stream for Changes {
+    type Item = Result<ChangeEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Fails to compile unless `T` can be shared between tasks.
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn should_be_shareable_between_tasks() {
+        assert_send_sync::<AsyncFinder>();
+        assert_send_sync::<Changes>();
+        assert_send_sync::<ChangeEvent>();
+    }
+
+    #[tokio::test]
+    async fn should_report_stopped_thread() {
+        // Arrange
+        let finder = AsyncFinder::spawn(|| panic!("no backend")).unwrap();
+
+        // Act
+        let result = finder.seed().await;
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(FinderError::Thread(
+                "favkit-finder thread has stopped".to_string()
+            ))
+        );
+    }
+}
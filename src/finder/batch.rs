@@ -0,0 +1,780 @@
+//! Several sidebar changes applied as one unit.
+//!
+//! A batch is validated against a single listing of the sidebar, so selectors are resolved once
+//! instead of once per operation, and it is then executed in order by item id. If any step
+//! fails, the sidebar is restored from that listing.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use thiserror::Error;
+
+use super::{
+    Change, FinderError, ItemId, Ownership, Position, Result, Selector, SidebarItem, Target,
+    Visibility, diff,
+    favorites::FavoritesApi,
+    path::{contract_home, expand_home},
+};
+use crate::system::favorites::MacOsUrl;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum OperationParseError {
+    #[error("empty operation")]
+    Empty,
+    #[error("unknown operation '{0}', expected add, remove, move or rename")]
+    UnknownOperation(String),
+    #[error("missing {0}")]
+    MissingArgument(&'static str),
+    #[error("unexpected argument '{0}'")]
+    UnexpectedArgument(String),
+    #[error("unclosed quote")]
+    UnclosedQuote,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BatchError {
+    #[error(transparent)]
+    Finder(#[from] FinderError),
+    #[error("line {line}: {source}")]
+    Parse {
+        line: usize,
+        source: OperationParseError,
+    },
+    #[error("operation {number} ({operation}): {source}")]
+    Invalid {
+        number: usize,
+        operation: String,
+        source: FinderError,
+    },
+    #[error("operation {number} failed, sidebar restored: {source}")]
+    RolledBack { number: usize, source: FinderError },
+    #[error("operation {number} failed ({source}) and restoring the sidebar failed: {rollback}")]
+    RollbackFailed {
+        number: usize,
+        source: FinderError,
+        rollback: FinderError,
+    },
+}
+
+/// Where an operation puts an item.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Destination {
+    First,
+    #[default]
+    Last,
+    After(Selector),
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First => write!(f, "first"),
+            Self::Last => write!(f, "last"),
+            Self::After(selector) => write!(f, "after {}", quote(&selector.to_string())),
+        }
+    }
+}
+
+/// A single change of a batch.
+///
+/// Parsed from one line of text, with words containing spaces in double or single quotes:
+///
+/// ```text
+/// add <label> <path or URL> [first | last | after <selector>]
+/// remove <selector>
+/// move <selector> first | last | after <selector>
+/// rename <selector> <label>
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Add { target: Target, to: Destination },
+    Remove(Selector),
+    Move { item: Selector, to: Destination },
+    Rename { item: Selector, label: String },
+}
+
+/// Target for a path (`~` is expanded) or URL, as accepted by `add`.
+pub fn target(label: &str, location: &str) -> Target {
+    let location = expand_home(location);
+    MacOsUrl::from(location.as_str()).into_target(label)
+}
+
+/// Path (with the home directory shortened to `~`) or URL of a target, as accepted by
+/// [`target`]. Unresolved targets have none.
+pub fn location(target: &Target) -> Option<String> {
+    match MacOsUrl::try_from(target).ok()? {
+        MacOsUrl::Custom(path) => Some(contract_home(&path)),
+        url => Some(url.to_string()),
+    }
+}
+
+impl Operation {
+    /// Adds an item for a path (`~` is expanded) or URL.
+    pub fn add(label: &str, location: &str, to: Destination) -> Self {
+        Self::Add {
+            target: target(label, location),
+            to,
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = OperationParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let words = split_words(s)?;
+        let mut words = words.iter().map(String::as_str);
+        let name = words.next().ok_or(OperationParseError::Empty)?;
+        let mut next = |argument| {
+            words
+                .next()
+                .ok_or(OperationParseError::MissingArgument(argument))
+        };
+
+        let operation = match name {
+            "add" => {
+                let label = next("label")?;
+                let location = next("path or URL")?;
+                let to = destination(&mut words)?.unwrap_or_default();
+                Self::add(label, location, to)
+            }
+            "remove" => Self::Remove(selector(next("selector")?)),
+            "move" => {
+                let item = selector(next("selector")?);
+                let to = destination(&mut words)?
+                    .ok_or(OperationParseError::MissingArgument("destination"))?;
+                Self::Move { item, to }
+            }
+            "rename" => Self::Rename {
+                item: selector(next("selector")?),
+                label: next("label")?.to_string(),
+            },
+            other => return Err(OperationParseError::UnknownOperation(other.to_string())),
+        };
+
+        match words.next() {
+            Some(extra) => Err(OperationParseError::UnexpectedArgument(extra.to_string())),
+            None => Ok(operation),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add { target, to } => {
+                let location = match target.path() {
+                    Some(path) => path.to_string(),
+                    None => MacOsUrl::try_from(target)
+                        .map(|url| url.to_string())
+                        .unwrap_or_default(),
+                };
+                write!(
+                    f,
+                    "add {} {} {}",
+                    quote(target.label()),
+                    quote(&location),
+                    to
+                )
+            }
+            Self::Remove(selector) => write!(f, "remove {}", quote(&selector.to_string())),
+            Self::Move { item, to } => write!(f, "move {} {}", quote(&item.to_string()), to),
+            Self::Rename { item, label } => {
+                write!(f, "rename {} {}", quote(&item.to_string()), quote(label))
+            }
+        }
+    }
+}
+
+fn destination<'a>(
+    words: &mut impl Iterator<Item = &'a str>,
+) -> std::result::Result<Option<Destination>, OperationParseError> {
+    match words.next() {
+        None => Ok(None),
+        Some("first") => Ok(Some(Destination::First)),
+        Some("last") => Ok(Some(Destination::Last)),
+        Some("after") => {
+            let selector = words.next().ok_or(OperationParseError::MissingArgument(
+                "selector after 'after'",
+            ))?;
+            Ok(Some(Destination::After(self::selector(selector))))
+        }
+        Some(other) => Err(OperationParseError::UnexpectedArgument(other.to_string())),
+    }
+}
+
+fn selector(word: &str) -> Selector {
+    let Ok(selector) = word.parse();
+    selector
+}
+
+/// Splits a line into words, honouring double and single quotes and backslash escapes.
+fn split_words(line: &str) -> std::result::Result<Vec<String>, OperationParseError> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some('"') | None, '\\') => {
+                let escaped = chars.next().unwrap_or('\\');
+                word.get_or_insert_default().push(escaped);
+            }
+            (Some(_), c) => word.get_or_insert_default().push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_default().push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(OperationParseError::UnclosedQuote);
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Quotes a word for [`split_words`] when it would not survive as is.
+fn quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && !word
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'));
+    if plain {
+        word.to_string()
+    } else {
+        format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Parses one operation per line, skipping blank lines and `#` comments.
+pub fn parse_lines(input: &str) -> std::result::Result<Vec<Operation>, BatchError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(index, line)| {
+            line.parse().map_err(|source| BatchError::Parse {
+                line: index + 1,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// An item as seen while planning: one that already exists, or the one added by a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Ref {
+    Existing(ItemId),
+    Added(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    First,
+    Last,
+    After(Ref),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Add {
+        target: Target,
+        after: Anchor,
+    },
+    Remove(Ref),
+    Move {
+        item: Ref,
+        after: Anchor,
+    },
+    Rename {
+        item: Ref,
+        target: Target,
+        visibility: Visibility,
+        ownership: Ownership,
+    },
+}
+
+/// A validated batch, ready to be executed against the sidebar it was planned for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    snapshot: Vec<SidebarItem>,
+    steps: Vec<Step>,
+}
+
+impl Plan {
+    /// Checks every operation against the listed items, applying them in memory one by one.
+    pub fn new(
+        snapshot: Vec<SidebarItem>,
+        operations: &[Operation],
+    ) -> std::result::Result<Self, BatchError> {
+        let mut working: Vec<(Ref, SidebarItem)> = snapshot
+            .iter()
+            .filter_map(|item| Some((Ref::Existing(item.id()?), item.clone())))
+            .collect();
+        let mut steps = Vec::with_capacity(operations.len());
+
+        for (index, operation) in operations.iter().enumerate() {
+            let step = plan_step(&mut working, index, operation).map_err(|source| {
+                BatchError::Invalid {
+                    number: index + 1,
+                    operation: operation.to_string(),
+                    source,
+                }
+            })?;
+            steps.push(step);
+        }
+        Ok(Self { snapshot, steps })
+    }
+
+    /// The sidebar the plan was validated against and is restored to on failure.
+    pub fn snapshot(&self) -> &[SidebarItem] {
+        &self.snapshot
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Runs the steps in order as one [`FavoritesApi::batch`], restoring the snapshot if one
+    /// of them fails.
+    pub(super) fn execute(
+        &self,
+        favorites: &impl FavoritesApi,
+    ) -> std::result::Result<(), BatchError> {
+        favorites.batch(|favorites| self.run(favorites))
+    }
+
+    fn run(&self, favorites: &impl FavoritesApi) -> std::result::Result<(), BatchError> {
+        let mut ids = HashMap::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            if let Err(source) = run_step(favorites, &mut ids, index, step) {
+                let number = index + 1;
+                return Err(match restore(favorites, &self.snapshot) {
+                    Ok(()) => BatchError::RolledBack { number, source },
+                    Err(rollback) => BatchError::RollbackFailed {
+                        number,
+                        source,
+                        rollback,
+                    },
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn plan_step(
+    working: &mut Vec<(Ref, SidebarItem)>,
+    index: usize,
+    operation: &Operation,
+) -> Result<Step> {
+    match operation {
+        Operation::Add { target, to } => {
+            let after = anchor(working, to)?;
+            insert(
+                working,
+                after,
+                Ref::Added(index),
+                SidebarItem::new(target.clone()).with_ownership(Ownership::Managed),
+            );
+            Ok(Step::Add {
+                target: target.clone(),
+                after,
+            })
+        }
+        Operation::Remove(selector) => {
+            let position = locate(working, selector)?;
+            let (item, _) = working.remove(position);
+            Ok(Step::Remove(item))
+        }
+        Operation::Move { item, to } => {
+            let position = locate(working, item)?;
+            let (item, sidebar_item) = working.remove(position);
+            let after = anchor(working, to)?;
+            insert(working, after, item, sidebar_item);
+            Ok(Step::Move { item, after })
+        }
+        Operation::Rename { item, label } => {
+            let position = locate(working, item)?;
+            let (reference, sidebar_item) = &mut working[position];
+            let target = match sidebar_item.target() {
+                Target::Custom { path, .. } => Target::custom(label, path),
+                Target::Network { url, .. } => Target::network(label, url),
+                _ => return Err(FinderError::CannotRename(item.clone())),
+            };
+            let visibility = sidebar_item.visibility();
+            let ownership = sidebar_item.ownership();
+            // The renamed item is a new entry with a new id once executed.
+            *sidebar_item = SidebarItem::new(target.clone())
+                .with_visibility(visibility)
+                .with_ownership(ownership);
+            Ok(Step::Rename {
+                item: *reference,
+                target,
+                visibility,
+                ownership,
+            })
+        }
+    }
+}
+
+fn locate(working: &[(Ref, SidebarItem)], selector: &Selector) -> Result<usize> {
+    let mut matches = working
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, item))| selector.matches(item))
+        .map(|(position, _)| position);
+
+    match (matches.next(), matches.next()) {
+        (Some(position), None) => Ok(position),
+        (None, _) => Err(FinderError::NoMatch(selector.clone())),
+        (Some(_), Some(_)) => Err(FinderError::AmbiguousSelector(selector.clone())),
+    }
+}
+
+fn anchor(working: &[(Ref, SidebarItem)], destination: &Destination) -> Result<Anchor> {
+    match destination {
+        Destination::First => Ok(Anchor::First),
+        Destination::Last => Ok(Anchor::Last),
+        Destination::After(selector) => {
+            let position = locate(working, selector)?;
+            Ok(Anchor::After(working[position].0))
+        }
+    }
+}
+
+fn insert(
+    working: &mut Vec<(Ref, SidebarItem)>,
+    after: Anchor,
+    item: Ref,
+    sidebar_item: SidebarItem,
+) {
+    let position = match after {
+        Anchor::First => 0,
+        Anchor::Last => working.len(),
+        Anchor::After(anchor) => working
+            .iter()
+            .position(|(reference, _)| *reference == anchor)
+            .map_or(working.len(), |position| position + 1),
+    };
+    working.insert(position, (item, sidebar_item));
+}
+
+fn run_step(
+    favorites: &impl FavoritesApi,
+    ids: &mut HashMap<Ref, ItemId>,
+    index: usize,
+    step: &Step,
+) -> Result<()> {
+    let id = |ids: &HashMap<Ref, ItemId>, reference: Ref| match (
+        ids.get(&reference).copied(),
+        reference,
+    ) {
+        (Some(id), _) | (None, Ref::Existing(id)) => id,
+        (None, Ref::Added(_)) => unreachable!("planning orders additions before their uses"),
+    };
+    let position = |ids: &HashMap<Ref, ItemId>, anchor: Anchor| match anchor {
+        Anchor::First => Position::First,
+        Anchor::Last => Position::Last,
+        Anchor::After(reference) => Position::After(id(ids, reference)),
+    };
+
+    match step {
+        Step::Add { target, after } => {
+            let new_id = favorites.insert_item(target, position(ids, *after))?;
+            favorites.set_ownership(new_id, Ownership::Managed)?;
+            ids.insert(Ref::Added(index), new_id);
+        }
+        Step::Remove(item) => favorites.remove_item(id(ids, *item))?,
+        Step::Move { item, after } => favorites.move_item(id(ids, *item), position(ids, *after))?,
+        Step::Rename {
+            item,
+            target,
+            visibility,
+            ownership,
+        } => {
+            // Core Services cannot relabel an entry, so the renamed one replaces it in place.
+            let old_id = id(ids, *item);
+            let new_id = favorites.insert_item(target, Position::After(old_id))?;
+            if *visibility == Visibility::Hidden {
+                favorites.set_visibility(new_id, Visibility::Hidden)?;
+            }
+            if *ownership == Ownership::Managed {
+                favorites.set_ownership(new_id, Ownership::Managed)?;
+            }
+            favorites.remove_item(old_id)?;
+            ids.insert(*item, new_id);
+        }
+    }
+    Ok(())
+}
+
+/// Brings the sidebar back to the listed items.
+///
+/// Current items are matched to listed ones by id first and by target second, so items re-added
+/// by an earlier restore are recognised. Only what differs is changed, as by [`apply`]; listed
+/// items without a match are added again and therefore get new ids.
+pub(super) fn restore(favorites: &impl FavoritesApi, snapshot: &[SidebarItem]) -> Result<()> {
+    favorites.batch(|favorites| {
+        let current = favorites.list_items()?;
+        let after = matched(&current, snapshot);
+        apply(favorites, &after, &diff::changes(&current, &after))
+    })
+}
+
+/// The listed items expressed with the current items they match, which keep their ids.
+fn matched(current: &[SidebarItem], snapshot: &[SidebarItem]) -> Vec<SidebarItem> {
+    let mut unmatched: Vec<&SidebarItem> = current.iter().collect();
+    let mut take = |matches: &dyn Fn(&SidebarItem) -> bool| {
+        let position = unmatched.iter().position(|item| matches(item))?;
+        Some(unmatched.remove(position))
+    };
+
+    let by_id: Vec<_> = snapshot
+        .iter()
+        .map(|wanted| take(&|item| item.id().is_some() && item.id() == wanted.id()))
+        .collect();
+    snapshot
+        .iter()
+        .zip(by_id)
+        .map(|(wanted, found)| {
+            match found.or_else(|| take(&|item| item.target() == wanted.target())) {
+                Some(item) => item.clone(),
+                None => SidebarItem::new(wanted.target().clone()),
+            }
+            .with_visibility(wanted.visibility())
+            .with_ownership(wanted.ownership())
+        })
+        .collect()
+}
+
+/// Makes the sidebar listed by `after` from the current one by carrying out `changes`, as
+/// found by [`diff::changes`] between the two.
+///
+/// Removed items are removed and added ones inserted after the item before them; only items
+/// outside the longest run still in order are moved, and updated ones only get the visibility
+/// and ownership that differ.
+pub(super) fn apply(
+    favorites: &impl FavoritesApi,
+    after: &[SidebarItem],
+    changes: &[Change],
+) -> Result<()> {
+    favorites.batch(|favorites| {
+        let mut placed: Vec<Option<ItemId>> = after.iter().map(SidebarItem::id).collect();
+        let position = |placed: &[Option<ItemId>], index: usize| match index.checked_sub(1) {
+            None => Position::First,
+            Some(previous) => Position::After(
+                placed[previous].expect("changes place items in the order they are listed"),
+            ),
+        };
+
+        for change in changes {
+            match change {
+                Change::Removed { item } => {
+                    if let Some(id) = item.id() {
+                        favorites.remove_item(id)?;
+                    }
+                }
+                Change::Added { index, item } => {
+                    let id = favorites.insert_item(item.target(), position(&placed, *index))?;
+                    if item.is_hidden() {
+                        favorites.set_visibility(id, Visibility::Hidden)?;
+                    }
+                    if item.is_managed() {
+                        favorites.set_ownership(id, Ownership::Managed)?;
+                    }
+                    placed[*index] = Some(id);
+                }
+                Change::Moved { index, item } => {
+                    if let Some(id) = item.id() {
+                        favorites.move_item(id, position(&placed, *index))?;
+                    }
+                }
+                Change::Updated { before, after } => {
+                    let Some(id) = after.id() else { continue };
+                    if before.visibility() != after.visibility() {
+                        favorites.set_visibility(id, after.visibility())?;
+                    }
+                    if before.ownership() != after.ownership() {
+                        favorites.set_ownership(id, after.ownership())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            SidebarItem::new(Target::custom("Projects", "/Users/user/Projects")).with_id(2),
+            SidebarItem::new(Target::custom("Downloads", "/Users/user/Downloads")).with_id(3),
+        ]
+    }
+
+    fn operations(lines: &str) -> Vec<Operation> {
+        parse_lines(lines).unwrap()
+    }
+
+    fn planned_labels(plan: &[Operation]) -> Vec<String> {
+        let mut working: Vec<(Ref, SidebarItem)> = sidebar()
+            .into_iter()
+            .map(|item| (Ref::Existing(item.id().unwrap()), item))
+            .collect();
+        for (index, operation) in plan.iter().enumerate() {
+            plan_step(&mut working, index, operation).unwrap();
+        }
+        working
+            .iter()
+            .map(|(_, item)| item.target().label().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_operations() {
+        // Act
+        let parsed = operations(
+            "# tidy up\n\
+             add Work ~/Work after Projects\n\
+             \n\
+             remove id:3\n\
+             move \"Projects\" first\n\
+             rename Projects 'My Projects'\n",
+        );
+
+        // Assert
+        assert_eq!(
+            parsed,
+            vec![
+                Operation::Add {
+                    target: Target::custom("Work", expand_home("~/Work")),
+                    to: Destination::After(Selector::Label("Projects".to_string())),
+                },
+                Operation::Remove(Selector::Id(ItemId::from(3))),
+                Operation::Move {
+                    item: Selector::Label("Projects".to_string()),
+                    to: Destination::First,
+                },
+                Operation::Rename {
+                    item: Selector::Label("Projects".to_string()),
+                    label: "My Projects".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_add_network_shares_last_by_default() {
+        assert_eq!(
+            "add NAS smb://nas.local/share".parse::<Operation>(),
+            Ok(Operation::Add {
+                target: Target::network("NAS", "smb://nas.local/share"),
+                to: Destination::Last,
+            })
+        );
+    }
+
+    #[test]
+    fn should_report_line_of_invalid_operation() {
+        assert_eq!(
+            parse_lines("remove Projects\n\nmove Projects\n"),
+            Err(BatchError::Parse {
+                line: 3,
+                source: OperationParseError::MissingArgument("destination"),
+            })
+        );
+        assert_eq!(
+            "copy Projects".parse::<Operation>(),
+            Err(OperationParseError::UnknownOperation("copy".to_string()))
+        );
+        assert_eq!(
+            "remove Projects now".parse::<Operation>(),
+            Err(OperationParseError::UnexpectedArgument("now".to_string()))
+        );
+        assert_eq!(
+            "rename Projects \"My Projects".parse::<Operation>(),
+            Err(OperationParseError::UnclosedQuote)
+        );
+    }
+
+    #[test]
+    fn should_format_operations_that_parse_back() {
+        let lines = [
+            "add \"My Work\" /Users/user/My\\ Work after id:2",
+            "remove Projects",
+            "move /Users/user/Projects last",
+            "rename Projects \"Say \\\"hi\\\"\"",
+        ];
+        for line in lines {
+            let operation: Operation = line.parse().unwrap();
+            assert_eq!(operation.to_string().parse(), Ok(operation));
+        }
+    }
+
+    #[test]
+    fn should_plan_operations_in_order() {
+        // Act
+        let labels = planned_labels(&operations(
+            "add Work /Users/user/Work after Projects\n\
+             move Downloads first\n\
+             rename Work Office\n\
+             remove AirDrop\n",
+        ));
+
+        // Assert
+        assert_eq!(labels, vec!["Downloads", "Projects", "Office"]);
+    }
+
+    #[test]
+    fn should_reject_operation_on_item_removed_earlier() {
+        // Act
+        let result = Plan::new(
+            sidebar(),
+            &operations("remove Projects\nrename Projects Code\n"),
+        );
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(BatchError::Invalid {
+                number: 2,
+                operation: "rename Projects Code".to_string(),
+                source: FinderError::NoMatch(Selector::Label("Projects".to_string())),
+            })
+        );
+    }
+
+    #[test]
+    fn should_refuse_to_rename_built_in_items() {
+        // Act
+        let result = Plan::new(sidebar(), &operations("rename AirDrop Beam\n"));
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(BatchError::Invalid {
+                source: FinderError::CannotRename(_),
+                ..
+            })
+        ));
+    }
+}
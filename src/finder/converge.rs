@@ -0,0 +1,274 @@
+//! Converging one item to a desired state, the way configuration management tools do.
+
+use std::fmt;
+
+use super::{
+    Change, Destination, FinderError, Ownership, Result, Selector, SidebarItem, Target, Visibility,
+    dedupe::TargetKey, diff,
+};
+
+/// Whether an item should be in the sidebar, and how.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Presence {
+    /// The item is there, at `position` if given and shown or hidden as asked; items that
+    /// must be added go last unless placed.
+    Present {
+        position: Option<Destination>,
+        visibility: Option<Visibility>,
+    },
+    /// No item points at the target.
+    Absent,
+}
+
+/// Desired state of the sidebar item pointing at `target`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesiredItem {
+    pub target: Target,
+    pub presence: Presence,
+}
+
+impl fmt::Display for DesiredItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.presence {
+            Presence::Present { .. } => "present",
+            Presence::Absent => "absent",
+        };
+        write!(f, "ensure {} {}", self.target.label(), state)
+    }
+}
+
+/// The sidebar before and after converging, and what differs between them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Convergence {
+    pub before: Vec<SidebarItem>,
+    pub after: Vec<SidebarItem>,
+    pub changes: Vec<Change>,
+}
+
+impl Convergence {
+    pub fn new(before: Vec<SidebarItem>, after: Vec<SidebarItem>) -> Self {
+        let changes = diff::changes(&before, &after);
+        Self {
+            before,
+            after,
+            changes,
+        }
+    }
+
+    pub fn is_changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+/// Returns the sidebar with `desired` in place; equal to `current` when it already is.
+///
+/// Items are matched to the target ignoring labels and trailing slashes. An absent item has
+/// every match removed; a present one keeps its first match, its id and its label, and only
+/// moves when a position is given. Fails when the position refers to an item that does not
+/// exist or is ambiguous.
+pub fn plan(current: &[SidebarItem], desired: &DesiredItem) -> Result<Vec<SidebarItem>> {
+    let key = TargetKey::of(&desired.target);
+    let matches = |item: &SidebarItem| key.is_some() && TargetKey::of(item.target()) == key;
+
+    let (position, visibility) = match &desired.presence {
+        Presence::Absent => {
+            return Ok(current
+                .iter()
+                .filter(|item| !matches(item))
+                .cloned()
+                .collect());
+        }
+        Presence::Present {
+            position,
+            visibility,
+        } => (position, visibility),
+    };
+
+    let mut items = current.to_vec();
+    let existing = items.iter().position(matches);
+    let item = match existing {
+        Some(index) => items[index].clone(),
+        None => SidebarItem::new(desired.target.clone()).with_ownership(Ownership::Managed),
+    };
+    let item = match visibility {
+        Some(visibility) => item.with_visibility(*visibility),
+        None => item,
+    };
+
+    let index = match (existing, position) {
+        (Some(index), None) => {
+            items[index] = item;
+            return Ok(items);
+        }
+        (Some(index), Some(_)) => {
+            items.remove(index);
+            insertion_index(&items, position.as_ref())?
+        }
+        (None, _) => insertion_index(&items, position.as_ref())?,
+    };
+    items.insert(index, item);
+    Ok(items)
+}
+
+fn insertion_index(items: &[SidebarItem], position: Option<&Destination>) -> Result<usize> {
+    match position {
+        Some(Destination::First) => Ok(0),
+        None | Some(Destination::Last) => Ok(items.len()),
+        Some(Destination::After(selector)) => anchor(items, selector).map(|index| index + 1),
+    }
+}
+
+fn anchor(items: &[SidebarItem], selector: &Selector) -> Result<usize> {
+    let mut matches = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| selector.matches(item));
+
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => Ok(index),
+        (None, _) => Err(FinderError::NoMatch(selector.clone())),
+        (Some(_), Some(_)) => Err(FinderError::AmbiguousSelector(selector.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn custom(id: u32, label: &str) -> SidebarItem {
+        SidebarItem::new(Target::custom(label, format!("/Users/user/{}", label))).with_id(id)
+    }
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::Applications).with_id(1),
+            custom(2, "Projects"),
+            custom(3, "Downloads"),
+        ]
+    }
+
+    fn present(label: &str, position: Option<Destination>) -> DesiredItem {
+        DesiredItem {
+            target: Target::custom(label, format!("/Users/user/{}/", label)),
+            presence: Presence::Present {
+                position,
+                visibility: None,
+            },
+        }
+    }
+
+    fn labels(items: &[SidebarItem]) -> Vec<&str> {
+        items.iter().map(|item| item.target().label()).collect()
+    }
+
+    #[test]
+    fn should_leave_present_item_in_place() {
+        // Act
+        let planned = plan(&sidebar(), &present("Projects", None));
+
+        // Assert
+        assert_eq!(planned, Ok(sidebar()));
+    }
+
+    #[test]
+    fn should_add_missing_item_last() {
+        // Act
+        let planned = plan(&sidebar(), &present("Music", None)).unwrap();
+
+        // Assert
+        assert_eq!(
+            labels(&planned),
+            vec!["Applications", "Projects", "Downloads", "Music"]
+        );
+        assert_eq!(planned[3].id(), None);
+    }
+
+    #[test]
+    fn should_move_item_after_anchor() {
+        // Arrange
+        let after = Destination::After(Selector::Label("Applications".into()));
+
+        // Act
+        let planned = plan(&sidebar(), &present("Downloads", Some(after.clone()))).unwrap();
+        let again = plan(&planned, &present("Downloads", Some(after))).unwrap();
+
+        // Assert
+        assert_eq!(
+            labels(&planned),
+            vec!["Applications", "Downloads", "Projects"]
+        );
+        assert_eq!(again, planned);
+    }
+
+    #[test]
+    fn should_set_visibility() {
+        // Arrange
+        let desired = DesiredItem {
+            target: Target::Applications,
+            presence: Presence::Present {
+                position: None,
+                visibility: Some(Visibility::Hidden),
+            },
+        };
+
+        // Act
+        let planned = plan(&sidebar(), &desired).unwrap();
+
+        // Assert
+        assert!(planned[0].is_hidden());
+    }
+
+    #[test]
+    fn should_remove_every_match_of_absent_item() {
+        // Arrange
+        let mut current = sidebar();
+        current.push(custom(4, "Projects"));
+        let desired = DesiredItem {
+            target: Target::custom("Code", "/Users/user/Projects"),
+            presence: Presence::Absent,
+        };
+
+        // Act
+        let planned = plan(&current, &desired).unwrap();
+
+        // Assert
+        assert_eq!(labels(&planned), vec!["Applications", "Downloads"]);
+    }
+
+    #[test]
+    fn should_fail_for_missing_anchor() {
+        // Arrange
+        let after = Destination::After(Selector::Label("Desktop".into()));
+
+        // Act
+        let planned = plan(&sidebar(), &present("Projects", Some(after)));
+
+        // Assert
+        assert_eq!(
+            planned,
+            Err(FinderError::NoMatch(Selector::Label("Desktop".into())))
+        );
+    }
+
+    #[test]
+    fn should_report_changes() {
+        // Act
+        let convergence = Convergence::new(
+            sidebar(),
+            plan(&sidebar(), &present("Music", Some(Destination::First))).unwrap(),
+        );
+
+        // Assert
+        assert!(convergence.is_changed());
+        assert_eq!(
+            convergence.changes,
+            vec![Change::Added {
+                index: 0,
+                item: SidebarItem::new(Target::custom("Music", "/Users/user/Music/"))
+                    .with_ownership(Ownership::Managed)
+            }]
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use super::{SidebarItem, Target, path::normalize};
+
+/// Decides which of several items pointing at the same target survives a dedupe.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DedupePolicy {
+    /// Keeps the item closest to the top of the sidebar.
+    #[default]
+    First,
+    /// Keeps the item closest to the bottom of the sidebar.
+    Last,
+    /// Keeps the first item with this label, or the first item when none has it.
+    PreferLabel(String),
+}
+
+/// What makes two items duplicates of each other.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(super) enum TargetKey {
+    AirDrop,
+    Recents,
+    Applications,
+    Path(PathBuf),
+    Url(String),
+}
+
+impl TargetKey {
+    /// Unresolved items have no known target and are never duplicates.
+    pub(super) fn of(target: &Target) -> Option<Self> {
+        match target {
+            Target::AirDrop => Some(Self::AirDrop),
+            Target::Recents => Some(Self::Recents),
+            Target::Applications => Some(Self::Applications),
+            Target::Custom { path, .. } => Some(Self::Path(normalize(path))),
+            Target::Network { url, .. } => Some(Self::Url(url.clone())),
+            Target::Unresolved { .. } => None,
+        }
+    }
+}
+
+/// Picks the items to remove so that every target appears only once.
+///
+/// Returned items keep their sidebar order; removing them leaves the survivors in their
+/// original relative order.
+pub fn duplicates(items: &[SidebarItem], policy: &DedupePolicy) -> Vec<SidebarItem> {
+    let mut groups: HashMap<TargetKey, Vec<usize>> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        if let Some(key) = TargetKey::of(item.target()) {
+            groups.entry(key).or_default().push(index);
+        }
+    }
+
+    let mut removed: Vec<usize> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flat_map(|group| {
+            let survivor = survivor(items, &group, policy);
+            group.into_iter().filter(move |&index| index != survivor)
+        })
+        .collect();
+    removed.sort_unstable();
+
+    removed
+        .into_iter()
+        .map(|index| items[index].clone())
+        .collect()
+}
+
+/// Index of the item to keep out of a group of duplicates, listed in sidebar order.
+fn survivor(items: &[SidebarItem], group: &[usize], policy: &DedupePolicy) -> usize {
+    match policy {
+        DedupePolicy::First => group[0],
+        DedupePolicy::Last => group[group.len() - 1],
+        DedupePolicy::PreferLabel(label) => group
+            .iter()
+            .copied()
+            .find(|&index| items[index].target().label() == label)
+            .unwrap_or(group[0]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn custom(id: u32, label: &str, path: &str) -> SidebarItem {
+        SidebarItem::new(Target::custom(label, path)).with_id(id)
+    }
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            custom(1, "Projects", "/Users/user/Projects"),
+            SidebarItem::new(Target::AirDrop).with_id(2),
+            custom(3, "Work", "/Users/user/Projects/"),
+            SidebarItem::new(Target::AirDrop).with_id(4),
+            custom(5, "Code", "/Users/user/tmp/../Projects"),
+        ]
+    }
+
+    #[test]
+    fn should_keep_first_item_by_default() {
+        // Act
+        let removed = duplicates(&sidebar(), &DedupePolicy::default());
+
+        // Assert
+        assert_eq!(
+            removed,
+            vec![
+                custom(3, "Work", "/Users/user/Projects/"),
+                SidebarItem::new(Target::AirDrop).with_id(4),
+                custom(5, "Code", "/Users/user/tmp/../Projects"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_last_item() {
+        // Act
+        let removed = duplicates(&sidebar(), &DedupePolicy::Last);
+
+        // Assert
+        assert_eq!(
+            removed,
+            vec![
+                custom(1, "Projects", "/Users/user/Projects"),
+                SidebarItem::new(Target::AirDrop).with_id(2),
+                custom(3, "Work", "/Users/user/Projects/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_item_with_preferred_label() {
+        // Act
+        let removed = duplicates(&sidebar(), &DedupePolicy::PreferLabel("Work".into()));
+
+        // Assert
+        assert_eq!(
+            removed,
+            vec![
+                custom(1, "Projects", "/Users/user/Projects"),
+                SidebarItem::new(Target::AirDrop).with_id(4),
+                custom(5, "Code", "/Users/user/tmp/../Projects"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fall_back_to_first_without_preferred_label() {
+        // Arrange
+        let items = vec![
+            custom(1, "Projects", "/Users/user/Projects"),
+            custom(2, "Work", "/Users/user/Projects"),
+        ];
+
+        // Act
+        let removed = duplicates(&items, &DedupePolicy::PreferLabel("Code".into()));
+
+        // Assert
+        assert_eq!(removed, vec![custom(2, "Work", "/Users/user/Projects")]);
+    }
+
+    #[test]
+    fn should_treat_network_shares_by_url() {
+        // Arrange
+        let items = vec![
+            SidebarItem::new(Target::network("NAS", "smb://nas.local/share")).with_id(1),
+            SidebarItem::new(Target::network("Share", "smb://nas.local/share")).with_id(2),
+            SidebarItem::new(Target::network("Other", "smb://nas.local/other")).with_id(3),
+        ];
+
+        // Act
+        let removed = duplicates(&items, &DedupePolicy::First);
+
+        // Assert
+        assert_eq!(
+            removed,
+            vec![SidebarItem::new(Target::network("Share", "smb://nas.local/share")).with_id(2)]
+        );
+    }
+
+    #[test]
+    fn should_ignore_unique_items() {
+        // Arrange
+        let items = vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            SidebarItem::new(Target::Recents).with_id(2),
+        ];
+
+        // Act & Assert
+        assert!(duplicates(&items, &DedupePolicy::First).is_empty());
+    }
+}
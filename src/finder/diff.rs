@@ -0,0 +1,237 @@
+//! Differences between two listings of the sidebar.
+
+use std::fmt;
+
+use super::{SidebarItem, Visibility, sort::longest_increasing};
+
+/// One difference between an old and a new listing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A new item appeared at `index` in the new listing.
+    Added { index: usize, item: SidebarItem },
+    /// An item is gone.
+    Removed { item: SidebarItem },
+    /// An item kept its id but changed places; `index` is where it is now.
+    Moved { index: usize, item: SidebarItem },
+    /// An item kept its id but its target or visibility changed.
+    Updated {
+        before: SidebarItem,
+        after: SidebarItem,
+    },
+}
+
+/// Describes the change as an instruction, e.g. `move Projects to 1`; positions count from 1
+/// like `list` does.
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { index, item } => {
+                write!(f, "add {} at {}", item.target().label(), index + 1)
+            }
+            Self::Removed { item } => write!(f, "remove {}", item.target().label()),
+            Self::Moved { index, item } => {
+                write!(f, "move {} to {}", item.target().label(), index + 1)
+            }
+            Self::Updated { before, after } => {
+                let verb = match (before.visibility(), after.visibility()) {
+                    (Visibility::Visible, Visibility::Hidden) => "hide",
+                    (Visibility::Hidden, Visibility::Visible) => "show",
+                    _ => "update",
+                };
+                write!(f, "{} {}", verb, after.target().label())
+            }
+        }
+    }
+}
+
+/// Lists what changed between two listings: removals first, then additions, moves and updates
+/// in the order of the new listing.
+///
+/// Items are matched by id, or by being equal when they have none. Like
+/// [`sort::moves`](super::sort::moves), only items outside the longest run still in their old
+/// order count as moved.
+pub fn changes(old: &[SidebarItem], new: &[SidebarItem]) -> Vec<Change> {
+    let mut matched = vec![false; old.len()];
+    let previous: Vec<Option<usize>> = new
+        .iter()
+        .map(|item| {
+            let index = old.iter().enumerate().position(|(index, candidate)| {
+                !matched[index]
+                    && match item.id() {
+                        Some(id) => candidate.id() == Some(id),
+                        None => candidate == item,
+                    }
+            })?;
+            matched[index] = true;
+            Some(index)
+        })
+        .collect();
+
+    let kept: Vec<usize> = previous.iter().flatten().copied().collect();
+    let mut staying = longest_increasing(&kept).into_iter();
+
+    let removed = old
+        .iter()
+        .zip(&matched)
+        .filter(|(_, matched)| !**matched)
+        .map(|(item, _)| Change::Removed { item: item.clone() });
+
+    let mut changes: Vec<Change> = removed.collect();
+    for (index, (item, previous)) in new.iter().zip(previous).enumerate() {
+        let Some(previous) = previous else {
+            changes.push(Change::Added {
+                index,
+                item: item.clone(),
+            });
+            continue;
+        };
+        if !staying.next().unwrap_or(true) {
+            changes.push(Change::Moved {
+                index,
+                item: item.clone(),
+            });
+        }
+        if old[previous] != *item {
+            changes.push(Change::Updated {
+                before: old[previous].clone(),
+                after: item.clone(),
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::{Target, Visibility};
+
+    fn custom(id: u32, label: &str) -> SidebarItem {
+        SidebarItem::new(Target::custom(label, format!("/Users/user/{}", label))).with_id(id)
+    }
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            custom(2, "Projects"),
+            custom(3, "Downloads"),
+        ]
+    }
+
+    #[test]
+    fn should_find_nothing_in_same_listing() {
+        assert_eq!(changes(&sidebar(), &sidebar()), Vec::new());
+    }
+
+    #[test]
+    fn should_report_added_and_removed_items() {
+        // Arrange
+        let old = sidebar();
+        let new = vec![old[0].clone(), custom(4, "Music"), old[2].clone()];
+
+        // Act
+        let changes = changes(&old, &new);
+
+        // Assert
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    item: custom(2, "Projects")
+                },
+                Change::Added {
+                    index: 1,
+                    item: custom(4, "Music")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_only_the_moved_item() {
+        // Arrange
+        let old = sidebar();
+        let new = vec![old[2].clone(), old[0].clone(), old[1].clone()];
+
+        // Act
+        let changes = changes(&old, &new);
+
+        // Assert
+        assert_eq!(
+            changes,
+            vec![Change::Moved {
+                index: 0,
+                item: custom(3, "Downloads")
+            }]
+        );
+    }
+
+    #[test]
+    fn should_report_updated_item() {
+        // Arrange
+        let old = sidebar();
+        let hidden = custom(2, "Projects").with_visibility(Visibility::Hidden);
+        let new = vec![old[0].clone(), hidden.clone(), old[2].clone()];
+
+        // Act
+        let changes = changes(&old, &new);
+
+        // Assert
+        assert_eq!(
+            changes,
+            vec![Change::Updated {
+                before: custom(2, "Projects"),
+                after: hidden
+            }]
+        );
+    }
+
+    #[test]
+    fn should_describe_changes() {
+        // Arrange
+        let changes = [
+            Change::Added {
+                index: 0,
+                item: custom(4, "Music"),
+            },
+            Change::Moved {
+                index: 2,
+                item: custom(2, "Projects"),
+            },
+            Change::Updated {
+                before: custom(3, "Downloads"),
+                after: custom(3, "Downloads").with_visibility(Visibility::Hidden),
+            },
+        ];
+
+        // Act
+        let descriptions: Vec<String> = changes.iter().map(Change::to_string).collect();
+
+        // Assert
+        assert_eq!(
+            descriptions,
+            vec!["add Music at 1", "move Projects to 3", "hide Downloads"]
+        );
+    }
+
+    #[test]
+    fn should_match_items_without_id_by_equality() {
+        // Arrange
+        let old = vec![SidebarItem::new(Target::Recents), custom(2, "Projects")];
+        let new = vec![custom(2, "Projects"), SidebarItem::new(Target::Recents)];
+
+        // Act
+        let changes = changes(&old, &new);
+
+        // Assert
+        assert_eq!(
+            changes,
+            vec![Change::Moved {
+                index: 0,
+                item: custom(2, "Projects")
+            }]
+        );
+    }
+}
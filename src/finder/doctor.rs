@@ -0,0 +1,363 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use super::{ResolutionFailure, Selector, SidebarItem, Target, path::normalize};
+
+/// Checks the outside world on behalf of [`diagnose`].
+///
+/// Keeps the diagnosis itself a pure function over sidebar items.
+pub trait Probe {
+    fn exists(&self, path: &Path) -> bool;
+    fn is_readable(&self, path: &Path) -> bool;
+    fn resolves_host(&self, host: &str) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.pad("warning"),
+            Self::Error => f.pad("error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    Missing,
+    Unreadable,
+    Duplicate { of: Selector },
+    UnresolvedHost { host: String },
+    Unresolved(ResolutionFailure),
+}
+
+impl Problem {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Missing | Self::Unresolved(_) => Severity::Error,
+            Self::Unreadable | Self::Duplicate { .. } | Self::UnresolvedHost { .. } => {
+                Severity::Warning
+            }
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "target does not exist"),
+            Self::Unreadable => write!(f, "target is not readable"),
+            Self::Duplicate { of } => write!(f, "duplicate of {}", of),
+            Self::UnresolvedHost { host } => write!(f, "host '{}' does not resolve", host),
+            Self::Unresolved(_) => write!(f, "target cannot be resolved"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub item: SidebarItem,
+    pub problem: Problem,
+}
+
+impl Finding {
+    pub fn severity(&self) -> Severity {
+        self.problem.severity()
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<7}  {}: {}", self.severity(), self.item, self.problem)
+    }
+}
+
+/// Result of checking every favorite in the sidebar.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// The most severe problem found, if any.
+    pub fn worst(&self) -> Option<Severity> {
+        self.findings.iter().map(Finding::severity).max()
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity() == severity)
+            .count()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_healthy() {
+            return writeln!(f, "No problems found");
+        }
+
+        for finding in &self.findings {
+            writeln!(f, "{}", finding)?;
+        }
+        writeln!(
+            f,
+            "{} problem(s) found: {} error(s), {} warning(s)",
+            self.findings.len(),
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        )
+    }
+}
+
+/// Checks custom locations for existence, readability and duplicates, and network shares for
+/// hosts that don't resolve.
+pub fn diagnose(items: &[SidebarItem], probe: &impl Probe) -> Report {
+    let mut first_by_path = HashMap::new();
+
+    let findings = items
+        .iter()
+        .flat_map(|item| {
+            let problem = match item.target() {
+                Target::Custom { path, .. } => {
+                    let normalized = normalize(path);
+                    match first_by_path.get(&normalized) {
+                        Some(first) => Some(Problem::Duplicate {
+                            of: selector_for(first),
+                        }),
+                        None => {
+                            let problem = check_path(&normalized, probe);
+                            first_by_path.insert(normalized, item.clone());
+                            problem
+                        }
+                    }
+                }
+                Target::Network { url, .. } => host_of(url)
+                    .filter(|host| !probe.resolves_host(host))
+                    .map(|host| Problem::UnresolvedHost {
+                        host: host.to_string(),
+                    }),
+                Target::Unresolved { failure, .. } => Some(Problem::Unresolved(failure.clone())),
+                _ => None,
+            };
+            problem.map(|problem| Finding {
+                item: item.clone(),
+                problem,
+            })
+        })
+        .collect();
+
+    Report { findings }
+}
+
+fn check_path(path: &Path, probe: &impl Probe) -> Option<Problem> {
+    if !probe.exists(path) {
+        Some(Problem::Missing)
+    } else if !probe.is_readable(path) {
+        Some(Problem::Unreadable)
+    } else {
+        None
+    }
+}
+
+fn selector_for(item: &SidebarItem) -> Selector {
+    item.id()
+        .map(Selector::Id)
+        .unwrap_or_else(|| Selector::Label(item.target().label().to_string()))
+}
+
+/// Extracts the host from a URL such as `smb://user@nas.local:445/share`.
+fn host_of(url: &str) -> Option<&str> {
+    let authority = url.split_once("://")?.1.split('/').next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, path::PathBuf};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::ItemId;
+
+    #[derive(Default)]
+    struct FakeProbe {
+        existing: HashSet<PathBuf>,
+        unreadable: HashSet<PathBuf>,
+        hosts: HashSet<String>,
+    }
+
+    impl FakeProbe {
+        fn with_path(mut self, path: &str) -> Self {
+            self.existing.insert(PathBuf::from(path));
+            self
+        }
+
+        fn with_unreadable_path(mut self, path: &str) -> Self {
+            self.unreadable.insert(PathBuf::from(path));
+            self.with_path(path)
+        }
+
+        fn with_host(mut self, host: &str) -> Self {
+            self.hosts.insert(host.to_string());
+            self
+        }
+    }
+
+    impl Probe for FakeProbe {
+        fn exists(&self, path: &Path) -> bool {
+            self.existing.contains(path)
+        }
+
+        fn is_readable(&self, path: &Path) -> bool {
+            !self.unreadable.contains(path)
+        }
+
+        fn resolves_host(&self, host: &str) -> bool {
+            self.hosts.contains(host)
+        }
+    }
+
+    fn custom(id: u32, label: &str, path: &str) -> SidebarItem {
+        SidebarItem::new(Target::custom(label, path)).with_id(id)
+    }
+
+    #[test]
+    fn should_report_healthy_sidebar() {
+        let items = vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            custom(2, "Projects", "/Users/user/Projects"),
+        ];
+        let probe = FakeProbe::default().with_path("/Users/user/Projects");
+
+        let report = diagnose(&items, &probe);
+
+        assert!(report.is_healthy());
+        assert_eq!(report.worst(), None);
+    }
+
+    #[test]
+    fn should_report_missing_path_as_error() {
+        let items = vec![custom(1, "Old", "/Users/user/Old")];
+
+        let report = diagnose(&items, &FakeProbe::default());
+
+        assert_eq!(
+            report.findings(),
+            &[Finding {
+                item: custom(1, "Old", "/Users/user/Old"),
+                problem: Problem::Missing,
+            }]
+        );
+        assert_eq!(report.worst(), Some(Severity::Error));
+    }
+
+    #[test]
+    fn should_report_unreadable_path_as_warning() {
+        let items = vec![custom(1, "Secret", "/private/secret")];
+        let probe = FakeProbe::default().with_unreadable_path("/private/secret");
+
+        let report = diagnose(&items, &probe);
+
+        assert_eq!(
+            report.findings(),
+            &[Finding {
+                item: custom(1, "Secret", "/private/secret"),
+                problem: Problem::Unreadable,
+            }]
+        );
+        assert_eq!(report.worst(), Some(Severity::Warning));
+    }
+
+    #[test]
+    fn should_report_duplicates_after_normalisation() {
+        let items = vec![
+            custom(1, "Projects", "/Users/user/Projects"),
+            custom(2, "Work", "/Users/user/tmp/../Projects/"),
+        ];
+        let probe = FakeProbe::default().with_path("/Users/user/Projects");
+
+        let report = diagnose(&items, &probe);
+
+        assert_eq!(
+            report.findings(),
+            &[Finding {
+                item: custom(2, "Work", "/Users/user/tmp/../Projects/"),
+                problem: Problem::Duplicate {
+                    of: Selector::Id(ItemId::from(1)),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn should_report_network_share_with_unknown_host() {
+        let items = vec![
+            SidebarItem::new(Target::network("NAS", "smb://user@nas.local:445/share")).with_id(1),
+            SidebarItem::new(Target::network("Backup", "afp://backup.local/tm")).with_id(2),
+        ];
+        let probe = FakeProbe::default().with_host("nas.local");
+
+        let report = diagnose(&items, &probe);
+
+        assert_eq!(report.findings().len(), 1);
+        assert_eq!(
+            report.findings()[0].problem,
+            Problem::UnresolvedHost {
+                host: "backup.local".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_report_unresolved_item_as_error() {
+        let item =
+            SidebarItem::new(Target::unresolved("Gone", ResolutionFailure::NotFound)).with_id(1);
+
+        let report = diagnose(&[item], &FakeProbe::default());
+
+        assert_eq!(
+            report.findings()[0].problem,
+            Problem::Unresolved(ResolutionFailure::NotFound)
+        );
+        assert_eq!(report.worst(), Some(Severity::Error));
+    }
+
+    #[test]
+    fn should_extract_host_from_url() {
+        assert_eq!(host_of("smb://user@nas.local:445/share"), Some("nas.local"));
+        assert_eq!(host_of("afp://backup.local"), Some("backup.local"));
+        assert_eq!(host_of("not a url"), None);
+    }
+
+    #[test]
+    fn should_summarise_report() {
+        let items = vec![custom(1, "Old", "/Users/user/Old")];
+
+        let report = diagnose(&items, &FakeProbe::default());
+
+        assert_eq!(
+            report.to_string(),
+            "error    Old -> /Users/user/Old: target does not exist\n\
+             1 problem(s) found: 1 error(s), 0 warning(s)\n"
+        );
+    }
+}
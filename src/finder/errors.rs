@@ -1,11 +1,38 @@
 use thiserror::Error;
 
+use super::{ItemId, JournalError, Seed, Selector};
 use crate::system::favorites::FavoritesError;
 
 #[derive(Debug, Error, PartialEq)]
 pub enum FinderError {
     #[error("failed to access Finder favorites: {0}")]
     AccessError(#[from] FavoritesError),
+    #[error("sidebar item not found: {0}")]
+    ItemNotFound(ItemId),
+    #[error("no sidebar item matches '{0}'")]
+    NoMatch(Selector),
+    #[error("more than one sidebar item matches '{0}', select it by id instead")]
+    AmbiguousSelector(Selector),
+    #[error("'{0}' is a built-in sidebar item and cannot be renamed")]
+    CannotRename(Selector),
+    #[error(transparent)]
+    Journal(Box<JournalError>),
+    #[error("no journal configured, changes are not recorded")]
+    NoJournal,
+    #[error(
+        "the sidebar changed outside favkit since the last recorded change (seed {current}, expected {recorded})"
+    )]
+    ChangedSinceJournal { recorded: Seed, current: Seed },
+    #[error("Finder thread failed: {0}")]
+    Thread(String),
+    #[error("Finder call timed out after {0:?}")]
+    TimedOut(std::time::Duration),
+}
+
+impl From<JournalError> for FinderError {
+    fn from(error: JournalError) -> Self {
+        Self::Journal(Box::new(error))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, FinderError>;
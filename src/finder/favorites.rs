@@ -1,4 +1,6 @@
-use super::{Result, SidebarItem};
+use super::{
+    ItemId, Ownership, Position, PropertyValue, Result, Seed, SidebarItem, Target, Visibility,
+};
 
 /// Provides access to Finder favorites
 pub trait FavoritesApi {
@@ -7,4 +9,47 @@ pub trait FavoritesApi {
     /// Returns a vector of sidebar items or an error if favorites cannot be accessed
     #[must_use = "this Result contains Finder favorites or an error that should be handled"]
     fn list_items(&self) -> Result<Vec<SidebarItem>>;
+
+    /// Reads the current version of the favorites list
+    #[must_use = "this Result contains the list seed or an error that should be handled"]
+    fn seed(&self) -> Result<Seed>;
+
+    /// Reads a custom property of a sidebar item
+    ///
+    /// Returns `None` when the property is not set on the item
+    #[must_use = "this Result contains the property value or an error that should be handled"]
+    fn item_property(&self, id: ItemId, name: &str) -> Result<Option<PropertyValue>>;
+
+    /// Shows or hides a sidebar item without removing it
+    #[must_use = "this Result reports whether the item was updated"]
+    fn set_visibility(&self, id: ItemId, visibility: Visibility) -> Result<()>;
+
+    /// Marks a sidebar item as added by favkit or not
+    #[must_use = "this Result reports whether the item was updated"]
+    fn set_ownership(&self, id: ItemId, ownership: Ownership) -> Result<()>;
+
+    /// Removes a sidebar item
+    #[must_use = "this Result reports whether the item was removed"]
+    fn remove_item(&self, id: ItemId) -> Result<()>;
+
+    /// Moves a sidebar item to another position
+    #[must_use = "this Result reports whether the item was moved"]
+    fn move_item(&self, id: ItemId, position: Position) -> Result<()>;
+
+    /// Inserts a new sidebar item pointing at the target
+    ///
+    /// Returns the id Core Services assigned to the new item
+    #[must_use = "this Result contains the new item id or an error that should be handled"]
+    fn insert_item(&self, target: &Target, position: Position) -> Result<ItemId>;
+
+    /// Runs several changes as one batch
+    ///
+    /// Implementations may look items up in a single listing for the whole batch instead of
+    /// listing the sidebar again for every change
+    fn batch<T>(&self, changes: impl FnOnce(&Self) -> T) -> T
+    where
+        Self: Sized,
+    {
+        changes(self)
+    }
 }
@@ -0,0 +1,326 @@
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use super::{ItemId, Kind, SidebarItem, path::normalize};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FilterError {
+    #[error("expected KEY=VALUE, got '{0}'")]
+    MissingValue(String),
+    #[error(
+        "unknown filter key '{0}', expected one of: kind, label, path, under, hidden, managed, id"
+    )]
+    UnknownKey(String),
+    #[error("invalid value for '{key}': {reason}")]
+    InvalidValue { key: String, reason: String },
+}
+
+/// Shell-style wildcard pattern: `*` matches any run of characters, `?` exactly one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob(String);
+
+impl Glob {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self(pattern.into())
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        let pattern: Vec<char> = self.0.chars().collect();
+        let text: Vec<char> = text.chars().collect();
+        let (mut p, mut t) = (0, 0);
+        // Position of the last `*` in the pattern and the text position it currently covers
+        let mut backtrack = None;
+
+        while t < text.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(&c) if c == '?' || c == text[t] => {
+                    p += 1;
+                    t += 1;
+                }
+                _ => match backtrack {
+                    Some((star, covered)) => {
+                        p = star + 1;
+                        t = covered + 1;
+                        backtrack = Some((star, covered + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|&c| c == '*')
+    }
+}
+
+impl FromStr for Glob {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s))
+    }
+}
+
+impl fmt::Display for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A single test an item must pass to be selected by a [`Filter`].
+///
+/// Parsed from `KEY=VALUE`:
+/// - `kind=custom` selects items of a kind (`airdrop`, `recents`, `applications`, `custom`,
+///   `network`, `unresolved`)
+/// - `label=Proj*` and `path=/Users/*/Work` match labels and paths against a [`Glob`]
+/// - `under=~/Work` selects custom locations inside a directory
+/// - `hidden=true` selects hidden (or with `false`, visible) items
+/// - `managed=true` selects items favkit added (or with `false`, the others)
+/// - `id=42` selects the item with that id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Kind(Kind),
+    Label(Glob),
+    Path(Glob),
+    Under(String),
+    Hidden(bool),
+    Managed(bool),
+    Id(ItemId),
+}
+
+impl Condition {
+    pub fn matches(&self, item: &SidebarItem) -> bool {
+        let target = item.target();
+        match self {
+            Self::Kind(kind) => target.kind() == *kind,
+            Self::Label(glob) => glob.matches(target.label()),
+            Self::Path(glob) => target.path().is_some_and(|path| glob.matches(path)),
+            Self::Under(directory) => target
+                .path()
+                .is_some_and(|path| normalize(path).starts_with(normalize(directory))),
+            Self::Hidden(hidden) => item.is_hidden() == *hidden,
+            Self::Managed(managed) => item.is_managed() == *managed,
+            Self::Id(id) => item.id() == Some(*id),
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| FilterError::MissingValue(s.to_string()))?;
+        let invalid = |reason: String| FilterError::InvalidValue {
+            key: key.to_string(),
+            reason,
+        };
+
+        match key.trim() {
+            "kind" => value.parse().map(Self::Kind).map_err(invalid),
+            "label" => Ok(Self::Label(Glob::new(value))),
+            "path" => Ok(Self::Path(Glob::new(value))),
+            "under" => Ok(Self::Under(value.to_string())),
+            "hidden" => value
+                .parse()
+                .map(Self::Hidden)
+                .map_err(|_| invalid(format!("expected true or false, got '{}'", value))),
+            "managed" => value
+                .parse()
+                .map(Self::Managed)
+                .map_err(|_| invalid(format!("expected true or false, got '{}'", value))),
+            "id" => value
+                .parse()
+                .map(Self::Id)
+                .map_err(|_| invalid(format!("expected a number, got '{}'", value))),
+            _ => Err(FilterError::UnknownKey(key.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Kind(kind) => write!(f, "kind={}", kind),
+            Self::Label(glob) => write!(f, "label={}", glob),
+            Self::Path(glob) => write!(f, "path={}", glob),
+            Self::Under(directory) => write!(f, "under={}", directory),
+            Self::Hidden(hidden) => write!(f, "hidden={}", hidden),
+            Self::Managed(managed) => write!(f, "managed={}", managed),
+            Self::Id(id) => write!(f, "id={}", id),
+        }
+    }
+}
+
+/// Selects every sidebar item passing all of its conditions.
+///
+/// An empty filter selects everything. Unlike a [`Selector`](super::Selector), which must
+/// match exactly one item, a filter is meant for listing and bulk operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    conditions: Vec<Condition>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    pub fn matches(&self, item: &SidebarItem) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.matches(item))
+    }
+
+    /// Returns the matching items, keeping their order.
+    pub fn apply(&self, items: &[SidebarItem]) -> Vec<SidebarItem> {
+        items
+            .iter()
+            .filter(|item| self.matches(item))
+            .cloned()
+            .collect()
+    }
+}
+
+impl FromIterator<Condition> for Filter {
+    fn from_iter<I: IntoIterator<Item = Condition>>(conditions: I) -> Self {
+        Self {
+            conditions: conditions.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conditions: Vec<_> = self.conditions.iter().map(ToString::to_string).collect();
+        write!(f, "{}", conditions.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::{Ownership, Target, Visibility};
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            SidebarItem::new(Target::custom("Projects", "/Users/user/Projects")).with_id(2),
+            SidebarItem::new(Target::custom("Prototypes", "/Users/user/Work/Prototypes"))
+                .with_id(3)
+                .with_visibility(Visibility::Hidden),
+            SidebarItem::new(Target::network("NAS", "smb://nas.local/share"))
+                .with_id(4)
+                .with_ownership(Ownership::Managed),
+        ]
+    }
+
+    fn ids(items: &[SidebarItem]) -> Vec<u32> {
+        items
+            .iter()
+            .filter_map(SidebarItem::id)
+            .map(u32::from)
+            .collect()
+    }
+
+    fn select(conditions: &[&str]) -> Vec<u32> {
+        let filter: Filter = conditions
+            .iter()
+            .map(|condition| condition.parse::<Condition>().unwrap())
+            .collect();
+        ids(&filter.apply(&sidebar()))
+    }
+
+    #[test]
+    fn should_match_globs() {
+        assert!(Glob::new("Proj*").matches("Projects"));
+        assert!(Glob::new("*o*s").matches("Prototypes"));
+        assert!(Glob::new("N?S").matches("NAS"));
+        assert!(Glob::new("*").matches(""));
+        assert!(!Glob::new("Proj*").matches("My Projects"));
+        assert!(!Glob::new("N?S").matches("NASA"));
+    }
+
+    #[test]
+    fn should_select_everything_with_empty_filter() {
+        assert_eq!(select(&[]), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn should_select_by_kind() {
+        assert_eq!(select(&["kind=custom"]), vec![2, 3]);
+        assert_eq!(select(&["kind=network"]), vec![4]);
+    }
+
+    #[test]
+    fn should_select_by_label_and_path_globs() {
+        assert_eq!(select(&["label=Pro*"]), vec![2, 3]);
+        assert_eq!(select(&["path=*/Work/*"]), vec![3]);
+    }
+
+    #[test]
+    fn should_select_paths_under_directory() {
+        assert_eq!(select(&["under=/Users/user/Work/"]), vec![3]);
+        assert_eq!(select(&["under=/Users/user/Proj"]), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn should_select_hidden_items_and_by_id() {
+        assert_eq!(select(&["hidden=true"]), vec![3]);
+        assert_eq!(select(&["id=4"]), vec![4]);
+    }
+
+    #[test]
+    fn should_select_managed_items() {
+        assert_eq!(select(&["managed=true"]), vec![4]);
+        assert_eq!(select(&["managed=false"]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn should_combine_conditions() {
+        assert_eq!(select(&["kind=custom", "hidden=false"]), vec![2]);
+    }
+
+    #[test]
+    fn should_reject_malformed_conditions() {
+        assert_eq!(
+            "kind".parse::<Condition>(),
+            Err(FilterError::MissingValue("kind".to_string()))
+        );
+        assert_eq!(
+            "colour=red".parse::<Condition>(),
+            Err(FilterError::UnknownKey("colour".to_string()))
+        );
+        assert!(matches!(
+            "hidden=maybe".parse::<Condition>(),
+            Err(FilterError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            "kind=folder".parse::<Condition>(),
+            Err(FilterError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn should_format_filter() {
+        let filter = Filter::new()
+            .with("kind=custom".parse().unwrap())
+            .with(Condition::Label(Glob::new("Proj*")));
+        assert_eq!(filter.to_string(), "kind=custom label=Proj*");
+    }
+}
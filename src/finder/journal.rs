@@ -0,0 +1,269 @@
+//! History of the changes favkit made to the sidebar, for undo and redo.
+//!
+//! The journal is append-only: undoing and redoing are recorded as entries of their own, and the
+//! undo and redo stacks are rebuilt by replaying the whole journal.
+
+use std::{cell::RefCell, fmt, path::PathBuf, time::SystemTime};
+
+use thiserror::Error;
+
+use super::{Seed, SidebarItem};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum JournalError {
+    #[error("failed to access journal {path}: {reason}")]
+    Io { path: PathBuf, reason: String },
+    #[error("journal {path} is corrupt at line {line}: {reason}")]
+    Corrupt {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+}
+
+/// What a journal entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// A change made by a command.
+    Do,
+    /// Reverting the latest change still in effect.
+    Undo,
+    /// Re-applying the latest undone change.
+    Redo,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Do => "do",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+        };
+        f.pad(name)
+    }
+}
+
+/// One recorded change: the sidebar before and after it, and the list seed it left behind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub action: Action,
+    /// Short description of the change, e.g. `remove Projects`.
+    pub operation: String,
+    pub before: Vec<SidebarItem>,
+    pub after: Vec<SidebarItem>,
+    pub seed: Seed,
+    pub timestamp: SystemTime,
+}
+
+/// Storage for journal entries.
+pub trait Journal {
+    /// Reads all entries, oldest first
+    fn entries(&self) -> Result<Vec<Entry>, JournalError>;
+
+    /// Adds an entry at the end
+    fn append(&self, entry: &Entry) -> Result<(), JournalError>;
+}
+
+/// Journal kept in memory, lost when dropped.
+#[derive(Debug, Default)]
+pub struct MemoryJournal {
+    entries: RefCell<Vec<Entry>>,
+}
+
+impl Journal for MemoryJournal {
+    fn entries(&self) -> Result<Vec<Entry>, JournalError> {
+        Ok(self.entries.borrow().clone())
+    }
+
+    fn append(&self, entry: &Entry) -> Result<(), JournalError> {
+        self.entries.borrow_mut().push(entry.clone());
+        Ok(())
+    }
+}
+
+/// The undo and redo stacks after replaying a journal, as positions of its entries.
+#[derive(Debug, Default)]
+struct Replay {
+    undo: Vec<usize>,
+    redo: Vec<usize>,
+    /// For each entry, the entry it undid or redid.
+    reverted: Vec<Option<usize>>,
+}
+
+fn replay(actions: impl IntoIterator<Item = Action>) -> Replay {
+    actions
+        .into_iter()
+        .enumerate()
+        .fold(Replay::default(), |mut replay, (position, action)| {
+            let reverted = match action {
+                Action::Do => {
+                    replay.undo.push(position);
+                    replay.redo.clear();
+                    None
+                }
+                Action::Undo => replay
+                    .undo
+                    .pop()
+                    .inspect(|&undone| replay.redo.push(undone)),
+                Action::Redo => replay
+                    .redo
+                    .pop()
+                    .inspect(|&redone| replay.undo.push(redone)),
+            };
+            replay.reverted.push(reverted);
+            replay
+        })
+}
+
+/// Undo and redo stacks rebuilt from the journal.
+#[derive(Debug, Default, PartialEq)]
+pub struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    seed: Option<Seed>,
+}
+
+impl History {
+    pub fn new(entries: impl IntoIterator<Item = Entry>) -> Self {
+        let entries: Vec<Entry> = entries.into_iter().collect();
+        let replay = replay(entries.iter().map(|entry| entry.action));
+        let take = |positions: Vec<usize>| -> Vec<Entry> {
+            positions
+                .into_iter()
+                .map(|position| entries[position].clone())
+                .collect()
+        };
+        Self {
+            undo: take(replay.undo),
+            redo: take(replay.redo),
+            seed: entries.last().map(|entry| entry.seed),
+        }
+    }
+
+    /// The latest position at or before `at` where the journal can start without an undo or
+    /// redo among the remaining entries referring to an entry before it, so dropping older
+    /// entries only ever drops whole undo chains.
+    pub fn replayable_from(entries: &[Entry], at: usize) -> usize {
+        let reverted = replay(entries.iter().map(|entry| entry.action)).reverted;
+        (0..=at.min(entries.len()))
+            .rev()
+            .find(|&start| {
+                reverted[start..]
+                    .iter()
+                    .flatten()
+                    .all(|&entry| entry >= start)
+            })
+            .unwrap_or(0)
+    }
+
+    /// The latest change still in effect.
+    pub fn undoable(&self) -> Option<&Entry> {
+        self.undo.last()
+    }
+
+    /// The latest undone change.
+    pub fn redoable(&self) -> Option<&Entry> {
+        self.redo.last()
+    }
+
+    /// Seed the sidebar had after the last recorded entry.
+    pub fn seed(&self) -> Option<Seed> {
+        self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn entry(action: Action, operation: &str, seed: u32) -> Entry {
+        Entry {
+            action,
+            operation: operation.to_string(),
+            before: Vec::new(),
+            after: Vec::new(),
+            seed: Seed::from(seed),
+            timestamp: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn operation(entry: Option<&Entry>) -> Option<&str> {
+        entry.map(|entry| entry.operation.as_str())
+    }
+
+    #[test]
+    fn should_have_nothing_to_undo_without_entries() {
+        let history = History::new(Vec::new());
+
+        assert_eq!(history.undoable(), None);
+        assert_eq!(history.redoable(), None);
+        assert_eq!(history.seed(), None);
+    }
+
+    #[test]
+    fn should_undo_latest_change_first() {
+        // Act
+        let history = History::new([
+            entry(Action::Do, "remove A", 2),
+            entry(Action::Do, "remove B", 3),
+        ]);
+
+        // Assert
+        assert_eq!(operation(history.undoable()), Some("remove B"));
+        assert_eq!(history.redoable(), None);
+        assert_eq!(history.seed(), Some(Seed::from(3)));
+    }
+
+    #[test]
+    fn should_replay_undo_and_redo() {
+        // Act
+        let history = History::new([
+            entry(Action::Do, "remove A", 2),
+            entry(Action::Do, "remove B", 3),
+            entry(Action::Undo, "remove B", 4),
+            entry(Action::Undo, "remove A", 5),
+            entry(Action::Redo, "remove A", 6),
+        ]);
+
+        // Assert
+        assert_eq!(operation(history.undoable()), Some("remove A"));
+        assert_eq!(operation(history.redoable()), Some("remove B"));
+        assert_eq!(history.seed(), Some(Seed::from(6)));
+    }
+
+    #[test]
+    fn should_replay_from_start_of_undo_chain() {
+        // Arrange
+        let entries = [
+            entry(Action::Do, "remove A", 2),
+            entry(Action::Do, "remove B", 3),
+            entry(Action::Do, "remove C", 4),
+            entry(Action::Undo, "remove C", 5),
+            entry(Action::Undo, "remove B", 6),
+        ];
+
+        // Act
+        let start = History::replayable_from(&entries, 3);
+
+        // Assert
+        assert_eq!(start, 1);
+        let history = History::new(entries[start..].to_vec());
+        assert_eq!(operation(history.redoable()), Some("remove B"));
+    }
+
+    #[test]
+    fn should_forget_undone_changes_after_new_change() {
+        // Act
+        let history = History::new([
+            entry(Action::Do, "remove A", 2),
+            entry(Action::Undo, "remove A", 3),
+            entry(Action::Do, "hide B", 4),
+        ]);
+
+        // Assert
+        assert_eq!(operation(history.undoable()), Some("hide B"));
+        assert_eq!(history.redoable(), None);
+    }
+}
@@ -0,0 +1,207 @@
+//! Declarative description of a sidebar, and the plan that brings a sidebar in line with it.
+
+use super::{Change, Ownership, SidebarItem, activation, dedupe::TargetKey, diff};
+
+/// Which items applying a [`Manifest`] may remove.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scope {
+    /// Every item not in the manifest.
+    #[default]
+    All,
+    /// Only items favkit added; the others stay where they are.
+    Managed,
+}
+
+/// The items a sidebar should have, in order, with their visibility.
+///
+/// ```
+/// use favkit::finder::{Manifest, SidebarItem, Target};
+///
+/// let manifest = Manifest::new([
+///     SidebarItem::new(Target::Applications),
+///     SidebarItem::new(Target::custom("Projects", "/Users/me/Projects")),
+/// ]);
+/// assert_eq!(manifest.items().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    items: Vec<SidebarItem>,
+}
+
+impl Manifest {
+    /// Describes a sidebar with these items; their ids, which only mean something to the
+    /// sidebar they were listed from, are dropped.
+    pub fn new(items: impl IntoIterator<Item = SidebarItem>) -> Self {
+        Self {
+            items: items
+                .into_iter()
+                .map(|item| {
+                    SidebarItem::new(item.target().clone()).with_visibility(item.visibility())
+                })
+                .collect(),
+        }
+    }
+
+    pub fn items(&self) -> &[SidebarItem] {
+        &self.items
+    }
+
+    /// Lists what applying the manifest to `current` would change; empty when the sidebar
+    /// already matches.
+    pub fn changes(&self, current: &[SidebarItem], scope: Scope) -> Vec<Change> {
+        diff::changes(current, &self.resolve(current, scope))
+    }
+
+    /// The sidebar the manifest asks for, expressed with the current items it matches.
+    ///
+    /// Items are matched by target, ignoring labels and trailing slashes, so a matched item
+    /// keeps its id, label and ownership and only takes its visibility from the manifest. Items
+    /// with no match are returned as listed in the manifest, without an id and managed. With
+    /// [`Scope::Managed`], items favkit did not add are kept in place, as when activating with
+    /// every managed item owned.
+    pub(super) fn resolve(&self, current: &[SidebarItem], scope: Scope) -> Vec<SidebarItem> {
+        if scope == Scope::Managed {
+            let owned: Vec<_> = current
+                .iter()
+                .filter(|item| item.is_managed())
+                .map(|item| item.target().clone())
+                .collect();
+            return activation::plan(current, self, &owned).convergence.after;
+        }
+
+        let mut unmatched: Vec<&SidebarItem> = current.iter().collect();
+        self.items
+            .iter()
+            .map(|wanted| {
+                let key = TargetKey::of(wanted.target());
+                let found = unmatched
+                    .iter()
+                    .position(|item| key.is_some() && TargetKey::of(item.target()) == key);
+                match found.map(|position| unmatched.remove(position)) {
+                    Some(item) => item.clone().with_visibility(wanted.visibility()),
+                    None => wanted.clone().with_ownership(Ownership::Managed),
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<SidebarItem> for Manifest {
+    fn from_iter<I: IntoIterator<Item = SidebarItem>>(items: I) -> Self {
+        Self::new(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::{Target, Visibility};
+
+    fn custom(label: &str, path: &str) -> SidebarItem {
+        SidebarItem::new(Target::custom(label, path))
+    }
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            custom("Projects", "/Users/user/Projects").with_id(2),
+            custom("Downloads", "/Users/user/Downloads").with_id(3),
+        ]
+    }
+
+    #[test]
+    fn should_drop_ids() {
+        // Act
+        let manifest = Manifest::new(sidebar());
+
+        // Assert
+        assert_eq!(
+            manifest.items()[1],
+            custom("Projects", "/Users/user/Projects")
+        );
+    }
+
+    #[test]
+    fn should_have_no_changes_for_matching_sidebar() {
+        // Arrange
+        let manifest = Manifest::new([
+            SidebarItem::new(Target::AirDrop),
+            custom("Code", "/Users/user/Projects/"),
+            custom("Downloads", "/Users/user/Downloads"),
+        ]);
+
+        // Act
+        let changes = manifest.changes(&sidebar(), Scope::All);
+
+        // Assert
+        assert_eq!(changes, Vec::new());
+    }
+
+    #[test]
+    fn should_plan_additions_removals_moves_and_visibility() {
+        // Arrange
+        let manifest = Manifest::new([
+            custom("Downloads", "/Users/user/Downloads"),
+            SidebarItem::new(Target::AirDrop).with_visibility(Visibility::Hidden),
+            SidebarItem::new(Target::Applications),
+        ]);
+
+        // Act
+        let changes = manifest.changes(&sidebar(), Scope::All);
+
+        // Assert
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    item: custom("Projects", "/Users/user/Projects").with_id(2)
+                },
+                Change::Moved {
+                    index: 0,
+                    item: custom("Downloads", "/Users/user/Downloads").with_id(3)
+                },
+                Change::Updated {
+                    before: SidebarItem::new(Target::AirDrop).with_id(1),
+                    after: SidebarItem::new(Target::AirDrop)
+                        .with_id(1)
+                        .with_visibility(Visibility::Hidden)
+                },
+                Change::Added {
+                    index: 2,
+                    item: SidebarItem::new(Target::Applications).with_ownership(Ownership::Managed)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_leave_items_favkit_did_not_add_alone() {
+        // Arrange
+        let mut current = sidebar();
+        current.push(
+            custom("Build", "/Users/user/Build")
+                .with_id(4)
+                .with_ownership(Ownership::Managed),
+        );
+        let manifest = Manifest::new([SidebarItem::new(Target::Applications)]);
+
+        // Act
+        let changes = manifest.changes(&current, Scope::Managed);
+
+        // Assert
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    item: current[3].clone()
+                },
+                Change::Added {
+                    index: 0,
+                    item: SidebarItem::new(Target::Applications).with_ownership(Ownership::Managed)
+                },
+            ]
+        );
+    }
+}
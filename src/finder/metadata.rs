@@ -0,0 +1,173 @@
+use std::{fmt, num::ParseIntError, str::FromStr};
+
+/// Stable identifier Core Services assigns to every sidebar item.
+///
+/// Unlike labels, ids survive renames and stay unique when two items share a display name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ItemId(u32);
+
+impl From<u32> for ItemId {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ItemId> for u32 {
+    fn from(id: ItemId) -> Self {
+        id.0
+    }
+}
+
+impl FromStr for ItemId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl fmt::Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Version of the favorites list; Core Services changes it whenever the list is modified.
+///
+/// Comparing seeds tells whether anyone changed the sidebar since it was last read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed(u32);
+
+impl From<u32> for Seed {
+    fn from(seed: u32) -> Self {
+        Self(seed)
+    }
+}
+
+impl From<Seed> for u32 {
+    fn from(seed: Seed) -> Self {
+        seed.0
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Whether an item is shown in the Finder sidebar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Visibility {
+    #[default]
+    Visible,
+    Hidden,
+}
+
+impl From<bool> for Visibility {
+    fn from(hidden: bool) -> Self {
+        if hidden { Self::Hidden } else { Self::Visible }
+    }
+}
+
+/// Whether favkit added an item, or someone else did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Ownership {
+    /// Added by the user or another program; commands scoped to managed items leave it alone.
+    #[default]
+    User,
+    /// Added by favkit.
+    Managed,
+}
+
+impl From<bool> for Ownership {
+    fn from(managed: bool) -> Self {
+        if managed { Self::Managed } else { Self::User }
+    }
+}
+
+/// Where an item goes when it is inserted or moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// At the top of the sidebar section.
+    First,
+    /// At the bottom of the sidebar section.
+    Last,
+    /// Right after the item with this id.
+    After(ItemId),
+}
+
+/// Value of a custom property attached to a sidebar item.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    /// The property exists but holds a Core Foundation type we don't model.
+    Unsupported,
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Integer(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "{}", value),
+            Self::Unsupported => write!(f, "<unsupported>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_item_id() {
+        assert_eq!("42".parse::<ItemId>(), Ok(ItemId::from(42)));
+    }
+
+    #[test]
+    fn should_reject_non_numeric_item_id() {
+        assert!("Projects".parse::<ItemId>().is_err());
+    }
+
+    #[test]
+    fn should_convert_hidden_flag_to_visibility() {
+        assert_eq!(Visibility::from(true), Visibility::Hidden);
+        assert_eq!(Visibility::from(false), Visibility::Visible);
+    }
+
+    #[test]
+    fn should_convert_managed_flag_to_ownership() {
+        assert_eq!(Ownership::from(true), Ownership::Managed);
+        assert_eq!(Ownership::from(false), Ownership::User);
+    }
+
+    #[test]
+    fn should_format_property_values() {
+        assert_eq!(PropertyValue::Bool(true).to_string(), "true");
+        assert_eq!(PropertyValue::Integer(7).to_string(), "7");
+        assert_eq!(
+            PropertyValue::String("managed".into()).to_string(),
+            "managed"
+        );
+    }
+}
@@ -1,18 +1,55 @@
+pub mod activation;
+pub mod batch;
+pub mod diff;
+pub mod doctor;
 pub mod favorites;
+pub mod journal;
+pub mod sort;
 
+#[cfg(feature = "async")]
+mod async_finder;
+mod converge;
+mod dedupe;
 mod display_name;
 mod errors;
+mod filter;
+mod manifest;
+mod metadata;
+pub(crate) mod path;
+mod prune;
+mod resolution;
+mod selector;
+mod shared;
 mod sidebar;
 
+pub use activation::Activation;
+#[cfg(feature = "async")]
+pub use async_finder::{AsyncFinder, ChangeEvent, Changes, DEFAULT_TIMEOUT};
+pub use batch::{BatchError, Destination, Operation, Plan};
+pub use converge::{Convergence, DesiredItem, Presence};
+pub use dedupe::DedupePolicy;
+pub use diff::Change;
 pub use display_name::DisplayName;
 pub use errors::{FinderError, Result};
 use favorites::FavoritesApi;
-pub use sidebar::{SidebarItem, Target};
+pub use filter::{Condition, Filter, FilterError, Glob};
+pub use journal::{Action, Entry, History, Journal, JournalError, MemoryJournal};
+pub use manifest::{Manifest, Scope};
+pub use metadata::{ItemId, Ownership, Position, PropertyValue, Seed, Visibility};
+pub use prune::PruneOptions;
+pub use resolution::{ResolutionFailure, ResolutionOptions};
+pub use selector::Selector;
+pub use shared::SharedFinder;
+pub use sidebar::{Kind, SidebarItem, SidebarItemBuilder, Target};
+pub use sort::{Move, SortKey, SortOrder};
 
-use crate::system::{RealMacOsApi, api::MacOsApi, favorites::Favorites};
+use std::time::SystemTime;
+
+use crate::system::{RealMacOsApi, SystemProbe, api::MacOsApi, favorites::Favorites};
 
 pub struct Finder {
     favorites: Favorites,
+    journal: Option<Box<dyn Journal>>,
 }
 
 impl Finder {
@@ -20,11 +57,425 @@ impl Finder {
     pub fn new(api: impl MacOsApi + 'static) -> Self {
         Self {
             favorites: Favorites::new(api),
+            journal: None,
         }
     }
 
+    /// Records every change to the sidebar in the journal, enabling [`Finder::undo`].
+    pub fn with_journal(mut self, journal: impl Journal + 'static) -> Self {
+        self.journal = Some(Box::new(journal));
+        self
+    }
+
+    /// Sets what Core Services may do while resolving items, e.g. mount volumes.
+    pub fn with_resolution(mut self, options: ResolutionOptions) -> Self {
+        self.favorites = self.favorites.with_resolution(options);
+        self
+    }
+
     pub fn get_favorites_list(&self) -> Result<Vec<SidebarItem>> {
-        self.favorites.list_items()
+        let items = self.favorites.list_items()?;
+        Ok(items
+            .into_iter()
+            .enumerate()
+            .map(|(position, item)| item.with_position(position))
+            .collect())
+    }
+
+    /// Reads the current version of the sidebar; it changes whenever anyone modifies it.
+    pub fn seed(&self) -> Result<Seed> {
+        self.favorites.seed()
+    }
+
+    /// Reads a custom property of the item with the given id.
+    ///
+    /// Returns `Ok(None)` when the item exists but the property is not set.
+    pub fn get_item_property(&self, id: ItemId, name: &str) -> Result<Option<PropertyValue>> {
+        self.favorites.item_property(id, name)
+    }
+
+    /// Finds the single sidebar item matching the selector.
+    ///
+    /// Fails when nothing matches, or when a label or path matches more than one item.
+    pub fn find_item(&self, selector: &Selector) -> Result<SidebarItem> {
+        let mut matches = self
+            .favorites
+            .list_items()?
+            .into_iter()
+            .filter(|item| selector.matches(item));
+
+        match (matches.next(), matches.next()) {
+            (Some(item), None) => Ok(item),
+            (None, _) => Err(FinderError::NoMatch(selector.clone())),
+            (Some(_), Some(_)) => Err(FinderError::AmbiguousSelector(selector.clone())),
+        }
+    }
+
+    /// Returns every item the filter selects, in sidebar order.
+    pub fn find_items(&self, filter: &Filter) -> Result<Vec<SidebarItem>> {
+        let items = self.favorites.list_items()?;
+        Ok(filter.apply(&items))
+    }
+
+    /// Removes every item the filter selects and returns them.
+    pub fn remove_matching(&self, filter: &Filter) -> Result<Vec<SidebarItem>> {
+        self.record(&format!("remove {}", filter), || {
+            self.favorites.batch(|favorites| {
+                let matching = self.find_items(filter)?;
+                for id in matching.iter().filter_map(SidebarItem::id) {
+                    favorites.remove_item(id)?;
+                }
+                Ok(matching)
+            })
+        })
+    }
+
+    /// Adds a new item pointing at the target and returns it.
+    pub fn add(&self, target: Target, position: Position) -> Result<SidebarItem> {
+        self.record(&format!("add {}", target.label()), || {
+            let id = self.favorites.insert_item(&target, position)?;
+            self.favorites.set_ownership(id, Ownership::Managed)?;
+            Ok(SidebarItem::new(target.clone())
+                .with_id(id)
+                .with_ownership(Ownership::Managed))
+        })
+    }
+
+    /// Removes the single item matching the selector and returns it.
+    pub fn remove(&self, selector: &Selector) -> Result<SidebarItem> {
+        self.record(&format!("remove {}", selector), || {
+            let item = self.find_item(selector)?;
+            let id = item
+                .id()
+                .ok_or_else(|| FinderError::NoMatch(selector.clone()))?;
+            self.favorites.remove_item(id)?;
+            Ok(item)
+        })
+    }
+
+    /// Moves the selected item, e.g. to the top or after another item.
+    pub fn move_to(
+        &self,
+        selector: &Selector,
+        to: Destination,
+    ) -> std::result::Result<(), BatchError> {
+        self.batch(&[Operation::Move {
+            item: selector.clone(),
+            to,
+        }])
+    }
+
+    /// Renames the selected item, keeping its position and visibility.
+    ///
+    /// Core Services cannot relabel an entry, so the item is replaced and gets a new id.
+    pub fn rename(
+        &self,
+        selector: &Selector,
+        label: impl Into<String>,
+    ) -> std::result::Result<(), BatchError> {
+        self.batch(&[Operation::Rename {
+            item: selector.clone(),
+            label: label.into(),
+        }])
+    }
+
+    /// Validates the operations against the current sidebar without changing it.
+    pub fn plan(&self, operations: &[Operation]) -> std::result::Result<Plan, BatchError> {
+        Plan::new(self.favorites.list_items()?, operations)
+    }
+
+    /// Applies the operations in order as one unit.
+    ///
+    /// All operations are validated against a single listing first; nothing changes if any of
+    /// them is invalid. If a step fails midway, the sidebar is restored to that listing.
+    pub fn batch(&self, operations: &[Operation]) -> std::result::Result<(), BatchError> {
+        let description = match operations {
+            [operation] => operation.to_string(),
+            _ => format!("batch of {} operations", operations.len()),
+        };
+        self.record(&description, || {
+            // Planning lists the items the steps then look up
+            self.favorites
+                .batch(|favorites| self.plan(operations)?.execute(favorites))
+        })
+    }
+
+    /// Brings the sidebar back to a previously listed state.
+    ///
+    /// Items are matched by id, then by target; matched ones are moved into place and missing
+    /// ones are added again with new ids.
+    pub fn restore(&self, snapshot: &[SidebarItem]) -> Result<()> {
+        self.record("restore", || batch::restore(&self.favorites, snapshot))
+    }
+
+    /// Plans how [`Finder::apply`] would change the sidebar, without changing it.
+    pub fn apply_changes(&self, manifest: &Manifest, scope: Scope) -> Result<Vec<Change>> {
+        Ok(manifest.changes(&self.favorites.list_items()?, scope))
+    }
+
+    /// Brings the sidebar in line with the manifest and returns what changed.
+    ///
+    /// Items are matched to the manifest by target and keep their ids and labels; missing ones
+    /// are added, the rest removed, and only items out of order are moved and only changed
+    /// visibility set. With
+    /// [`Scope::Managed`], items favkit did not add are left alone unless the manifest lists
+    /// them. Does nothing when the sidebar already matches.
+    pub fn apply(&self, manifest: &Manifest, scope: Scope) -> Result<Vec<Change>> {
+        self.record("apply", || {
+            self.favorites.batch(|favorites| {
+                let current = favorites.list_items()?;
+                let after = manifest.resolve(&current, scope);
+                let changes = diff::changes(&current, &after);
+                batch::apply(favorites, &after, &changes)?;
+                Ok(changes)
+            })
+        })
+    }
+
+    /// Plans how [`Finder::activate`] would change the sidebar, without changing it.
+    pub fn plan_activation(&self, manifest: &Manifest, owned: &[Target]) -> Result<Activation> {
+        Ok(activation::plan(
+            &self.favorites.list_items()?,
+            manifest,
+            owned,
+        ))
+    }
+
+    /// Brings the manifest's items into the sidebar while leaving items the user added alone,
+    /// and returns what changed and the targets favkit owns afterwards.
+    ///
+    /// `owned` are the targets owned after the previous activation; those no longer in the
+    /// manifest are removed. Does nothing when the sidebar already matches.
+    pub fn activate(&self, manifest: &Manifest, owned: &[Target]) -> Result<Activation> {
+        self.record("activate", || {
+            self.favorites.batch(|favorites| {
+                let activation = self.plan_activation(manifest, owned)?;
+                let convergence = &activation.convergence;
+                batch::apply(favorites, &convergence.after, &convergence.changes)?;
+                Ok(activation)
+            })
+        })
+    }
+
+    /// Plans how [`Finder::converge`] would change the sidebar, without changing it.
+    pub fn plan_convergence(&self, desired: &DesiredItem) -> Result<Convergence> {
+        let before = self.favorites.list_items()?;
+        let after = converge::plan(&before, desired)?;
+        Ok(Convergence::new(before, after))
+    }
+
+    /// Brings the item pointing at the desired target into its desired state and returns
+    /// what changed; nothing does when it already is.
+    pub fn converge(&self, desired: &DesiredItem) -> Result<Convergence> {
+        self.record(&desired.to_string(), || {
+            self.favorites.batch(|favorites| {
+                let convergence = self.plan_convergence(desired)?;
+                batch::apply(favorites, &convergence.after, &convergence.changes)?;
+                Ok(convergence)
+            })
+        })
+    }
+
+    /// Reverts the latest recorded change still in effect and returns it.
+    ///
+    /// Refuses with [`FinderError::ChangedSinceJournal`] when the sidebar was changed outside
+    /// favkit since the last recorded entry, unless `force` is set. Returns `Ok(None)` when
+    /// there is nothing to undo.
+    pub fn undo(&self, force: bool) -> Result<Option<Entry>> {
+        self.travel(Action::Undo, force)
+    }
+
+    /// Re-applies the latest undone change and returns it.
+    ///
+    /// Checks the sidebar like [`Finder::undo`] does.
+    pub fn redo(&self, force: bool) -> Result<Option<Entry>> {
+        self.travel(Action::Redo, force)
+    }
+
+    fn travel(&self, action: Action, force: bool) -> Result<Option<Entry>> {
+        let journal = self.journal.as_deref().ok_or(FinderError::NoJournal)?;
+        let history = History::new(journal.entries()?);
+        let entry = match action {
+            Action::Undo => history.undoable(),
+            Action::Redo => history.redoable(),
+            Action::Do => None,
+        };
+        let Some(entry) = entry.cloned() else {
+            return Ok(None);
+        };
+
+        let current = self.favorites.seed()?;
+        match history.seed() {
+            Some(recorded) if recorded != current && !force => {
+                return Err(FinderError::ChangedSinceJournal { recorded, current });
+            }
+            _ => {}
+        }
+
+        let state = match action {
+            Action::Undo => &entry.before,
+            _ => &entry.after,
+        };
+        self.record_as(action, &entry.operation, || {
+            batch::restore(&self.favorites, state)
+        })?;
+        Ok(Some(entry))
+    }
+
+    /// Runs a change and journals it if it modified the sidebar.
+    fn record<T, E: From<FinderError>>(
+        &self,
+        operation: &str,
+        change: impl FnOnce() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        self.record_as(Action::Do, operation, change)
+    }
+
+    fn record_as<T, E: From<FinderError>>(
+        &self,
+        action: Action,
+        operation: &str,
+        change: impl FnOnce() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        let Some(journal) = self.journal.as_deref() else {
+            return change();
+        };
+
+        let before = self.favorites.list_items()?;
+        let result = change();
+        // Failed changes are journaled too when they left the sidebar modified
+        let after = match self.favorites.list_items() {
+            Ok(after) => after,
+            Err(error) => return result.and(Err(error.into())),
+        };
+        if after != before {
+            let entry = Entry {
+                action,
+                operation: operation.to_string(),
+                before,
+                after,
+                seed: self.favorites.seed()?,
+                timestamp: SystemTime::now(),
+            };
+            journal.append(&entry).map_err(FinderError::from)?;
+        }
+        result
+    }
+
+    /// Checks every favorite for missing, unreadable, duplicate and unreachable targets.
+    pub fn doctor(&self) -> Result<doctor::Report> {
+        self.doctor_with(&SystemProbe)
+    }
+
+    /// Same as [`Finder::doctor`], checking targets through the given probe.
+    pub fn doctor_with(&self, probe: &impl doctor::Probe) -> Result<doctor::Report> {
+        let items = self.favorites.list_items()?;
+        Ok(doctor::diagnose(&items, probe))
+    }
+
+    /// Removes favorites whose targets no longer exist and returns them.
+    ///
+    /// With [`PruneOptions::dry_run`] the sidebar is left untouched and the returned items are
+    /// the ones that would be removed.
+    pub fn prune(&self, options: PruneOptions) -> Result<Vec<SidebarItem>> {
+        self.prune_with(&SystemProbe, options)
+    }
+
+    /// Same as [`Finder::prune`], checking targets through the given probe.
+    pub fn prune_with(
+        &self,
+        probe: &impl doctor::Probe,
+        options: PruneOptions,
+    ) -> Result<Vec<SidebarItem>> {
+        self.favorites.batch(|favorites| {
+            let items = favorites.list_items()?;
+            let stale = prune::stale_items(&items, probe, options);
+            if options.is_dry_run() {
+                return Ok(stale);
+            }
+
+            self.record("prune", || {
+                for id in stale.iter().filter_map(SidebarItem::id) {
+                    favorites.remove_item(id)?;
+                }
+                Ok(stale)
+            })
+        })
+    }
+
+    /// Lists the items [`Finder::dedupe`] would remove, in sidebar order.
+    pub fn duplicates(&self, policy: &DedupePolicy) -> Result<Vec<SidebarItem>> {
+        let items = self.favorites.list_items()?;
+        Ok(dedupe::duplicates(&items, policy))
+    }
+
+    /// Removes items pointing at a target that appears earlier or later in the sidebar.
+    ///
+    /// The policy picks which item of each group survives; survivors keep their order.
+    /// Returns the removed items.
+    pub fn dedupe(&self, policy: &DedupePolicy) -> Result<Vec<SidebarItem>> {
+        self.record("dedupe", || {
+            self.favorites.batch(|favorites| {
+                let duplicates = self.duplicates(policy)?;
+                for id in duplicates.iter().filter_map(SidebarItem::id) {
+                    favorites.remove_item(id)?;
+                }
+                Ok(duplicates)
+            })
+        })
+    }
+
+    /// Plans the moves [`Finder::sort`] would make, without changing the sidebar.
+    pub fn sort_moves(&self, order: &SortOrder) -> Result<Vec<Move>> {
+        let items = self.favorites.list_items()?;
+        Ok(sort::moves(&items, order))
+    }
+
+    /// Reorders the sidebar with the fewest moves and returns the moves made.
+    pub fn sort(&self, order: &SortOrder) -> Result<Vec<Move>> {
+        self.record("sort", || {
+            self.favorites.batch(|favorites| {
+                let moves = self.sort_moves(order)?;
+                for step in &moves {
+                    let position = step
+                        .after
+                        .as_ref()
+                        .and_then(SidebarItem::id)
+                        .map_or(Position::First, Position::After);
+                    if let Some(id) = step.item.id() {
+                        favorites.move_item(id, position)?;
+                    }
+                }
+                Ok(moves)
+            })
+        })
+    }
+
+    /// Hides the selected item from the sidebar, keeping the entry itself.
+    pub fn hide(&self, selector: &Selector) -> Result<()> {
+        self.set_visibility(selector, Visibility::Hidden)
+    }
+
+    /// Shows a previously hidden item again.
+    pub fn show(&self, selector: &Selector) -> Result<()> {
+        self.set_visibility(selector, Visibility::Visible)
+    }
+
+    fn set_visibility(&self, selector: &Selector, visibility: Visibility) -> Result<()> {
+        let verb = match visibility {
+            Visibility::Hidden => "hide",
+            Visibility::Visible => "show",
+        };
+        self.record(&format!("{} {}", verb, selector), || {
+            let id = self.item_id(selector)?;
+            self.favorites.set_visibility(id, visibility)
+        })
+    }
+
+    fn item_id(&self, selector: &Selector) -> Result<ItemId> {
+        self.find_item(selector)?
+            .id()
+            .ok_or_else(|| FinderError::NoMatch(selector.clone()))
     }
 }
 
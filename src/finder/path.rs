@@ -0,0 +1,98 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Replaces a leading `~` with the current user's home directory.
+pub fn expand_home(path: &str) -> String {
+    let home = || dirs::home_dir().map(|home| home.to_string_lossy().into_owned());
+
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => home()
+            .map(|home| format!("{}{}", home, rest))
+            .unwrap_or_else(|| path.to_string()),
+        _ => path.to_string(),
+    }
+}
+
+/// Replaces the current user's home directory at the start of a path with `~`.
+pub fn contract_home(path: &str) -> String {
+    let home = dirs::home_dir().map(|home| home.to_string_lossy().into_owned());
+
+    match home.as_deref().and_then(|home| path.strip_prefix(home)) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{}", rest),
+        _ => path.to_string(),
+    }
+}
+
+/// Normalises a path for comparison without touching the file system.
+///
+/// Expands `~`, drops `.` components and trailing slashes, and folds `..` into its parent, so
+/// `~/Projects/`, `/Users/me/./Projects` and `/Users/me/tmp/../Projects` compare equal.
+pub fn normalize(path: &str) -> PathBuf {
+    Path::new(&expand_home(path))
+        .components()
+        .fold(PathBuf::new(), |mut normalized, component| {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                other => normalized.push(other),
+            }
+            normalized
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_strip_trailing_slash() {
+        assert_eq!(
+            normalize("/Users/user/Projects/"),
+            PathBuf::from("/Users/user/Projects")
+        );
+    }
+
+    #[test]
+    fn should_drop_current_dir_components() {
+        assert_eq!(
+            normalize("/Users/./user/Projects"),
+            PathBuf::from("/Users/user/Projects")
+        );
+    }
+
+    #[test]
+    fn should_fold_parent_dir_components() {
+        assert_eq!(
+            normalize("/Users/user/tmp/../Projects"),
+            PathBuf::from("/Users/user/Projects")
+        );
+    }
+
+    #[test]
+    fn should_expand_home() {
+        let home = dirs::home_dir().expect("home directory is known");
+        assert_eq!(normalize("~/Projects"), home.join("Projects"));
+    }
+
+    #[test]
+    fn should_contract_home() {
+        let home = dirs::home_dir().expect("home directory is known");
+        let projects = home.join("Projects");
+        assert_eq!(contract_home(&projects.to_string_lossy()), "~/Projects");
+    }
+
+    #[test]
+    fn should_not_contract_sibling_of_home() {
+        let home = dirs::home_dir().expect("home directory is known");
+        let sibling = format!("{}-old/Projects", home.to_string_lossy());
+        assert_eq!(contract_home(&sibling), sibling);
+    }
+
+    #[test]
+    fn should_leave_other_tildes_alone() {
+        assert_eq!(expand_home("~user/Projects"), "~user/Projects");
+    }
+}
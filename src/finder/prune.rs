@@ -0,0 +1,235 @@
+use std::path::{Component, Path};
+
+use super::{ResolutionFailure, SidebarItem, Target, doctor::Probe, path::normalize};
+
+/// Where macOS mounts network shares and external volumes.
+const VOLUMES: &str = "/Volumes";
+
+/// Controls which stale favorites [`Finder::prune`](super::Finder::prune) removes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneOptions {
+    dry_run: bool,
+    keep_network: bool,
+    managed_only: bool,
+}
+
+impl PruneOptions {
+    /// Reports what would be removed without touching the sidebar.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Leaves items on unmounted volumes alone, e.g. shares of an offline NAS.
+    pub fn keep_network(mut self) -> Self {
+        self.keep_network = true;
+        self
+    }
+
+    /// Leaves items favkit did not add alone.
+    pub fn managed_only(mut self) -> Self {
+        self.managed_only = true;
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn keeps_network(&self) -> bool {
+        self.keep_network
+    }
+
+    pub fn is_managed_only(&self) -> bool {
+        self.managed_only
+    }
+}
+
+/// Picks the items whose targets no longer exist.
+///
+/// Custom locations are stale when their path is missing, unresolved items when Core Services
+/// reports the target as not found. Built-in items and network shares are never stale, and
+/// neither are items favkit did not add when only managed items are pruned.
+pub fn stale_items(
+    items: &[SidebarItem],
+    probe: &impl Probe,
+    options: PruneOptions,
+) -> Vec<SidebarItem> {
+    items
+        .iter()
+        .filter(|item| item.is_managed() || !options.is_managed_only())
+        .filter(|item| match item.target() {
+            Target::Custom { path, .. } => {
+                let path = normalize(path);
+                let kept = options.keeps_network() && on_unmounted_volume(&path, probe);
+                !(kept || probe.exists(&path))
+            }
+            Target::Unresolved { failure, .. } => match failure {
+                ResolutionFailure::NotFound => true,
+                ResolutionFailure::VolumeNotMounted => !options.keeps_network(),
+                ResolutionFailure::Other { .. } => false,
+            },
+            _ => false,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether the path lives under `/Volumes/<name>` and that volume is not mounted.
+fn on_unmounted_volume(path: &Path, probe: &impl Probe) -> bool {
+    let Ok(relative) = path.strip_prefix(VOLUMES) else {
+        return false;
+    };
+    match relative.components().next() {
+        Some(Component::Normal(volume)) => !probe.exists(&Path::new(VOLUMES).join(volume)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, path::PathBuf};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::Ownership;
+
+    #[derive(Default)]
+    struct FakeProbe {
+        existing: HashSet<PathBuf>,
+    }
+
+    impl FakeProbe {
+        fn with_path(mut self, path: &str) -> Self {
+            self.existing.insert(PathBuf::from(path));
+            self
+        }
+    }
+
+    impl Probe for FakeProbe {
+        fn exists(&self, path: &Path) -> bool {
+            self.existing.contains(path)
+        }
+
+        fn is_readable(&self, _path: &Path) -> bool {
+            true
+        }
+
+        fn resolves_host(&self, _host: &str) -> bool {
+            true
+        }
+    }
+
+    fn custom(id: u32, label: &str, path: &str) -> SidebarItem {
+        SidebarItem::new(Target::custom(label, path)).with_id(id)
+    }
+
+    fn unresolved(id: u32, label: &str, failure: ResolutionFailure) -> SidebarItem {
+        SidebarItem::new(Target::unresolved(label, failure)).with_id(id)
+    }
+
+    #[test]
+    fn should_pick_missing_custom_locations() {
+        // Arrange
+        let items = vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            custom(2, "Projects", "/Users/user/Projects"),
+            custom(3, "Old", "/Users/user/Old"),
+        ];
+        let probe = FakeProbe::default().with_path("/Users/user/Projects");
+
+        // Act
+        let stale = stale_items(&items, &probe, PruneOptions::default());
+
+        // Assert
+        assert_eq!(stale, vec![custom(3, "Old", "/Users/user/Old")]);
+    }
+
+    #[test]
+    fn should_pick_items_not_found_during_resolution() {
+        // Arrange
+        let items = vec![
+            unresolved(1, "Gone", ResolutionFailure::NotFound),
+            unresolved(
+                2,
+                "Locked",
+                ResolutionFailure::Other {
+                    domain: "NSCocoaErrorDomain".into(),
+                    code: 257,
+                },
+            ),
+        ];
+
+        // Act
+        let stale = stale_items(&items, &FakeProbe::default(), PruneOptions::default());
+
+        // Assert
+        assert_eq!(
+            stale,
+            vec![unresolved(1, "Gone", ResolutionFailure::NotFound)]
+        );
+    }
+
+    #[test]
+    fn should_only_pick_managed_items_when_asked() {
+        // Arrange
+        let managed = custom(2, "Build", "/Users/user/Build").with_ownership(Ownership::Managed);
+        let items = vec![custom(1, "Old", "/Users/user/Old"), managed.clone()];
+
+        // Act
+        let stale = stale_items(
+            &items,
+            &FakeProbe::default(),
+            PruneOptions::default().managed_only(),
+        );
+
+        // Assert
+        assert_eq!(stale, vec![managed]);
+    }
+
+    #[test]
+    fn should_never_pick_network_shares() {
+        // Arrange
+        let items =
+            vec![SidebarItem::new(Target::network("NAS", "smb://nas.local/share")).with_id(1)];
+
+        // Act
+        let stale = stale_items(&items, &FakeProbe::default(), PruneOptions::default());
+
+        // Assert
+        assert!(stale.is_empty());
+    }
+
+    #[test]
+    fn should_pick_unmounted_volumes_by_default() {
+        // Arrange
+        let items = vec![
+            unresolved(1, "Share", ResolutionFailure::VolumeNotMounted),
+            custom(2, "Media", "/Volumes/NAS/Media"),
+        ];
+
+        // Act
+        let stale = stale_items(&items, &FakeProbe::default(), PruneOptions::default());
+
+        // Assert
+        assert_eq!(stale, items);
+    }
+
+    #[test]
+    fn should_keep_unmounted_volumes_when_asked() {
+        // Arrange
+        let items = vec![
+            unresolved(1, "Share", ResolutionFailure::VolumeNotMounted),
+            custom(2, "Media", "/Volumes/NAS/Media"),
+            custom(3, "Old", "/Volumes/Backup/Old"),
+        ];
+        let probe = FakeProbe::default().with_path("/Volumes/Backup");
+
+        // Act
+        let stale = stale_items(&items, &probe, PruneOptions::default().keep_network());
+
+        // Assert
+        assert_eq!(stale, vec![custom(3, "Old", "/Volumes/Backup/Old")]);
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+
+/// Controls what Core Services may do while resolving an item to its URL.
+///
+/// Resolving a favorite can mount network volumes or ask the user to authenticate, which blocks
+/// headless callers such as scripts run over SSH. Both are allowed by default, matching Finder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResolutionOptions {
+    no_user_interaction: bool,
+    no_mount_volumes: bool,
+}
+
+impl ResolutionOptions {
+    /// Never shows UI and never mounts volumes.
+    pub fn non_interactive() -> Self {
+        Self::default()
+            .without_user_interaction()
+            .without_mounting()
+    }
+
+    /// Fails resolution instead of showing authentication or other dialogs.
+    pub fn without_user_interaction(mut self) -> Self {
+        self.no_user_interaction = true;
+        self
+    }
+
+    /// Fails resolution instead of mounting the volume an item lives on.
+    pub fn without_mounting(mut self) -> Self {
+        self.no_mount_volumes = true;
+        self
+    }
+
+    pub fn allows_user_interaction(&self) -> bool {
+        !self.no_user_interaction
+    }
+
+    pub fn allows_mounting(&self) -> bool {
+        !self.no_mount_volumes
+    }
+}
+
+/// Why Core Services could not resolve an item to its URL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ResolutionFailure {
+    /// The file or folder the item points to no longer exists.
+    NotFound,
+    /// The item lives on a volume that isn't mounted, typically a network share.
+    VolumeNotMounted,
+    /// Any other error, identified by its error domain and code.
+    Other { domain: String, code: i64 },
+}
+
+impl fmt::Display for ResolutionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "target not found"),
+            Self::VolumeNotMounted => write!(f, "volume not mounted"),
+            Self::Other { domain, code } => write!(f, "{} error {}", domain, code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_allow_everything_by_default() {
+        let options = ResolutionOptions::default();
+        assert!(options.allows_user_interaction());
+        assert!(options.allows_mounting());
+    }
+
+    #[test]
+    fn should_forbid_everything_when_non_interactive() {
+        let options = ResolutionOptions::non_interactive();
+        assert!(!options.allows_user_interaction());
+        assert!(!options.allows_mounting());
+    }
+
+    #[test]
+    fn should_forbid_only_mounting() {
+        let options = ResolutionOptions::default().without_mounting();
+        assert!(options.allows_user_interaction());
+        assert!(!options.allows_mounting());
+    }
+
+    #[test]
+    fn should_format_resolution_failure() {
+        assert_eq!(ResolutionFailure::NotFound.to_string(), "target not found");
+        assert_eq!(
+            ResolutionFailure::Other {
+                domain: "NSCocoaErrorDomain".into(),
+                code: 256,
+            }
+            .to_string(),
+            "NSCocoaErrorDomain error 256"
+        );
+    }
+}
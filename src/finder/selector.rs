@@ -0,0 +1,127 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use super::{ItemId, SidebarItem, path::normalize};
+
+/// Identifies a sidebar item on the command line or in library calls.
+///
+/// Parsed from text as follows:
+/// - `id:<n>` selects the item with Core Services id `n`
+/// - anything starting with `/` or `~` selects a custom location by path
+/// - everything else selects an item by its label
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    Id(ItemId),
+    Label(String),
+    Path(String),
+}
+
+impl Selector {
+    const ID_PREFIX: &'static str = "id:";
+
+    pub fn matches(&self, item: &SidebarItem) -> bool {
+        match self {
+            Self::Id(id) => item.id() == Some(*id),
+            Self::Label(label) => item.target().label() == label,
+            Self::Path(path) => item
+                .target()
+                .path()
+                .is_some_and(|target| normalize(target) == normalize(path)),
+        }
+    }
+}
+
+impl FromStr for Selector {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let selector = match s.strip_prefix(Self::ID_PREFIX).map(str::parse) {
+            Some(Ok(id)) => Self::Id(id),
+            _ if s.starts_with('/') || s.starts_with('~') => Self::Path(s.to_string()),
+            _ => Self::Label(s.to_string()),
+        };
+        Ok(selector)
+    }
+}
+
+impl From<ItemId> for Selector {
+    fn from(id: ItemId) -> Self {
+        Self::Id(id)
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "{}{}", Self::ID_PREFIX, id),
+            Self::Label(label) => write!(f, "{}", label),
+            Self::Path(path) => write!(f, "{}", path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::Target;
+
+    fn projects() -> SidebarItem {
+        SidebarItem::new(Target::custom("Projects", "/Users/user/Projects")).with_id(3)
+    }
+
+    #[test]
+    fn should_parse_id_selector() {
+        assert_eq!(
+            "id:3".parse::<Selector>(),
+            Ok(Selector::Id(ItemId::from(3)))
+        );
+    }
+
+    #[test]
+    fn should_parse_path_selector() {
+        assert_eq!(
+            "/Users/user/Projects".parse::<Selector>(),
+            Ok(Selector::Path("/Users/user/Projects".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_parse_label_selector() {
+        assert_eq!(
+            "AirDrop".parse::<Selector>(),
+            Ok(Selector::Label("AirDrop".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_treat_malformed_id_as_label() {
+        assert_eq!(
+            "id:abc".parse::<Selector>(),
+            Ok(Selector::Label("id:abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_match_by_id_label_and_path() {
+        let item = projects();
+        assert!(Selector::Id(ItemId::from(3)).matches(&item));
+        assert!(Selector::Label("Projects".to_string()).matches(&item));
+        assert!(Selector::Path("/Users/user/Projects".to_string()).matches(&item));
+        assert!(Selector::Path("/Users/user/Projects/".to_string()).matches(&item));
+        assert!(!Selector::Label("Downloads".to_string()).matches(&item));
+    }
+
+    #[test]
+    fn should_match_built_in_items_by_label() {
+        let item = SidebarItem::new(Target::AirDrop);
+        assert!(Selector::Label("AirDrop".to_string()).matches(&item));
+        assert!(!Selector::Path("AirDrop".to_string()).matches(&item));
+    }
+
+    #[test]
+    fn should_format_selector() {
+        assert_eq!(Selector::Id(ItemId::from(3)).to_string(), "id:3");
+        assert_eq!(Selector::Label("AirDrop".into()).to_string(), "AirDrop");
+    }
+}
@@ -0,0 +1,125 @@
+//! A [`Finder`] that can be shared between threads.
+//!
+//! Apple documents no thread safety for the `LSSharedFileList` API in Core Services, which is
+//! deprecated, and the Core Foundation objects it hands out are only safe to use from one thread
+//! at a time. So [`MacOsApi`](crate::system::MacOsApi) has no `Send` or `Sync` bound and a
+//! [`Finder`] stays on the thread that made it.
+//!
+//! [`SharedFinder`] builds a [`Finder`] on a dedicated thread and runs every call there, one at a
+//! time. The handle is `Send + Sync` and cheap to clone, so it can be moved into worker threads
+//! or kept in the state of an async service. The thread exits once the last handle is dropped.
+
+use std::{
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use super::{Finder, FinderError, Result, SidebarItem};
+
+/// A call waiting to run on the Finder's thread.
+type Job = Box<dyn FnOnce(&Finder) + Send>;
+
+/// Name of the thread that owns the [`Finder`].
+const THREAD_NAME: &str = "favkit-finder";
+
+/// Handle to a [`Finder`] living on its own thread.
+///
+/// ```no_run
+/// use favkit::{Finder, finder::SharedFinder};
+///
+/// let finder = SharedFinder::spawn(Finder::default)?;
+/// let worker = finder.clone();
+/// std::thread::spawn(move || worker.call(|finder| finder.get_favorites_list()))
+///     .join()
+///     .unwrap()??;
+/// # Ok::<(), favkit::finder::FinderError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedFinder {
+    jobs: Sender<Job>,
+}
+
+impl SharedFinder {
+    /// Starts the thread and builds the [`Finder`] on it with `make`, so backends that are not
+    /// `Send` themselves can be used too.
+    pub fn spawn(make: impl FnOnce() -> Finder + Send + 'static) -> Result<Self> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name(THREAD_NAME.to_string())
+            .spawn(move || {
+                let finder = make();
+                for job in queue {
+                    job(&finder);
+                }
+            })
+            .map_err(|error| FinderError::Thread(error.to_string()))?;
+        Ok(Self { jobs })
+    }
+
+    /// Runs `call` on the Finder's thread and waits for its result.
+    ///
+    /// Calls from all handles run in the order they arrive. If one panics, the thread stops and
+    /// this and every later call fail with [`FinderError::Thread`].
+    pub fn call<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&Finder) -> T + Send + 'static,
+    ) -> Result<T> {
+        let (reply, result) = mpsc::sync_channel(1);
+        self.submit(move |finder| {
+            // The caller may have stopped waiting; the result is dropped then
+            let _ = reply.send(call(finder));
+        })?;
+        result.recv().map_err(|_| stopped())
+    }
+
+    /// Queues `job` to run on the Finder's thread without waiting for it.
+    ///
+    /// The job is dropped without running if the thread stops first.
+    pub(super) fn submit(&self, job: impl FnOnce(&Finder) + Send + 'static) -> Result<()> {
+        self.jobs.send(Box::new(job)).map_err(|_| stopped())
+    }
+
+    pub fn get_favorites_list(&self) -> Result<Vec<SidebarItem>> {
+        self.call(|finder| finder.get_favorites_list())?
+    }
+}
+
+pub(super) fn stopped() -> FinderError {
+    FinderError::Thread(format!("{} thread has stopped", THREAD_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::finder::Manifest;
+
+    /// Fails to compile unless `T` can be shared between threads.
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn should_be_shareable_between_threads() {
+        assert_send_sync::<SharedFinder>();
+        assert_send_sync::<SidebarItem>();
+        assert_send_sync::<Manifest>();
+        assert_send_sync::<FinderError>();
+    }
+
+    #[test]
+    fn should_report_stopped_thread() {
+        // Arrange
+        let finder = SharedFinder::spawn(|| panic!("no backend")).unwrap();
+
+        // Act
+        let result = finder.call(|_| ());
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(FinderError::Thread(
+                "favkit-finder thread has stopped".to_string()
+            ))
+        );
+    }
+}
@@ -1,30 +1,345 @@
-use std::fmt;
+use std::{cmp::Ordering, fmt, hash, str::FromStr};
 
-#[derive(Debug, PartialEq)]
+use super::{ItemId, Ownership, ResolutionFailure, Visibility, batch};
+
+/// The variant of a [`Target`], without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Kind {
+    AirDrop,
+    Recents,
+    Applications,
+    Custom,
+    Network,
+    Unresolved,
+}
+
+impl Kind {
+    const NAMES: [(Self, &'static str); 6] = [
+        (Self::AirDrop, "airdrop"),
+        (Self::Recents, "recents"),
+        (Self::Applications, "applications"),
+        (Self::Custom, "custom"),
+        (Self::Network, "network"),
+        (Self::Unresolved, "unresolved"),
+    ];
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(kind, _)| *kind == self)
+            .map_or("", |(_, name)| name)
+    }
+
+    /// Returns whether this is one of the items macOS provides itself.
+    pub fn is_built_in(self) -> bool {
+        matches!(self, Self::AirDrop | Self::Recents | Self::Applications)
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(s))
+            .map(|(kind, _)| *kind)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::NAMES.iter().map(|(_, name)| *name).collect();
+                format!(
+                    "unknown kind '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "lowercase")
+)]
 pub enum Target {
     AirDrop,
     Recents,
     Applications,
-    Custom { label: String, path: String },
+    Custom {
+        label: String,
+        path: String,
+    },
+    Network {
+        label: String,
+        url: String,
+    },
+    /// An item Core Services could not resolve to a URL.
+    Unresolved {
+        label: String,
+        failure: ResolutionFailure,
+    },
 }
 
 impl Target {
+    /// The items macOS provides itself, which have no path or URL of their own to set.
+    pub const BUILT_IN: [Self; 3] = [Self::AirDrop, Self::Recents, Self::Applications];
+
     pub fn custom(label: impl Into<String>, path: impl Into<String>) -> Self {
         Self::Custom {
             label: label.into(),
             path: path.into(),
         }
     }
+
+    pub fn network(label: impl Into<String>, url: impl Into<String>) -> Self {
+        Self::Network {
+            label: label.into(),
+            url: url.into(),
+        }
+    }
+
+    pub fn unresolved(label: impl Into<String>, failure: ResolutionFailure) -> Self {
+        Self::Unresolved {
+            label: label.into(),
+            failure,
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        match self {
+            Self::AirDrop => Kind::AirDrop,
+            Self::Recents => Kind::Recents,
+            Self::Applications => Kind::Applications,
+            Self::Custom { .. } => Kind::Custom,
+            Self::Network { .. } => Kind::Network,
+            Self::Unresolved { .. } => Kind::Unresolved,
+        }
+    }
+
+    /// Returns the label Finder shows for this target.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::AirDrop => "AirDrop",
+            Self::Recents => "Recents",
+            Self::Applications => "Applications",
+            Self::Custom { label, .. }
+            | Self::Network { label, .. }
+            | Self::Unresolved { label, .. } => label,
+        }
+    }
+
+    /// Returns the file system path for custom locations.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::Custom { path, .. } => Some(path),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+/// An item in the Finder sidebar, or one a caller wants there.
+///
+/// Items are compared, hashed and ordered by target, then id, visibility and ownership. The
+/// position is where the item was read and takes no part, so an item that moved still equals
+/// its earlier self.
+///
+/// Custom properties are not carried along, since Core Services cannot list them; read one by
+/// name with [`Finder::get_item_property`](super::Finder::get_item_property). Icon references are
+/// left out as well: an `IconRef` is an opaque handle with nothing to compare or serialize.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SidebarItem {
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    id: Option<ItemId>,
     target: Target,
+    #[cfg_attr(feature = "serde", serde(default))]
+    visibility: Visibility,
+    #[cfg_attr(feature = "serde", serde(default))]
+    ownership: Ownership,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    position: Option<usize>,
 }
 
 impl SidebarItem {
     pub fn new(target: Target) -> Self {
-        Self { target }
+        Self {
+            id: None,
+            target,
+            visibility: Visibility::default(),
+            ownership: Ownership::default(),
+            position: None,
+        }
+    }
+
+    /// Starts building an item for the given target.
+    pub fn builder(target: Target) -> SidebarItemBuilder {
+        SidebarItemBuilder {
+            item: Self::new(target),
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<ItemId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    pub fn with_ownership(mut self, ownership: Ownership) -> Self {
+        self.ownership = ownership;
+        self
+    }
+
+    pub fn with_position(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    /// Returns the label Finder shows for the item.
+    pub fn label(&self) -> &str {
+        self.target.label()
+    }
+
+    /// Returns the file system path for custom locations.
+    pub fn path(&self) -> Option<&str> {
+        self.target.path()
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.target.kind()
+    }
+
+    /// Returns the Core Services id, if the item was read from the sidebar.
+    pub fn id(&self) -> Option<ItemId> {
+        self.id
+    }
+
+    /// Returns the zero-based place in the sidebar, if the item was read from it.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.visibility == Visibility::Hidden
+    }
+
+    pub fn ownership(&self) -> Ownership {
+        self.ownership
+    }
+
+    /// Returns whether favkit added the item.
+    pub fn is_managed(&self) -> bool {
+        self.ownership == Ownership::Managed
+    }
+
+    /// The fields items are compared by.
+    fn key(&self) -> (&Target, Option<ItemId>, Visibility, Ownership) {
+        (&self.target, self.id, self.visibility, self.ownership)
+    }
+}
+
+impl PartialEq for SidebarItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SidebarItem {}
+
+impl hash::Hash for SidebarItem {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for SidebarItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SidebarItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Builds the items a caller wants in the sidebar, e.g. for [`Manifest`](super::Manifest).
+///
+/// ```
+/// use favkit::finder::{Kind, SidebarItem, SidebarItemBuilder, Target};
+///
+/// let item = SidebarItemBuilder::location("Builds", "smb://nas.local/builds")
+///     .hidden(true)
+///     .managed(true)
+///     .build();
+/// assert_eq!(item.kind(), Kind::Network);
+/// assert!(item.is_hidden() && item.is_managed());
+///
+/// let applications = SidebarItem::builder(Target::Applications).build();
+/// assert_eq!(applications.label(), "Applications");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SidebarItemBuilder {
+    item: SidebarItem,
+}
+
+impl SidebarItemBuilder {
+    /// Starts from a path (`~` is expanded) or URL, read the way manifests read locations.
+    pub fn location(label: &str, location: &str) -> Self {
+        SidebarItem::builder(batch::target(label, location))
+    }
+
+    pub fn id(mut self, id: impl Into<ItemId>) -> Self {
+        self.item.id = Some(id.into());
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.item.visibility = Visibility::from(hidden);
+        self
+    }
+
+    pub fn managed(mut self, managed: bool) -> Self {
+        self.item.ownership = Ownership::from(managed);
+        self
+    }
+
+    pub fn position(mut self, position: usize) -> Self {
+        self.item.position = Some(position);
+        self
+    }
+
+    pub fn build(self) -> SidebarItem {
+        self.item
     }
 }
 
@@ -35,7 +350,19 @@ impl fmt::Display for SidebarItem {
             Target::Recents => write!(f, "Recents"),
             Target::Applications => write!(f, "Applications"),
             Target::Custom { label, path } => write!(f, "{} -> {}", label, path),
+            Target::Network { label, url } => write!(f, "{} -> {}", label, url),
+            Target::Unresolved { label, failure } => {
+                write!(f, "{} (unresolved: {})", label, failure)
+            }
+        }?;
+
+        if self.is_hidden() {
+            write!(f, " (hidden)")?;
+        }
+        if self.is_managed() {
+            write!(f, " (managed)")?;
         }
+        Ok(())
     }
 }
 
@@ -68,4 +395,170 @@ mod tests {
         let item = SidebarItem::new(Target::Applications);
         assert_eq!(format!("{}", item), "Applications");
     }
+
+    #[test]
+    fn should_create_sidebar_item_with_network_target() {
+        let item = SidebarItem::new(Target::network("NAS", "smb://nas.local/share"));
+        assert_eq!(format!("{}", item), "NAS -> smb://nas.local/share");
+    }
+
+    #[test]
+    fn should_create_sidebar_item_with_unresolved_target() {
+        let item = SidebarItem::new(Target::unresolved("Old", ResolutionFailure::NotFound));
+        assert_eq!(format!("{}", item), "Old (unresolved: target not found)");
+    }
+
+    #[test]
+    fn should_mark_hidden_items() {
+        let item = SidebarItem::new(Target::AirDrop).with_visibility(Visibility::Hidden);
+        assert_eq!(format!("{}", item), "AirDrop (hidden)");
+    }
+
+    #[test]
+    fn should_mark_managed_items() {
+        let item = SidebarItem::new(Target::custom("Projects", "/Users/user/Projects"))
+            .with_visibility(Visibility::Hidden)
+            .with_ownership(Ownership::Managed);
+        assert_eq!(
+            format!("{}", item),
+            "Projects -> /Users/user/Projects (hidden) (managed)"
+        );
+    }
+
+    #[test]
+    fn should_expose_target_label_and_path() {
+        let target = Target::custom("Projects", "/Users/user/Projects");
+        assert_eq!(target.label(), "Projects");
+        assert_eq!(target.path(), Some("/Users/user/Projects"));
+        assert_eq!(Target::Applications.label(), "Applications");
+        assert_eq!(Target::Applications.path(), None);
+    }
+
+    #[test]
+    fn should_name_target_kinds() {
+        assert_eq!(Target::AirDrop.kind().to_string(), "airdrop");
+        assert_eq!(
+            Target::network("NAS", "smb://nas.local/share").kind(),
+            Kind::Network
+        );
+        assert!(Kind::Recents.is_built_in());
+        assert!(!Kind::Custom.is_built_in());
+    }
+
+    #[test]
+    fn should_parse_kind_ignoring_case() {
+        assert_eq!("Custom".parse::<Kind>(), Ok(Kind::Custom));
+        assert!("folder".parse::<Kind>().is_err());
+    }
+
+    #[test]
+    fn should_expose_item_metadata() {
+        let item = SidebarItem::new(Target::AirDrop)
+            .with_id(7)
+            .with_visibility(Visibility::Hidden);
+        assert_eq!(item.id(), Some(ItemId::from(7)));
+        assert!(item.is_hidden());
+    }
+
+    #[test]
+    fn should_expose_item_target_details() {
+        // Arrange
+        let item = SidebarItem::builder(Target::custom("Projects", "/Users/user/Projects"))
+            .id(7)
+            .position(2)
+            .build();
+
+        // Act & Assert
+        assert_eq!(item.label(), "Projects");
+        assert_eq!(item.path(), Some("/Users/user/Projects"));
+        assert_eq!(item.kind(), Kind::Custom);
+        assert_eq!(item.id(), Some(ItemId::from(7)));
+        assert_eq!(item.position(), Some(2));
+    }
+
+    #[test]
+    fn should_build_items_from_locations() {
+        assert_eq!(
+            SidebarItemBuilder::location("AirDrop", "nwnode://domain-AirDrop")
+                .hidden(true)
+                .managed(true)
+                .build(),
+            SidebarItem::new(Target::AirDrop)
+                .with_visibility(Visibility::Hidden)
+                .with_ownership(Ownership::Managed)
+        );
+    }
+
+    #[test]
+    fn should_compare_items_regardless_of_position() {
+        // Arrange
+        let read = SidebarItem::new(Target::Recents)
+            .with_id(3)
+            .with_position(4);
+        let moved = read.clone().with_position(0);
+
+        // Act
+        let mut items = std::collections::BTreeSet::new();
+        items.insert(read.clone());
+        items.insert(moved.clone());
+
+        // Assert
+        assert_eq!(read, moved);
+        assert_eq!(items.len(), 1);
+    }
+
+    #[test]
+    fn should_order_items_by_target() {
+        // Arrange
+        let mut items = [
+            SidebarItem::new(Target::custom("Work", "/Users/user/Work")),
+            SidebarItem::new(Target::Applications),
+            SidebarItem::new(Target::custom("Music", "/Users/user/Music")),
+            SidebarItem::new(Target::AirDrop),
+        ];
+
+        // Act
+        items.sort();
+
+        // Assert
+        let labels: Vec<&str> = items.iter().map(SidebarItem::label).collect();
+        assert_eq!(labels, vec!["AirDrop", "Applications", "Music", "Work"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn should_round_trip_through_serde() {
+        // Arrange
+        let item = SidebarItem::new(Target::unresolved(
+            "NAS",
+            ResolutionFailure::VolumeNotMounted,
+        ))
+        .with_id(9)
+        .with_visibility(Visibility::Hidden)
+        .with_position(1);
+
+        // Act
+        let json = serde_json::to_value(&item).unwrap();
+        let parsed: SidebarItem = serde_json::from_value(json.clone()).unwrap();
+
+        // Assert
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": 9,
+                "target": {"kind": "unresolved", "label": "NAS", "failure": "volume_not_mounted"},
+                "visibility": "hidden",
+                "ownership": "user",
+                "position": 1,
+            })
+        );
+        assert_eq!((parsed.clone(), parsed.position()), (item, Some(1)));
+    }
+
+    #[test]
+    fn should_be_visible_without_id_by_default() {
+        let item = SidebarItem::new(Target::Recents);
+        assert_eq!(item.id(), None);
+        assert!(!item.is_hidden());
+    }
 }
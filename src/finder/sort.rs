@@ -0,0 +1,326 @@
+use std::{cmp::Ordering, fmt};
+
+use super::{SidebarItem, Target};
+
+/// Key the sidebar is sorted by once priority labels are placed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Keeps the current order.
+    #[default]
+    Current,
+    /// Sorts by label, ignoring case.
+    Label,
+    /// Sorts by path or URL; built-in items come first.
+    Path,
+    /// Moves AirDrop, Recents and Applications to the top, keeping the rest in place.
+    SpecialFirst,
+}
+
+/// Target order of the sidebar: labels listed by priority first, then the rest by key.
+///
+/// ```
+/// use favkit::finder::{SortKey, SortOrder};
+///
+/// // "Applications, Desktop, Downloads, then alphabetical"
+/// let order = SortOrder::new(SortKey::Label).with_priority(["Applications", "Desktop", "Downloads"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortOrder {
+    priority: Vec<String>,
+    key: SortKey,
+}
+
+impl SortOrder {
+    pub fn new(key: SortKey) -> Self {
+        Self {
+            priority: Vec::new(),
+            key,
+        }
+    }
+
+    /// Places items with these labels first, in the given order. Labels match ignoring case.
+    pub fn with_priority(mut self, labels: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.priority = labels.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn rank(&self, item: &SidebarItem) -> usize {
+        let label = item.target().label();
+        self.priority
+            .iter()
+            .position(|priority| priority.eq_ignore_ascii_case(label))
+            .unwrap_or(self.priority.len())
+    }
+
+    fn compare(&self, a: &SidebarItem, b: &SidebarItem) -> Ordering {
+        self.rank(a)
+            .cmp(&self.rank(b))
+            .then_with(|| match self.key {
+                SortKey::Current => Ordering::Equal,
+                SortKey::Label => compare_labels(a.target(), b.target()),
+                SortKey::Path => location(a.target()).cmp(&location(b.target())),
+                SortKey::SpecialFirst => b
+                    .target()
+                    .kind()
+                    .is_built_in()
+                    .cmp(&a.target().kind().is_built_in()),
+            })
+    }
+}
+
+fn compare_labels(a: &Target, b: &Target) -> Ordering {
+    a.label().to_lowercase().cmp(&b.label().to_lowercase())
+}
+
+fn location(target: &Target) -> Option<&str> {
+    match target {
+        Target::Custom { path, .. } => Some(path),
+        Target::Network { url, .. } => Some(url),
+        _ => None,
+    }
+}
+
+/// One step towards the target order: put `item` right after another item, or at the top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub item: SidebarItem,
+    pub after: Option<SidebarItem>,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.after {
+            Some(after) => write!(f, "{} after {}", self.item, after.target().label()),
+            None => write!(f, "{} to the top", self.item),
+        }
+    }
+}
+
+/// Returns the items in the target order. Items comparing equal keep their current order.
+pub fn sort(items: &[SidebarItem], order: &SortOrder) -> Vec<SidebarItem> {
+    permutation(items, order)
+        .into_iter()
+        .map(|index| items[index].clone())
+        .collect()
+}
+
+/// Plans the fewest moves that bring the items into the target order.
+///
+/// Items forming the longest run already in target order stay put; every other item is moved
+/// right after its predecessor in the target order. Moves must be applied in the returned order.
+pub fn moves(items: &[SidebarItem], order: &SortOrder) -> Vec<Move> {
+    let target = permutation(items, order);
+    let staying = longest_increasing(&target);
+
+    target
+        .iter()
+        .enumerate()
+        .filter(|(position, _)| !staying[*position])
+        .map(|(position, &index)| Move {
+            item: items[index].clone(),
+            after: position
+                .checked_sub(1)
+                .map(|previous| items[target[previous]].clone()),
+        })
+        .collect()
+}
+
+/// Current indices of the items, listed in target order.
+fn permutation(items: &[SidebarItem], order: &SortOrder) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..items.len()).collect();
+    indices.sort_by(|&a, &b| order.compare(&items[a], &items[b]));
+    indices
+}
+
+/// Marks the positions of one longest strictly increasing subsequence.
+pub(super) fn longest_increasing(values: &[usize]) -> Vec<bool> {
+    let mut lengths = vec![1; values.len()];
+    let mut previous = vec![None; values.len()];
+
+    for i in 0..values.len() {
+        for j in 0..i {
+            if values[j] < values[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+
+    let mut marked = vec![false; values.len()];
+    let mut current = (0..values.len()).max_by_key(|&i| lengths[i]);
+    while let Some(i) = current {
+        marked[i] = true;
+        current = previous[i];
+    }
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn custom(id: u32, label: &str, path: &str) -> SidebarItem {
+        SidebarItem::new(Target::custom(label, path)).with_id(id)
+    }
+
+    fn labels(items: &[SidebarItem]) -> Vec<&str> {
+        items.iter().map(|item| item.target().label()).collect()
+    }
+
+    fn sidebar() -> Vec<SidebarItem> {
+        vec![
+            custom(1, "projects", "/Users/user/Projects"),
+            SidebarItem::new(Target::AirDrop).with_id(2),
+            custom(3, "Downloads", "/Users/user/Downloads"),
+            SidebarItem::new(Target::Applications).with_id(4),
+            custom(5, "Desktop", "/Users/user/Desktop"),
+        ]
+    }
+
+    /// Applies moves the way Core Services would, to check they reach the target order.
+    fn apply(items: &[SidebarItem], moves: &[Move]) -> Vec<SidebarItem> {
+        let mut items = items.to_vec();
+        for step in moves {
+            let from = items.iter().position(|item| *item == step.item).unwrap();
+            let item = items.remove(from);
+            let to = step.after.as_ref().map_or(0, |after| {
+                items.iter().position(|item| item == after).unwrap() + 1
+            });
+            items.insert(to, item);
+        }
+        items
+    }
+
+    #[test]
+    fn should_sort_alphabetically_ignoring_case() {
+        // Act
+        let sorted = sort(&sidebar(), &SortOrder::new(SortKey::Label));
+
+        // Assert
+        assert_eq!(
+            labels(&sorted),
+            vec![
+                "AirDrop",
+                "Applications",
+                "Desktop",
+                "Downloads",
+                "projects"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_sort_by_path_with_built_in_items_first() {
+        // Act
+        let sorted = sort(&sidebar(), &SortOrder::new(SortKey::Path));
+
+        // Assert
+        assert_eq!(
+            labels(&sorted),
+            vec![
+                "AirDrop",
+                "Applications",
+                "Desktop",
+                "Downloads",
+                "projects"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_move_special_items_first_keeping_the_rest() {
+        // Act
+        let sorted = sort(&sidebar(), &SortOrder::new(SortKey::SpecialFirst));
+
+        // Assert
+        assert_eq!(
+            labels(&sorted),
+            vec![
+                "AirDrop",
+                "Applications",
+                "projects",
+                "Downloads",
+                "Desktop"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_place_priority_labels_first() {
+        // Arrange
+        let order =
+            SortOrder::new(SortKey::Label).with_priority(["Applications", "desktop", "Downloads"]);
+
+        // Act
+        let sorted = sort(&sidebar(), &order);
+
+        // Assert
+        assert_eq!(
+            labels(&sorted),
+            vec![
+                "Applications",
+                "Desktop",
+                "Downloads",
+                "AirDrop",
+                "projects"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_plan_no_moves_when_already_sorted() {
+        // Arrange
+        let items = sort(&sidebar(), &SortOrder::new(SortKey::Label));
+
+        // Act & Assert
+        assert!(moves(&items, &SortOrder::new(SortKey::Label)).is_empty());
+    }
+
+    #[test]
+    fn should_plan_single_move_for_one_misplaced_item() {
+        // Arrange
+        let items = vec![
+            custom(1, "B", "/b"),
+            custom(2, "C", "/c"),
+            custom(3, "D", "/d"),
+            custom(4, "A", "/a"),
+        ];
+
+        // Act
+        let moves = moves(&items, &SortOrder::new(SortKey::Label));
+
+        // Assert
+        assert_eq!(
+            moves,
+            vec![Move {
+                item: custom(4, "A", "/a"),
+                after: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn should_reach_target_order_with_minimal_moves() {
+        // Arrange
+        let items = sidebar();
+        let order = SortOrder::new(SortKey::Label);
+
+        // Act
+        let moves = moves(&items, &order);
+
+        // Assert
+        assert_eq!(moves.len(), 2);
+        assert_eq!(apply(&items, &moves), sort(&items, &order));
+    }
+
+    #[test]
+    fn should_describe_moves() {
+        let step = Move {
+            item: custom(1, "A", "/a"),
+            after: Some(SidebarItem::new(Target::AirDrop)),
+        };
+        assert_eq!(step.to_string(), "A -> /a after AirDrop");
+    }
+}
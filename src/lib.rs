@@ -1,5 +1,10 @@
+#[cfg(feature = "cli")]
+pub mod cli;
 pub mod finder;
 pub mod system;
 
+#[cfg(all(test, feature = "serde"))]
+mod testing;
+
 pub use finder::Finder;
 pub use system::RealMacOsApi;
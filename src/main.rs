@@ -1,15 +1,43 @@
-use favkit::Finder;
+use std::{io, process::ExitCode};
+
+use clap::Parser;
+use favkit::{
+    Finder,
+    cli::{self, Cli, Config},
+    system::FileJournal,
+};
 
 #[cfg_attr(coverage, coverage(off))]
-fn main() {
-    let finder = Finder::default();
-
-    match finder.get_favorites_list() {
-        Ok(items) => {
-            for item in items {
-                println!("{}", item);
-            }
+fn main() -> ExitCode {
+    let cli = Cli::parse_from(cli::personality_args(std::env::args_os()));
+    let config = match Config::load(&cli, std::env::vars()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let finder = Finder::default().with_resolution(config.resolution());
+    let finder = match (config.journal_path(), config.journal_retention) {
+        (Some(path), Some(retention)) => {
+            finder.with_journal(FileJournal::new(path).with_retention(retention))
+        }
+        (Some(path), None) => finder.with_journal(FileJournal::new(path)),
+        (None, _) => finder,
+    };
+
+    match cli::run_with_config(
+        cli,
+        &config,
+        &finder,
+        io::BufReader::new(io::stdin()),
+        &mut io::stdout().lock(),
+    ) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
         }
-        Err(err) => eprintln!("Error: {}", err),
     }
 }
@@ -0,0 +1,180 @@
+//! Targets favkit owns between activations, stored as JSON in the user's state directory.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{finder::Target, system::favorites::MacOsUrl};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StateError {
+    #[error("failed to access activation state {path}: {reason}")]
+    Io { path: PathBuf, reason: String },
+    #[error("corrupt activation state {path}: {reason}")]
+    Corrupt { path: PathBuf, reason: String },
+}
+
+/// File recording the targets favkit added during activations, keyed by URL.
+#[derive(Debug, Clone)]
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `favkit/activation.json` in the state directory, or the local data directory on macOS.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("favkit").join("activation.json"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the owned targets; none before the first activation.
+    pub fn load(&self) -> Result<Vec<Target>, StateError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(self.io_error(error)),
+        };
+        let state: StateRecord =
+            serde_json::from_str(&text).map_err(|error| StateError::Corrupt {
+                path: self.path.clone(),
+                reason: error.to_string(),
+            })?;
+        Ok(state.owned.into_iter().map(Target::from).collect())
+    }
+
+    /// Replaces the owned targets, creating the file and its directory on first use.
+    ///
+    /// The state is written to a temporary file first and renamed over the old one, so an
+    /// interrupted save leaves the previous state intact.
+    pub fn save(&self, owned: &[Target]) -> Result<(), StateError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|error| self.io_error(error))?;
+        }
+        let state = StateRecord {
+            owned: owned.iter().filter_map(OwnedRecord::from_target).collect(),
+        };
+        let text = serde_json::to_string_pretty(&state).map_err(|error| StateError::Io {
+            path: self.path.clone(),
+            reason: error.to_string(),
+        })?;
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, text + "\n")
+            .and_then(|()| fs::rename(&temporary, &self.path))
+            .map_err(|error| self.io_error(error))
+    }
+
+    fn io_error(&self, error: io::Error) -> StateError {
+        StateError::Io {
+            path: self.path.clone(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StateRecord {
+    owned: Vec<OwnedRecord>,
+}
+
+/// An owned target; the label is kept for readers of the file, matching uses the URL.
+#[derive(Debug, Serialize, Deserialize)]
+struct OwnedRecord {
+    url: String,
+    label: String,
+}
+
+impl OwnedRecord {
+    fn from_target(target: &Target) -> Option<Self> {
+        let url = MacOsUrl::try_from(target).ok()?;
+        Some(Self {
+            url: url.to_string(),
+            label: target.label().to_string(),
+        })
+    }
+}
+
+impl From<OwnedRecord> for Target {
+    fn from(record: OwnedRecord) -> Self {
+        MacOsUrl::from(record.url.as_str()).into_target(record.label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn state(dir: &TempDir) -> StateFile {
+        StateFile::new(dir.join("activation.json"))
+    }
+
+    #[test]
+    fn should_own_nothing_before_first_activation() {
+        let dir = TempDir::new("activation");
+        assert_eq!(state(&dir).load(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn should_read_back_saved_targets() {
+        // Arrange
+        let dir = TempDir::new("activation");
+        let state = state(&dir);
+        let owned = vec![
+            Target::custom("Projects", "/Users/user/Projects/"),
+            Target::network("NAS", "smb://nas.local/share"),
+        ];
+
+        // Act
+        state.save(&owned).unwrap();
+
+        // Assert
+        assert_eq!(state.load(), Ok(owned));
+    }
+
+    #[test]
+    fn should_replace_saved_targets_without_leaving_temporary_file() {
+        // Arrange
+        let dir = TempDir::new("activation");
+        let state = state(&dir);
+        state
+            .save(&[Target::custom("Projects", "/Users/user/Projects/")])
+            .unwrap();
+        let owned = vec![Target::network("NAS", "smb://nas.local/share")];
+
+        // Act
+        state.save(&owned).unwrap();
+
+        // Assert
+        assert_eq!(state.load(), Ok(owned));
+        assert!(!dir.join("activation.json.tmp").exists());
+    }
+
+    #[test]
+    fn should_report_corrupt_state() {
+        // Arrange
+        let dir = TempDir::new("activation");
+        let state = state(&dir);
+        fs::write(state.path(), "not json").unwrap();
+
+        // Act
+        let result = state.load();
+
+        // Assert
+        assert!(matches!(result, Err(StateError::Corrupt { .. })));
+    }
+}
@@ -1,12 +1,13 @@
 use core_foundation::{
     array::CFArrayRef,
-    base::{CFAllocatorRef, CFTypeRef},
+    base::{CFAllocatorRef, CFTypeRef, OSStatus},
+    dictionary::CFDictionaryRef,
     error::CFErrorRef,
     string::CFStringRef,
     url::CFURLRef,
 };
 use core_services::{
-    LSSharedFileListItemRef, LSSharedFileListRef, LSSharedFileListResolutionFlags,
+    IconRef, LSSharedFileListItemRef, LSSharedFileListRef, LSSharedFileListResolutionFlags,
 };
 
 /// Trait for interacting with MacOS APIs.
@@ -68,4 +69,91 @@ pub trait MacOsApi {
         flags: LSSharedFileListResolutionFlags,
         error: *mut CFErrorRef,
     ) -> CFURLRef;
+
+    /// Gets the unique identifier of a shared file list item.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because:
+    /// - It interacts with raw C pointers through Core Foundation API
+    /// - The caller must ensure the item reference is valid
+    unsafe fn ls_shared_file_list_item_get_id(&self, item: LSSharedFileListItemRef) -> u32;
+
+    /// Gets the value of a named property of a shared file list item.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because:
+    /// - It interacts with raw C pointers through Core Foundation API
+    /// - The caller must ensure the item and property name references are valid
+    /// - The returned property reference, if not null, must be properly released
+    unsafe fn ls_shared_file_list_item_copy_property(
+        &self,
+        item: LSSharedFileListItemRef,
+        name: CFStringRef,
+    ) -> CFTypeRef;
+
+    /// Sets the value of a named property of a shared file list item.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because:
+    /// - It interacts with raw C pointers through Core Foundation API
+    /// - The caller must ensure the item, property name and value references are valid
+    unsafe fn ls_shared_file_list_item_set_property(
+        &self,
+        item: LSSharedFileListItemRef,
+        name: CFStringRef,
+        value: CFTypeRef,
+    ) -> OSStatus;
+
+    /// Removes an item from the shared file list.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because:
+    /// - It interacts with raw C pointers through Core Foundation API
+    /// - The caller must ensure the list and item references are valid
+    unsafe fn ls_shared_file_list_item_remove(
+        &self,
+        list: LSSharedFileListRef,
+        item: LSSharedFileListItemRef,
+    ) -> OSStatus;
+
+    /// Moves an item within the shared file list.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because:
+    /// - It interacts with raw C pointers through Core Foundation API
+    /// - The caller must ensure the list, item and `after` references are valid
+    /// - `after` may also be one of the `kLSSharedFileListItemBeforeFirst` or
+    ///   `kLSSharedFileListItemLast` markers
+    unsafe fn ls_shared_file_list_item_move(
+        &self,
+        list: LSSharedFileListRef,
+        item: LSSharedFileListItemRef,
+        after: LSSharedFileListItemRef,
+    ) -> OSStatus;
+
+    /// Inserts a new item pointing at `url` into the shared file list.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because:
+    /// - It interacts with raw C pointers through Core Foundation API
+    /// - The caller must ensure the list, `after`, name and URL references are valid
+    /// - The icon and property references may be null
+    /// - The returned item reference, if not null, must be properly released
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn ls_shared_file_list_insert_item_url(
+        &self,
+        list: LSSharedFileListRef,
+        after: LSSharedFileListItemRef,
+        name: CFStringRef,
+        icon: IconRef,
+        url: CFURLRef,
+        properties_to_set: CFDictionaryRef,
+        properties_to_clear: CFArrayRef,
+    ) -> LSSharedFileListItemRef;
 }
@@ -195,6 +195,22 @@ impl<T: TCFType> CFRef<T> {
             .map(Self)
             .ok_or(Error::NullPointer)
     }
+
+    /// Takes over a reference returned by a Core Foundation `Create` or `Copy` function.
+    ///
+    /// Unlike [`CFRef::try_from_ref`] the reference is not retained again, so it is released
+    /// exactly once when the `CFRef` is dropped.
+    ///
+    /// Returns `Error::NullPointer` if the reference is null.
+    pub fn try_from_create_rule(raw: T::Ref) -> Result<Self>
+    where
+        T::Ref: TCFTypeRef,
+    {
+        (!raw.as_void_ptr().is_null())
+            .then(|| unsafe { T::wrap_under_create_rule(raw) })
+            .map(Self)
+            .ok_or(Error::NullPointer)
+    }
 }
 
 impl<T: TCFType> Deref for CFRef<T> {
@@ -299,6 +315,34 @@ mod tests {
             assert!(!wrapped.as_concrete_TypeRef().is_null());
             Ok(())
         }
+
+        #[test]
+        fn should_return_error_for_null_created_string() {
+            // Arrange
+            let ptr: CFStringRef = std::ptr::null_mut();
+
+            // Act
+            let result = CFRef::<CFString>::try_from_create_rule(ptr);
+
+            // Assert
+            assert!(matches!(result.unwrap_err(), Error::NullPointer));
+        }
+
+        #[test]
+        fn should_take_over_created_reference() -> Result<()> {
+            // Arrange
+            let string = CFString::new("test");
+            let ptr = string.as_concrete_TypeRef();
+            let retained = string.retain_count();
+            std::mem::forget(string.clone());
+
+            // Act
+            drop(CFRef::<CFString>::try_from_create_rule(ptr)?);
+
+            // Assert
+            assert_eq!(string.retain_count(), retained);
+            Ok(())
+        }
     }
 
     mod raw_pointer {
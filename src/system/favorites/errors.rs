@@ -10,6 +10,14 @@ pub enum FavoritesError {
     NullUrlHandle,
     #[error("failed to get display name: null handle")]
     NullDisplayNameHandle,
+    #[error("failed to get item property: null handle")]
+    NullPropertyHandle,
+    #[error("invalid URL: {0}")]
+    InvalidUrl(String),
+    #[error("failed to insert item: null handle")]
+    NullItemHandle,
+    #[error("failed to update favorites: OSStatus {0}")]
+    OperationFailed(i32),
 }
 
 pub type Result<T> = std::result::Result<T, FavoritesError>;
@@ -1,34 +1,57 @@
-use core_services::OpaqueLSSharedFileListRef;
+use core_foundation::{base::TCFType, declare_TCFType, impl_TCFType};
+use core_services::{LSSharedFileListGetTypeID, LSSharedFileListRef};
 
 use crate::system::{
-    core_foundation::RawRef,
+    core_foundation::CFRef,
     favorites::errors::{FavoritesError, Result},
 };
 
-pub struct FavoritesHandle(RawRef<OpaqueLSSharedFileListRef>);
+declare_TCFType!(
+    /// A Core Services shared file list, released when dropped.
+    SharedFileList, LSSharedFileListRef
+);
+impl_TCFType!(
+    SharedFileList,
+    LSSharedFileListRef,
+    LSSharedFileListGetTypeID
+);
 
-impl TryFrom<*mut OpaqueLSSharedFileListRef> for FavoritesHandle {
+/// Owning handle to the favorites list returned by `LSSharedFileListCreate`.
+///
+/// The list is released when the last clone of the handle is dropped.
+#[derive(Clone)]
+pub struct FavoritesHandle(CFRef<SharedFileList>);
+
+impl TryFrom<LSSharedFileListRef> for FavoritesHandle {
     type Error = FavoritesError;
 
-    fn try_from(ptr: *mut OpaqueLSSharedFileListRef) -> Result<Self> {
-        RawRef::try_from(ptr)
+    /// Takes over the reference Core Services created for the caller.
+    fn try_from(ptr: LSSharedFileListRef) -> Result<Self> {
+        CFRef::try_from_create_rule(ptr)
             .map(Self)
             .map_err(|_| FavoritesError::NullListHandle)
     }
 }
 
-impl From<FavoritesHandle> for *mut OpaqueLSSharedFileListRef {
-    fn from(handle: FavoritesHandle) -> Self {
-        handle.0.into()
+impl From<&FavoritesHandle> for LSSharedFileListRef {
+    fn from(handle: &FavoritesHandle) -> Self {
+        handle.0.as_concrete_TypeRef()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::ptr::NonNull;
+    use core_foundation::string::CFString;
 
     use super::*;
-    const FAVORITES_REF: *mut OpaqueLSSharedFileListRef = 42 as *mut _;
+
+    /// A Core Foundation object standing in for a list, with one reference for the handle.
+    fn created_list() -> (CFString, LSSharedFileListRef) {
+        let list = CFString::new("favorites list for handle tests");
+        let list_ref = list.as_concrete_TypeRef() as LSSharedFileListRef;
+        std::mem::forget(list.clone());
+        (list, list_ref)
+    }
 
     #[test]
     fn should_fail_when_handle_is_null() {
@@ -40,26 +63,30 @@ mod tests {
     }
 
     #[test]
-    fn should_wrap_handle() -> Result<()> {
+    fn should_unwrap_handle() -> Result<()> {
         // Arrange
-        let favorites_ref = FAVORITES_REF;
+        let (_list, favorites_ref) = created_list();
+        let handle = FavoritesHandle::try_from(favorites_ref)?;
 
-        // Act & Assert
-        let _handle = FavoritesHandle::try_from(favorites_ref)?;
+        // Act
+        let unwrapped: LSSharedFileListRef = (&handle).into();
+
+        // Assert
+        assert_eq!(unwrapped, favorites_ref);
         Ok(())
     }
 
     #[test]
-    fn should_unwrap_handle() {
+    fn should_release_list_when_dropped() -> Result<()> {
         // Arrange
-        let favorites_ref = FAVORITES_REF;
-        let ptr = NonNull::new(favorites_ref).unwrap();
-        let handle = FavoritesHandle(RawRef::new(ptr));
+        let (list, favorites_ref) = created_list();
+        let retained = list.retain_count();
 
         // Act
-        let unwrapped: *mut OpaqueLSSharedFileListRef = handle.into();
+        drop(FavoritesHandle::try_from(favorites_ref)?);
 
         // Assert
-        assert_eq!(unwrapped, favorites_ref);
+        assert_eq!(list.retain_count(), retained - 1);
+        Ok(())
     }
 }
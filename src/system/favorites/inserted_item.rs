@@ -0,0 +1,81 @@
+use std::ptr::NonNull;
+
+use core_foundation::{base::TCFType, declare_TCFType, impl_TCFType};
+use core_services::{LSSharedFileListItemGetTypeID, LSSharedFileListItemRef};
+
+use crate::system::{
+    core_foundation::CFRef,
+    favorites::{
+        errors::{FavoritesError, Result},
+        snapshot_item::SnapshotItem,
+    },
+};
+
+declare_TCFType!(
+    /// A Core Services shared file list item, released when dropped.
+    SharedFileListItem, LSSharedFileListItemRef
+);
+impl_TCFType!(
+    SharedFileListItem,
+    LSSharedFileListItemRef,
+    LSSharedFileListItemGetTypeID
+);
+
+/// Owning handle to an item returned by `LSSharedFileListInsertItemURL`.
+pub struct InsertedItem(CFRef<SharedFileListItem>);
+
+impl InsertedItem {
+    /// The item, valid for as long as this handle lives.
+    pub fn item(&self) -> SnapshotItem {
+        let ptr = NonNull::new(self.0.as_concrete_TypeRef()).expect("inserted items are not null");
+        SnapshotItem::from(ptr)
+    }
+}
+
+impl TryFrom<LSSharedFileListItemRef> for InsertedItem {
+    type Error = FavoritesError;
+
+    /// Takes over the reference Core Services created for the caller.
+    fn try_from(ptr: LSSharedFileListItemRef) -> Result<Self> {
+        CFRef::try_from_create_rule(ptr)
+            .map(Self)
+            .map_err(|_| FavoritesError::NullItemHandle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core_foundation::string::CFString;
+
+    use super::*;
+
+    /// A Core Foundation object standing in for an item, with one reference for the handle.
+    fn created_item() -> (CFString, LSSharedFileListItemRef) {
+        let item = CFString::new("inserted item for handle tests");
+        let item_ref = item.as_concrete_TypeRef() as LSSharedFileListItemRef;
+        std::mem::forget(item.clone());
+        (item, item_ref)
+    }
+
+    #[test]
+    fn should_fail_when_inserted_item_is_null() {
+        assert!(InsertedItem::try_from(std::ptr::null_mut()).is_err());
+    }
+
+    #[test]
+    fn should_release_item_when_dropped() -> Result<()> {
+        // Arrange
+        let (item, item_ref) = created_item();
+        let retained = item.retain_count();
+
+        // Act
+        let inserted = InsertedItem::try_from(item_ref)?;
+        let unwrapped: LSSharedFileListItemRef = (&inserted.item()).into();
+        drop(inserted);
+
+        // Assert
+        assert_eq!(unwrapped, item_ref);
+        assert_eq!(item.retain_count(), retained - 1);
+        Ok(())
+    }
+}
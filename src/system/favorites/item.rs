@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::{
     finder::Target,
-    system::favorites::{DisplayName, Url},
+    system::favorites::{DisplayName, FavoritesError, Url},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,35 +11,128 @@ pub enum MacOsUrl {
     Recents,
     Applications,
     Custom(String),
+    Network(String),
 }
 
 impl MacOsUrl {
     const AIRDROP: &'static str = "nwnode://domain-AirDrop";
     const RECENTS: &'static str = "file:///System/Library/CoreServices/Finder.app/Contents/Resources/MyLibraries/myDocuments.cannedSearch/";
     const APPLICATIONS: &'static str = "file:///Applications/";
+    const FILE_SCHEME: &'static str = "file://";
+    const SCHEME_SEPARATOR: &'static str = "://";
 
+    /// Path of a `file://` URL, percent-decoded; anything else is taken as a path already.
     fn clean_path(url: impl AsRef<str>) -> String {
-        url.as_ref()
-            .strip_prefix("file://")
-            .and_then(|p| p.strip_suffix('/'))
-            .unwrap_or(url.as_ref())
-            .to_string()
+        let url = url.as_ref();
+        match url.strip_prefix(Self::FILE_SCHEME) {
+            Some(path) => decode_path(path.strip_suffix('/').unwrap_or(path)),
+            None => url.to_string(),
+        }
+    }
+
+    fn is_network(url: &str) -> bool {
+        !url.starts_with(Self::FILE_SCHEME) && url.contains(Self::SCHEME_SEPARATOR)
     }
 }
 
-impl From<Url> for MacOsUrl {
-    fn from(url: Url) -> Self {
-        let url_str = url.to_string();
+impl MacOsUrl {
+    /// Builds the sidebar target this URL points at, using `label` for custom locations.
+    pub fn into_target(self, label: impl Into<String>) -> Target {
+        match self {
+            Self::AirDrop => Target::AirDrop,
+            Self::Recents => Target::Recents,
+            Self::Applications => Target::Applications,
+            Self::Custom(path) => Target::custom(label, path),
+            Self::Network(url) => Target::network(label, url),
+        }
+    }
+}
 
-        match url_str.as_str() {
+impl From<&str> for MacOsUrl {
+    fn from(url: &str) -> Self {
+        match url {
             Self::AIRDROP => Self::AirDrop,
             Self::RECENTS => Self::Recents,
             Self::APPLICATIONS => Self::Applications,
+            url if Self::is_network(url) => Self::Network(url.to_string()),
             url => Self::Custom(Self::clean_path(url)),
         }
     }
 }
 
+impl From<Url> for MacOsUrl {
+    fn from(url: Url) -> Self {
+        match Self::from(url.to_string().as_str()) {
+            Self::Custom(_) => Self::Custom(url.file_system_path()),
+            other => other,
+        }
+    }
+}
+
+impl TryFrom<&Target> for MacOsUrl {
+    type Error = FavoritesError;
+
+    fn try_from(target: &Target) -> Result<Self, Self::Error> {
+        match target {
+            Target::AirDrop => Ok(Self::AirDrop),
+            Target::Recents => Ok(Self::Recents),
+            Target::Applications => Ok(Self::Applications),
+            Target::Custom { path, .. } => Ok(Self::Custom(path.clone())),
+            Target::Network { url, .. } => Ok(Self::Network(url.clone())),
+            Target::Unresolved { label, .. } => Err(FavoritesError::InvalidUrl(label.clone())),
+        }
+    }
+}
+
+impl fmt::Display for MacOsUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AirDrop => write!(f, "{}", Self::AIRDROP),
+            Self::Recents => write!(f, "{}", Self::RECENTS),
+            Self::Applications => write!(f, "{}", Self::APPLICATIONS),
+            Self::Custom(path) => write!(f, "{}{}/", Self::FILE_SCHEME, encode_path(path)),
+            Self::Network(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Whether a byte may appear in the path of a file URL as is, like Core Foundation leaves it.
+fn is_url_safe(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~/!$&'()*+,;=:@".contains(&byte)
+}
+
+fn encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match is_url_safe(byte) {
+            true => char::from(byte).to_string(),
+            false => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn decode_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|hex| bytes[index] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 #[derive(Debug, Clone)]
 pub struct FavoriteItem {
     url: Url,
@@ -60,31 +153,19 @@ impl fmt::Display for FavoriteItem {
 
 impl From<FavoriteItem> for Target {
     fn from(item: FavoriteItem) -> Self {
-        match MacOsUrl::from(item.url) {
-            MacOsUrl::AirDrop => Target::AirDrop,
-            MacOsUrl::Recents => Target::Recents,
-            MacOsUrl::Applications => Target::Applications,
-            MacOsUrl::Custom(path) => Target::custom(item.name.to_string(), path),
-        }
+        MacOsUrl::from(item.url).into_target(item.name.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use core_foundation::{
-        base::TCFType,
-        string::CFString,
-        url::{CFURL, kCFURLPOSIXPathStyle},
-    };
+    use core_foundation::{base::TCFType, string::CFString};
     use pretty_assertions::assert_eq;
 
     use super::*;
 
-    fn create_url(path: &str) -> Url {
-        let cf_string = CFString::new(path);
-        let is_dir = path.ends_with('/');
-        let cf_url = CFURL::from_file_system_path(cf_string, kCFURLPOSIXPathStyle, is_dir);
-        Url::try_from(cf_url.as_concrete_TypeRef()).unwrap()
+    fn create_url(url: &str) -> Url {
+        Url::try_from(url).unwrap()
     }
 
     fn create_display_name(name: &str) -> DisplayName {
@@ -131,6 +212,86 @@ mod tests {
         });
     }
 
+    #[test]
+    fn should_decode_custom_url() {
+        let target = Target::from(FavoriteItem::new(
+            create_url("file:///Users/user/My%20Projects/Caf%C3%A9/"),
+            create_display_name("Café"),
+        ));
+        assert_eq!(
+            target,
+            Target::custom("Café", "/Users/user/My Projects/Café")
+        );
+    }
+
+    #[test]
+    fn should_decode_custom_url_string() {
+        assert_eq!(
+            MacOsUrl::from("file:///Users/user/100%25%20done/"),
+            MacOsUrl::Custom("/Users/user/100% done".to_string())
+        );
+    }
+
+    #[test]
+    fn should_keep_percent_signs_in_paths() {
+        assert_eq!(
+            MacOsUrl::from("/Users/user/100%20"),
+            MacOsUrl::Custom("/Users/user/100%20".to_string())
+        );
+    }
+
+    #[test]
+    fn should_convert_network_url() {
+        let target = Target::from(FavoriteItem::new(
+            create_url("smb://nas.local/share"),
+            create_display_name("NAS"),
+        ));
+        assert_eq!(target, Target::network("NAS", "smb://nas.local/share"));
+    }
+
+    #[test]
+    fn should_convert_custom_url_without_trailing_slash() {
+        assert_eq!(
+            MacOsUrl::from("file:///Users/user/Projects"),
+            MacOsUrl::Custom("/Users/user/Projects".to_string())
+        );
+    }
+
+    #[test]
+    fn should_format_urls_of_targets() -> Result<(), FavoritesError> {
+        let urls = [
+            Target::AirDrop,
+            Target::Applications,
+            Target::custom("Projects", "/Users/user/Projects"),
+            Target::custom("My Projects", "/Users/user/My Projects"),
+            Target::network("NAS", "smb://nas.local/share"),
+        ]
+        .iter()
+        .map(|target| MacOsUrl::try_from(target).map(|url| url.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(
+            urls,
+            vec![
+                "nwnode://domain-AirDrop",
+                "file:///Applications/",
+                "file:///Users/user/Projects/",
+                "file:///Users/user/My%20Projects/",
+                "smb://nas.local/share",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_format_unresolved_target() {
+        let target = Target::unresolved("Gone", crate::finder::ResolutionFailure::NotFound);
+        assert_eq!(
+            MacOsUrl::try_from(&target),
+            Err(FavoritesError::InvalidUrl("Gone".to_string()))
+        );
+    }
+
     #[test]
     fn should_format_favorite_item() {
         let item = FavoriteItem::new(
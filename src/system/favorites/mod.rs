@@ -1,33 +1,104 @@
 mod display_name;
 mod errors;
 mod handle;
+mod inserted_item;
 mod item;
+mod property;
+mod resolution;
+mod resolution_error;
 mod snapshot;
 mod snapshot_item;
+mod status;
 mod url;
 
-use core_foundation::base::kCFAllocatorDefault;
-use core_services::{LSSharedFileListResolutionFlags, kLSSharedFileListFavoriteItems};
+use std::cell::RefCell;
+
+use core_foundation::{
+    base::{CFTypeRef, TCFType, kCFAllocatorDefault},
+    boolean::CFBoolean,
+    error::CFErrorRef,
+    string::{CFString, CFStringRef},
+};
+use core_services::{
+    LSSharedFileListItemRef, kLSSharedFileListFavoriteItems, kLSSharedFileListItemBeforeFirst,
+    kLSSharedFileListItemHidden, kLSSharedFileListItemLast,
+};
 pub use display_name::DisplayName;
 pub use errors::FavoritesError;
 pub use handle::FavoritesHandle;
-pub use item::FavoriteItem;
+pub use inserted_item::InsertedItem;
+pub use item::{FavoriteItem, MacOsUrl};
+pub use property::Property;
+pub use resolution::ResolutionFlags;
+pub use resolution_error::ResolutionError;
 pub use snapshot::Snapshot;
 pub use snapshot_item::SnapshotItem;
+pub use status::Status;
 pub use url::Url;
 
 use crate::{
-    finder::{Result, SidebarItem, Target, favorites::FavoritesApi},
+    finder::{
+        FinderError, ItemId, Ownership, Position, PropertyValue, ResolutionFailure,
+        ResolutionOptions, Result, Seed, SidebarItem, Target, Visibility, favorites::FavoritesApi,
+    },
     system::api::MacOsApi,
 };
 
+/// Custom item property favkit sets on the items it adds.
+pub const MANAGED_PROPERTY: &str = "nl.happygopher.favkit.managed";
+
 pub struct Favorites {
     api: Box<dyn MacOsApi>,
+    resolution: ResolutionFlags,
+    /// The favorites list, created on first use and released when `Favorites` is dropped.
+    list: RefCell<Option<FavoritesHandle>>,
+    /// Items of the latest snapshot while a batch runs, looked up instead of copying a
+    /// snapshot for every change.
+    session: RefCell<Option<Session>>,
+}
+
+/// Items looked up during a batch, with the snapshot and inserted items that keep their
+/// references valid until the batch ends.
+#[derive(Default)]
+struct Session {
+    /// Owns the array, which retains the items listed from it.
+    _snapshot: Option<Snapshot>,
+    inserted: Vec<InsertedItem>,
+    items: Vec<(ItemId, SnapshotItem)>,
+}
+
+/// Ends the batch's session when dropped, also when the batch panics.
+struct SessionGuard<'a>(&'a RefCell<Option<Session>>);
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        self.0.replace(None);
+    }
 }
 
 impl Favorites {
     pub fn new(api: impl MacOsApi + 'static) -> Self {
-        Self { api: Box::new(api) }
+        Self {
+            api: Box::new(api),
+            resolution: ResolutionFlags::default(),
+            list: RefCell::new(None),
+            session: RefCell::new(None),
+        }
+    }
+
+    pub fn with_resolution(mut self, options: ResolutionOptions) -> Self {
+        self.resolution = ResolutionFlags::from(options);
+        self
+    }
+
+    /// The favorites list, reused for every call so a long-lived `Favorites` creates only one.
+    unsafe fn list(&self) -> errors::Result<FavoritesHandle> {
+        if let Some(list) = self.list.borrow().as_ref() {
+            return Ok(list.clone());
+        }
+        let list = unsafe { self.list_create() }?;
+        self.list.replace(Some(list.clone()));
+        Ok(list)
     }
 
     unsafe fn list_create(&self) -> errors::Result<FavoritesHandle> {
@@ -41,13 +112,20 @@ impl Favorites {
         FavoritesHandle::try_from(ptr)
     }
 
-    unsafe fn copy_snapshot(&self, list: FavoritesHandle) -> errors::Result<Snapshot> {
+    unsafe fn copy_snapshot(&self, list: &FavoritesHandle) -> errors::Result<Snapshot> {
+        unsafe { self.copy_snapshot_with_seed(list) }.map(|(snapshot, _)| snapshot)
+    }
+
+    unsafe fn copy_snapshot_with_seed(
+        &self,
+        list: &FavoritesHandle,
+    ) -> errors::Result<(Snapshot, Seed)> {
         let mut seed: u32 = 0;
         let array_ref = unsafe {
             self.api
                 .ls_shared_file_list_copy_snapshot(list.into(), &mut seed)
         };
-        Snapshot::try_from(array_ref)
+        Snapshot::try_from(array_ref).map(|snapshot| (snapshot, Seed::from(seed)))
     }
 
     unsafe fn copy_display_name(&self, item: &SnapshotItem) -> errors::Result<DisplayName> {
@@ -58,35 +136,286 @@ impl Favorites {
         DisplayName::try_from(name_ref)
     }
 
-    unsafe fn copy_resolved_url(&self, item: &SnapshotItem) -> errors::Result<Url> {
+    /// Resolves the item's URL, or explains why Core Services could not.
+    unsafe fn copy_resolved_url(
+        &self,
+        item: &SnapshotItem,
+    ) -> errors::Result<std::result::Result<Url, ResolutionFailure>> {
+        let mut error: CFErrorRef = std::ptr::null_mut();
         let url_ref = unsafe {
             self.api.ls_shared_file_list_item_copy_resolved_url(
                 item.into(),
-                LSSharedFileListResolutionFlags::default(),
+                self.resolution.into(),
+                &mut error,
+            )
+        };
+
+        match Url::try_from(url_ref) {
+            Ok(url) => Ok(Ok(url)),
+            Err(_) => {
+                ResolutionError::try_from(error).map(|error| Err(ResolutionFailure::from(&error)))
+            }
+        }
+    }
+
+    unsafe fn item_id(&self, item: &SnapshotItem) -> ItemId {
+        let id = unsafe { self.api.ls_shared_file_list_item_get_id(item.into()) };
+        ItemId::from(id)
+    }
+
+    unsafe fn copy_property(&self, item: &SnapshotItem, name: CFStringRef) -> Option<Property> {
+        let property_ref = unsafe {
+            self.api
+                .ls_shared_file_list_item_copy_property(item.into(), name)
+        };
+        Property::try_from(property_ref).ok()
+    }
+
+    unsafe fn set_property(
+        &self,
+        item: &SnapshotItem,
+        name: CFStringRef,
+        value: CFTypeRef,
+    ) -> errors::Result<Status> {
+        let status = unsafe {
+            self.api
+                .ls_shared_file_list_item_set_property(item.into(), name, value)
+        };
+        Status::try_from(status)
+    }
+
+    unsafe fn visibility(&self, item: &SnapshotItem) -> Visibility {
+        let hidden = unsafe { self.copy_property(item, kLSSharedFileListItemHidden) }
+            .map(|property| PropertyValue::from(&property) == PropertyValue::Bool(true))
+            .unwrap_or_default();
+        Visibility::from(hidden)
+    }
+
+    unsafe fn ownership(&self, item: &SnapshotItem) -> Ownership {
+        let name = CFString::new(MANAGED_PROPERTY);
+        let managed = unsafe { self.copy_property(item, name.as_concrete_TypeRef()) }
+            .map(|property| PropertyValue::from(&property) == PropertyValue::Bool(true))
+            .unwrap_or_default();
+        Ownership::from(managed)
+    }
+
+    unsafe fn remove(&self, list: &FavoritesHandle, item: &SnapshotItem) -> errors::Result<Status> {
+        let status = unsafe {
+            self.api
+                .ls_shared_file_list_item_remove(list.into(), item.into())
+        };
+        Status::try_from(status)
+    }
+
+    unsafe fn insert_after(
+        &self,
+        list: &FavoritesHandle,
+        after: LSSharedFileListItemRef,
+        name: &CFString,
+        url: &Url,
+    ) -> errors::Result<InsertedItem> {
+        let item_ref = unsafe {
+            self.api.ls_shared_file_list_insert_item_url(
+                list.into(),
+                after,
+                name.as_concrete_TypeRef(),
                 std::ptr::null_mut(),
+                url.into(),
+                std::ptr::null(),
+                std::ptr::null(),
             )
         };
-        Url::try_from(url_ref)
+        InsertedItem::try_from(item_ref)
+    }
+
+    unsafe fn move_after(
+        &self,
+        list: &FavoritesHandle,
+        item: &SnapshotItem,
+        after: LSSharedFileListItemRef,
+    ) -> errors::Result<Status> {
+        let status = unsafe {
+            self.api
+                .ls_shared_file_list_item_move(list.into(), item.into(), after)
+        };
+        Status::try_from(status)
+    }
+
+    /// Core Services reference of the item a [`Position`] points after, valid until the
+    /// session ends.
+    unsafe fn anchor(
+        &self,
+        list: &FavoritesHandle,
+        position: Position,
+    ) -> Result<LSSharedFileListItemRef> {
+        match position {
+            Position::First => Ok(unsafe { kLSSharedFileListItemBeforeFirst }),
+            Position::Last => Ok(unsafe { kLSSharedFileListItemLast }),
+            Position::After(id) => unsafe { self.find_item(list, id) }.map(|item| (&item).into()),
+        }
+    }
+
+    /// Finds the item in the session's snapshot, copying a new one when it is not there.
+    ///
+    /// Must be called within [`FavoritesApi::batch`]; the item is valid until it ends.
+    unsafe fn find_item(&self, list: &FavoritesHandle, id: ItemId) -> Result<SnapshotItem> {
+        if let Some(item) = self.session_item(id) {
+            return Ok(item);
+        }
+        let snapshot = unsafe { self.copy_snapshot(list) }?;
+        unsafe { self.remember(snapshot) };
+        self.session_item(id).ok_or(FinderError::ItemNotFound(id))
+    }
+
+    fn session_item(&self, id: ItemId) -> Option<SnapshotItem> {
+        let session = self.session.borrow();
+        let (_, item) = session
+            .as_ref()?
+            .items
+            .iter()
+            .find(|(item_id, _)| *item_id == id)?;
+        Some(item.clone())
+    }
+
+    /// Keeps the snapshot and its items for the rest of the session.
+    unsafe fn remember(&self, snapshot: Snapshot) {
+        let items = snapshot
+            .items()
+            .map(|item| (unsafe { self.item_id(&item) }, item))
+            .collect();
+        if let Some(session) = self.session.borrow_mut().as_mut() {
+            *session = Session {
+                _snapshot: Some(snapshot),
+                inserted: Vec::new(),
+                items,
+            };
+        }
+    }
+
+    /// Updates the session's items after a change.
+    fn update_session(&self, update: impl FnOnce(&mut Session)) {
+        if let Some(session) = self.session.borrow_mut().as_mut() {
+            update(session);
+        }
     }
 
     unsafe fn convert_item(&self, item: SnapshotItem) -> Result<SidebarItem> {
         let url = unsafe { self.copy_resolved_url(&item) }?;
         let name = unsafe { self.copy_display_name(&item) }?;
-        let target = Target::from(FavoriteItem::new(url, name));
-        Ok(SidebarItem::new(target))
+        let target = match url {
+            Ok(url) => Target::from(FavoriteItem::new(url, name)),
+            Err(failure) => Target::unresolved(name.to_string(), failure),
+        };
+        let id = unsafe { self.item_id(&item) };
+        let visibility = unsafe { self.visibility(&item) };
+        let ownership = unsafe { self.ownership(&item) };
+        Ok(SidebarItem::new(target)
+            .with_id(id)
+            .with_visibility(visibility)
+            .with_ownership(ownership))
     }
 }
 
 impl FavoritesApi for Favorites {
     fn list_items(&self) -> Result<Vec<SidebarItem>> {
+        self.batch(|favorites| unsafe {
+            let list = favorites.list()?;
+            let snapshot = favorites.copy_snapshot(&list)?;
+            let listed = snapshot
+                .items()
+                .map(|item| favorites.convert_item(item))
+                .collect();
+            favorites.remember(snapshot);
+            listed
+        })
+    }
+
+    fn seed(&self) -> Result<Seed> {
         unsafe {
-            let list = self.list_create()?;
-            let snapshot = self.copy_snapshot(list)?;
+            let list = self.list()?;
+            let (_, seed) = self.copy_snapshot_with_seed(&list)?;
+            Ok(seed)
+        }
+    }
+
+    fn item_property(&self, id: ItemId, name: &str) -> Result<Option<PropertyValue>> {
+        let name = CFString::new(name);
+        self.batch(|favorites| unsafe {
+            let list = favorites.list()?;
+            let item = favorites.find_item(&list, id)?;
+            let property = favorites.copy_property(&item, name.as_concrete_TypeRef());
+            Ok(property.as_ref().map(PropertyValue::from))
+        })
+    }
+
+    fn set_visibility(&self, id: ItemId, visibility: Visibility) -> Result<()> {
+        let hidden = CFBoolean::from(visibility == Visibility::Hidden);
+        self.batch(|favorites| unsafe {
+            let list = favorites.list()?;
+            let item = favorites.find_item(&list, id)?;
+            favorites.set_property(&item, kLSSharedFileListItemHidden, hidden.as_CFTypeRef())?;
+            Ok(())
+        })
+    }
+
+    fn set_ownership(&self, id: ItemId, ownership: Ownership) -> Result<()> {
+        let name = CFString::new(MANAGED_PROPERTY);
+        let managed = CFBoolean::from(ownership == Ownership::Managed);
+        self.batch(|favorites| unsafe {
+            let list = favorites.list()?;
+            let item = favorites.find_item(&list, id)?;
+            favorites.set_property(&item, name.as_concrete_TypeRef(), managed.as_CFTypeRef())?;
+            Ok(())
+        })
+    }
+
+    fn remove_item(&self, id: ItemId) -> Result<()> {
+        self.batch(|favorites| unsafe {
+            let list = favorites.list()?;
+            let item = favorites.find_item(&list, id)?;
+            favorites.remove(&list, &item)?;
+            favorites.update_session(|session| {
+                session.items.retain(|(item_id, _)| *item_id != id);
+            });
+            Ok(())
+        })
+    }
+
+    fn move_item(&self, id: ItemId, position: Position) -> Result<()> {
+        self.batch(|favorites| unsafe {
+            let list = favorites.list()?;
+            let item = favorites.find_item(&list, id)?;
+            let after = favorites.anchor(&list, position)?;
+            favorites.move_after(&list, &item, after)?;
+            Ok(())
+        })
+    }
+
+    fn insert_item(&self, target: &Target, position: Position) -> Result<ItemId> {
+        let url = Url::try_from(&MacOsUrl::try_from(target)?)?;
+        let name = CFString::new(target.label());
+        self.batch(|favorites| unsafe {
+            let list = favorites.list()?;
+            let after = favorites.anchor(&list, position)?;
+            let inserted = favorites.insert_after(&list, after, &name, &url)?;
+            let item = inserted.item();
+            let id = favorites.item_id(&item);
+            favorites.update_session(|session| {
+                session.items.push((id, item));
+                session.inserted.push(inserted);
+            });
+            Ok(id)
+        })
+    }
 
-            snapshot
-                .into_iter()
-                .map(|item| self.convert_item(item))
-                .collect()
+    /// Looks items up in one snapshot for the whole batch, copying another only for items
+    /// it does not know. Item references stay valid until the outermost batch ends.
+    fn batch<T>(&self, changes: impl FnOnce(&Self) -> T) -> T {
+        if self.session.borrow().is_some() {
+            return changes(self);
         }
+        self.session.replace(Some(Session::default()));
+        let _session = SessionGuard(&self.session);
+        changes(self)
     }
 }
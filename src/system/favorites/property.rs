@@ -0,0 +1,143 @@
+use core_foundation::{
+    base::{CFType, CFTypeRef, TCFType},
+    boolean::CFBoolean,
+    number::CFNumber,
+    string::CFString,
+};
+
+use crate::{
+    finder::PropertyValue,
+    system::{
+        core_foundation::CFRef,
+        favorites::errors::{FavoritesError, Result},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct Property(CFRef<CFType>);
+
+impl TryFrom<CFTypeRef> for Property {
+    type Error = FavoritesError;
+
+    fn try_from(type_ref: CFTypeRef) -> Result<Self> {
+        CFRef::try_from_ref(type_ref)
+            .map(Self)
+            .map_err(|_| FavoritesError::NullPropertyHandle)
+    }
+}
+
+impl From<&Property> for CFTypeRef {
+    fn from(property: &Property) -> Self {
+        property.0.as_CFTypeRef()
+    }
+}
+
+impl From<&Property> for PropertyValue {
+    fn from(property: &Property) -> Self {
+        let value: &CFType = &property.0;
+
+        value
+            .downcast::<CFBoolean>()
+            .map(|flag| PropertyValue::Bool(flag.into()))
+            .or_else(|| {
+                value
+                    .downcast::<CFNumber>()
+                    .and_then(|number| number.to_i64())
+                    .map(PropertyValue::Integer)
+            })
+            .or_else(|| {
+                value
+                    .downcast::<CFString>()
+                    .map(|string| PropertyValue::String(string.to_string()))
+            })
+            .unwrap_or(PropertyValue::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core_foundation::{array::CFArray, base::TCFType};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_fail_when_property_is_null() {
+        // Arrange
+        let property_ref: CFTypeRef = std::ptr::null();
+
+        // Act & Assert
+        assert!(Property::try_from(property_ref).is_err());
+    }
+
+    #[test]
+    fn should_unwrap_property() -> Result<()> {
+        // Arrange
+        let cf_bool = CFBoolean::true_value();
+        let property_ref = cf_bool.as_CFTypeRef();
+        let property = Property::try_from(property_ref)?;
+
+        // Act
+        let unwrapped: CFTypeRef = (&property).into();
+
+        // Assert
+        assert_eq!(unwrapped, property_ref);
+        Ok(())
+    }
+
+    #[test]
+    fn should_convert_boolean_property() -> Result<()> {
+        // Arrange
+        let cf_bool = CFBoolean::true_value();
+        let property = Property::try_from(cf_bool.as_CFTypeRef())?;
+
+        // Act
+        let value = PropertyValue::from(&property);
+
+        // Assert
+        assert_eq!(value, PropertyValue::Bool(true));
+        Ok(())
+    }
+
+    #[test]
+    fn should_convert_number_property() -> Result<()> {
+        // Arrange
+        let cf_number = CFNumber::from(42i64);
+        let property = Property::try_from(cf_number.as_CFTypeRef())?;
+
+        // Act
+        let value = PropertyValue::from(&property);
+
+        // Assert
+        assert_eq!(value, PropertyValue::Integer(42));
+        Ok(())
+    }
+
+    #[test]
+    fn should_convert_string_property() -> Result<()> {
+        // Arrange
+        let cf_string = CFString::new("managed");
+        let property = Property::try_from(cf_string.as_CFTypeRef())?;
+
+        // Act
+        let value = PropertyValue::from(&property);
+
+        // Assert
+        assert_eq!(value, PropertyValue::String("managed".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn should_mark_unknown_types_as_unsupported() -> Result<()> {
+        // Arrange
+        let cf_array = CFArray::from_CFTypes(&[CFString::new("a")]);
+        let property = Property::try_from(cf_array.as_CFTypeRef())?;
+
+        // Act
+        let value = PropertyValue::from(&property);
+
+        // Assert
+        assert_eq!(value, PropertyValue::Unsupported);
+        Ok(())
+    }
+}
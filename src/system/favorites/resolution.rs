@@ -0,0 +1,65 @@
+use core_services::{
+    LSSharedFileListResolutionFlags, kLSSharedFileListDoNotMountVolumes,
+    kLSSharedFileListNoUserInteraction,
+};
+
+use crate::finder::ResolutionOptions;
+
+/// Core Services flags equivalent to a set of [`ResolutionOptions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResolutionFlags(LSSharedFileListResolutionFlags);
+
+impl From<ResolutionOptions> for ResolutionFlags {
+    fn from(options: ResolutionOptions) -> Self {
+        [
+            (
+                options.allows_user_interaction(),
+                kLSSharedFileListNoUserInteraction,
+            ),
+            (
+                options.allows_mounting(),
+                kLSSharedFileListDoNotMountVolumes,
+            ),
+        ]
+        .into_iter()
+        .filter(|(allowed, _)| !allowed)
+        .fold(Self::default(), |Self(flags), (_, flag)| Self(flags | flag))
+    }
+}
+
+impl From<ResolutionFlags> for LSSharedFileListResolutionFlags {
+    fn from(flags: ResolutionFlags) -> Self {
+        flags.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_map_default_options_to_no_flags() {
+        let flags = ResolutionFlags::from(ResolutionOptions::default());
+        assert_eq!(LSSharedFileListResolutionFlags::from(flags), 0);
+    }
+
+    #[test]
+    fn should_map_non_interactive_options_to_both_flags() {
+        let flags = ResolutionFlags::from(ResolutionOptions::non_interactive());
+        assert_eq!(
+            LSSharedFileListResolutionFlags::from(flags),
+            kLSSharedFileListNoUserInteraction | kLSSharedFileListDoNotMountVolumes
+        );
+    }
+
+    #[test]
+    fn should_map_no_mount_option() {
+        let flags = ResolutionFlags::from(ResolutionOptions::default().without_mounting());
+        assert_eq!(
+            LSSharedFileListResolutionFlags::from(flags),
+            kLSSharedFileListDoNotMountVolumes
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use core_foundation::error::{CFError, CFErrorRef};
+
+use crate::{
+    finder::ResolutionFailure,
+    system::{
+        core_foundation::CFRef,
+        favorites::errors::{FavoritesError, Result},
+    },
+};
+
+const COCOA_DOMAIN: &str = "NSCocoaErrorDomain";
+const OS_STATUS_DOMAIN: &str = "NSOSStatusErrorDomain";
+const POSIX_DOMAIN: &str = "NSPOSIXErrorDomain";
+
+/// `NSFileNoSuchFileError`, `NSFileReadNoSuchFileError`
+const COCOA_NOT_FOUND: [i64; 2] = [4, 260];
+/// `fnfErr`, `dirNFErr`
+const OS_STATUS_NOT_FOUND: [i64; 2] = [-43, -120];
+/// `nsvErr`
+const OS_STATUS_NO_SUCH_VOLUME: i64 = -35;
+/// `ENOENT`
+const POSIX_NOT_FOUND: i64 = 2;
+
+/// Error Core Services reports alongside a null resolved URL.
+#[derive(Debug, Clone)]
+pub struct ResolutionError(CFRef<CFError>);
+
+impl TryFrom<CFErrorRef> for ResolutionError {
+    type Error = FavoritesError;
+
+    /// Takes over the error Core Services created for the caller.
+    fn try_from(error_ref: CFErrorRef) -> Result<Self> {
+        CFRef::try_from_create_rule(error_ref)
+            .map(Self)
+            .map_err(|_| FavoritesError::NullUrlHandle)
+    }
+}
+
+impl From<&ResolutionError> for ResolutionFailure {
+    fn from(error: &ResolutionError) -> Self {
+        let domain = error.0.domain().to_string();
+        let code = error.0.code() as i64;
+
+        match (domain.as_str(), code) {
+            (COCOA_DOMAIN, code) if COCOA_NOT_FOUND.contains(&code) => Self::NotFound,
+            (OS_STATUS_DOMAIN, code) if OS_STATUS_NOT_FOUND.contains(&code) => Self::NotFound,
+            (OS_STATUS_DOMAIN, OS_STATUS_NO_SUCH_VOLUME) => Self::VolumeNotMounted,
+            (POSIX_DOMAIN, POSIX_NOT_FOUND) => Self::NotFound,
+            _ => Self::Other { domain, code },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core_foundation::{
+        base::{TCFType, kCFAllocatorDefault},
+        error::CFErrorCreate,
+        string::CFString,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// A new error, owned by the caller like one reported through an out-parameter.
+    fn created_error(domain: &str, code: isize) -> CFErrorRef {
+        let domain = CFString::new(domain);
+        unsafe {
+            CFErrorCreate(
+                kCFAllocatorDefault,
+                domain.as_concrete_TypeRef(),
+                code,
+                std::ptr::null(),
+            )
+        }
+    }
+
+    fn failure(domain: &str, code: isize) -> Result<ResolutionFailure> {
+        let error = ResolutionError::try_from(created_error(domain, code))?;
+        Ok(ResolutionFailure::from(&error))
+    }
+
+    #[test]
+    fn should_fail_when_error_is_null() {
+        // Arrange
+        let error_ref: CFErrorRef = std::ptr::null_mut();
+
+        // Act & Assert
+        assert_eq!(
+            ResolutionError::try_from(error_ref).unwrap_err(),
+            FavoritesError::NullUrlHandle
+        );
+    }
+
+    #[test]
+    fn should_release_error_when_dropped() -> Result<()> {
+        // Arrange
+        let error_ref = created_error(COCOA_DOMAIN, 4);
+        let error = unsafe { CFError::wrap_under_get_rule(error_ref) };
+        let retained = error.retain_count();
+
+        // Act
+        drop(ResolutionError::try_from(error_ref)?);
+
+        // Assert
+        assert_eq!(error.retain_count(), retained - 1);
+        Ok(())
+    }
+
+    #[test]
+    fn should_classify_missing_files_as_not_found() -> Result<()> {
+        assert_eq!(failure(COCOA_DOMAIN, 4)?, ResolutionFailure::NotFound);
+        assert_eq!(failure(OS_STATUS_DOMAIN, -43)?, ResolutionFailure::NotFound);
+        assert_eq!(failure(POSIX_DOMAIN, 2)?, ResolutionFailure::NotFound);
+        Ok(())
+    }
+
+    #[test]
+    fn should_classify_missing_volume() -> Result<()> {
+        assert_eq!(
+            failure(OS_STATUS_DOMAIN, -35)?,
+            ResolutionFailure::VolumeNotMounted
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_other_errors() -> Result<()> {
+        assert_eq!(
+            failure(COCOA_DOMAIN, 256)?,
+            ResolutionFailure::Other {
+                domain: COCOA_DOMAIN.to_string(),
+                code: 256,
+            }
+        );
+        Ok(())
+    }
+}
@@ -39,12 +39,19 @@ impl Deref for Snapshot {
     }
 }
 
+impl Snapshot {
+    /// The items, valid for as long as the snapshot lives.
+    pub fn items(&self) -> SnapshotIterator {
+        SnapshotIterator::new(self)
+    }
+}
+
 impl IntoIterator for Snapshot {
     type Item = SnapshotItem;
     type IntoIter = SnapshotIterator;
 
     fn into_iter(self) -> Self::IntoIter {
-        SnapshotIterator::new(self)
+        SnapshotIterator::new(&self)
     }
 }
 
@@ -53,7 +60,7 @@ pub struct SnapshotIterator {
 }
 
 impl SnapshotIterator {
-    fn new(snapshot: Snapshot) -> Self {
+    fn new(snapshot: &Snapshot) -> Self {
         let range = CFRange::init(0, snapshot.len());
         let mut values: Vec<_> = snapshot
             .get_values(range)
@@ -0,0 +1,42 @@
+use core_foundation::base::OSStatus;
+
+use crate::system::favorites::errors::{FavoritesError, Result};
+
+/// Successful outcome of a Core Services call reporting an `OSStatus`.
+#[derive(Debug, PartialEq)]
+pub struct Status;
+
+impl Status {
+    const NO_ERR: OSStatus = 0;
+}
+
+impl TryFrom<OSStatus> for Status {
+    type Error = FavoritesError;
+
+    fn try_from(status: OSStatus) -> Result<Self> {
+        match status {
+            Self::NO_ERR => Ok(Self),
+            code => Err(FavoritesError::OperationFailed(code)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_accept_no_error_status() {
+        assert_eq!(Status::try_from(0), Ok(Status));
+    }
+
+    #[test]
+    fn should_fail_on_error_status() {
+        assert_eq!(
+            Status::try_from(-50),
+            Err(FavoritesError::OperationFailed(-50))
+        );
+    }
+}
@@ -1,13 +1,17 @@
-use std::fmt;
+use std::{fmt, path::Path};
 
 use core_foundation::{
-    base::TCFType,
-    url::{CFURL, CFURLRef},
+    base::{TCFType, kCFAllocatorDefault},
+    string::CFString,
+    url::{CFURL, CFURLCreateWithString, CFURLRef, kCFURLPOSIXPathStyle},
 };
 
 use crate::system::{
     core_foundation::CFRef,
-    favorites::errors::{FavoritesError, Result},
+    favorites::{
+        MacOsUrl,
+        errors::{FavoritesError, Result},
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -23,6 +27,59 @@ impl TryFrom<CFURLRef> for Url {
     }
 }
 
+impl TryFrom<&str> for Url {
+    type Error = FavoritesError;
+
+    /// Parses a URL string, which must already be percent-encoded.
+    fn try_from(url: &str) -> Result<Self> {
+        let string = CFString::new(url);
+        let url_ref = unsafe {
+            CFURLCreateWithString(
+                kCFAllocatorDefault,
+                string.as_concrete_TypeRef(),
+                std::ptr::null(),
+            )
+        };
+        (!url_ref.is_null())
+            .then(|| unsafe { CFURL::wrap_under_create_rule(url_ref) })
+            .and_then(|cf_url| CFRef::try_from_ref(cf_url.as_concrete_TypeRef()).ok())
+            .map(Self)
+            .ok_or_else(|| FavoritesError::InvalidUrl(url.to_string()))
+    }
+}
+
+impl TryFrom<&MacOsUrl> for Url {
+    type Error = FavoritesError;
+
+    /// Custom locations become directory URLs unless they name an existing file, so folders
+    /// that are not there yet, such as on unmounted volumes, still get one.
+    fn try_from(url: &MacOsUrl) -> Result<Self> {
+        match url {
+            MacOsUrl::Custom(path) => {
+                let is_directory = !Path::new(path).is_file();
+                let cf_url = CFURL::from_file_system_path(
+                    CFString::new(path),
+                    kCFURLPOSIXPathStyle,
+                    is_directory,
+                );
+                CFRef::try_from_ref(cf_url.as_concrete_TypeRef())
+                    .map(Self)
+                    .map_err(|_| FavoritesError::InvalidUrl(url.to_string()))
+            }
+            url => Self::try_from(url.to_string().as_str()),
+        }
+    }
+}
+
+impl Url {
+    /// File system path of a `file://` URL, percent-decoded and without a trailing slash.
+    pub fn file_system_path(&self) -> String {
+        self.0
+            .get_file_system_path(kCFURLPOSIXPathStyle)
+            .to_string()
+    }
+}
+
 impl From<&Url> for CFURLRef {
     fn from(url: &Url) -> Self {
         url.0.as_concrete_TypeRef()
@@ -82,6 +139,71 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_create_url_for_custom_location() -> Result<()> {
+        // Arrange
+        let location = MacOsUrl::Custom("/Users/user/Documents".to_string());
+
+        // Act
+        let url = Url::try_from(&location)?;
+
+        // Assert
+        assert_eq!(url.to_string(), "file:///Users/user/Documents/");
+        Ok(())
+    }
+
+    #[test]
+    fn should_create_file_url_for_existing_file() -> Result<()> {
+        // Arrange
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        let location = MacOsUrl::Custom(path.to_string());
+
+        // Act
+        let url = Url::try_from(&location)?;
+
+        // Assert
+        assert_eq!(url.to_string(), format!("file://{}", path));
+        Ok(())
+    }
+
+    #[test]
+    fn should_round_trip_custom_location_with_space() -> Result<()> {
+        // Arrange
+        let location = MacOsUrl::Custom("/Users/user/My Projects".to_string());
+
+        // Act
+        let url = Url::try_from(&location)?;
+
+        // Assert
+        assert_eq!(url.to_string(), "file:///Users/user/My%20Projects/");
+        assert_eq!(url.file_system_path(), "/Users/user/My Projects");
+        assert_eq!(MacOsUrl::from(url), location);
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_url_string() -> Result<()> {
+        // Act
+        let url = Url::try_from("file:///Users/user/My%20Projects/")?;
+
+        // Assert
+        assert_eq!(url.to_string(), "file:///Users/user/My%20Projects/");
+        Ok(())
+    }
+
+    #[test]
+    fn should_create_url_for_network_share() -> Result<()> {
+        // Arrange
+        let share = MacOsUrl::Network("smb://nas.local/share".to_string());
+
+        // Act
+        let url = Url::try_from(&share)?;
+
+        // Assert
+        assert_eq!(url.to_string(), "smb://nas.local/share");
+        Ok(())
+    }
+
     #[test]
     fn should_convert_to_string() -> Result<()> {
         // Arrange
@@ -0,0 +1,434 @@
+//! Journal stored as JSON lines in the user's state directory.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    finder::{
+        Action, Entry, History, Journal, JournalError, Ownership, ResolutionFailure, Seed,
+        SidebarItem, Target, Visibility,
+    },
+    system::favorites::MacOsUrl,
+};
+
+/// Appends one JSON object per entry to a file, creating it and its directory on first use.
+#[derive(Debug, Clone)]
+pub struct FileJournal {
+    path: PathBuf,
+    retention: Option<usize>,
+}
+
+impl FileJournal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            retention: None,
+        }
+    }
+
+    /// Keeps only the latest `entries` entries, dropping older ones as new ones are appended.
+    pub fn with_retention(mut self, entries: usize) -> Self {
+        self.retention = Some(entries);
+        self
+    }
+
+    /// `favkit/journal.jsonl` in the state directory, or the local data directory on macOS.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("favkit").join("journal.jsonl"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn io_error(&self, error: io::Error) -> JournalError {
+        JournalError::Io {
+            path: self.path.clone(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+impl Journal for FileJournal {
+    fn entries(&self) -> Result<Vec<Entry>, JournalError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(self.io_error(error)),
+        };
+
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str::<EntryRecord>(line)
+                    .map(Entry::from)
+                    .map_err(|error| JournalError::Corrupt {
+                        path: self.path.clone(),
+                        line: index + 1,
+                        reason: error.to_string(),
+                    })
+            })
+            .collect()
+    }
+
+    fn append(&self, entry: &Entry) -> Result<(), JournalError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|error| self.io_error(error))?;
+        }
+        let line =
+            serde_json::to_string(&EntryRecord::from(entry)).map_err(|error| JournalError::Io {
+                path: self.path.clone(),
+                reason: error.to_string(),
+            })?;
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|error| self.io_error(error))?;
+
+        match self.retention {
+            Some(retention) => self.truncate(retention),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FileJournal {
+    /// Rewrites the file without the entries beyond the latest `retention`.
+    ///
+    /// Older entries are kept while an undo or redo among the latest ones refers to them, and
+    /// the file is replaced in one step, so an interrupted rewrite leaves the old journal.
+    fn truncate(&self, retention: usize) -> Result<(), JournalError> {
+        let text = fs::read_to_string(&self.path).map_err(|error| self.io_error(error))?;
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        if lines.len() <= retention {
+            return Ok(());
+        }
+        let start = History::replayable_from(&self.entries()?, lines.len() - retention);
+        if start == 0 {
+            return Ok(());
+        }
+
+        let kept: String = lines[start..]
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let temporary = self.path.with_extension("jsonl.tmp");
+        fs::write(&temporary, kept)
+            .and_then(|()| fs::rename(&temporary, &self.path))
+            .map_err(|error| self.io_error(error))
+    }
+}
+
+/// Writes sidebar items as JSON in the form journal entries store them.
+pub fn write_snapshot(items: &[SidebarItem]) -> String {
+    let records: Vec<ItemRecord> = items.iter().map(ItemRecord::from).collect();
+    serde_json::to_string_pretty(&records).expect("item records serialize to JSON")
+}
+
+/// Reads sidebar items written by [`write_snapshot`].
+pub fn read_snapshot(text: &str) -> serde_json::Result<Vec<SidebarItem>> {
+    let records: Vec<ItemRecord> = serde_json::from_str(text)?;
+    Ok(records.into_iter().map(SidebarItem::from).collect())
+}
+
+/// Serialized form of an [`Entry`].
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryRecord {
+    action: ActionRecord,
+    operation: String,
+    before: Vec<ItemRecord>,
+    after: Vec<ItemRecord>,
+    seed: u32,
+    /// Seconds since the Unix epoch
+    timestamp: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ActionRecord {
+    Do,
+    Undo,
+    Redo,
+}
+
+/// Serialized form of a [`SidebarItem`]; targets are stored as the URLs Core Services uses.
+#[derive(Debug, Serialize, Deserialize)]
+struct ItemRecord {
+    id: Option<u32>,
+    label: String,
+    /// Missing for items that could not be resolved
+    url: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    managed: bool,
+}
+
+impl From<&Entry> for EntryRecord {
+    fn from(entry: &Entry) -> Self {
+        let timestamp = entry
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            action: match entry.action {
+                Action::Do => ActionRecord::Do,
+                Action::Undo => ActionRecord::Undo,
+                Action::Redo => ActionRecord::Redo,
+            },
+            operation: entry.operation.clone(),
+            before: entry.before.iter().map(ItemRecord::from).collect(),
+            after: entry.after.iter().map(ItemRecord::from).collect(),
+            seed: entry.seed.into(),
+            timestamp: timestamp.as_secs(),
+        }
+    }
+}
+
+impl From<EntryRecord> for Entry {
+    fn from(record: EntryRecord) -> Self {
+        Self {
+            action: match record.action {
+                ActionRecord::Do => Action::Do,
+                ActionRecord::Undo => Action::Undo,
+                ActionRecord::Redo => Action::Redo,
+            },
+            operation: record.operation,
+            before: record.before.into_iter().map(SidebarItem::from).collect(),
+            after: record.after.into_iter().map(SidebarItem::from).collect(),
+            seed: Seed::from(record.seed),
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(record.timestamp),
+        }
+    }
+}
+
+impl From<&SidebarItem> for ItemRecord {
+    fn from(item: &SidebarItem) -> Self {
+        Self {
+            id: item.id().map(u32::from),
+            label: item.target().label().to_string(),
+            url: MacOsUrl::try_from(item.target())
+                .ok()
+                .map(|url| url.to_string()),
+            hidden: item.is_hidden(),
+            managed: item.is_managed(),
+        }
+    }
+}
+
+impl From<ItemRecord> for SidebarItem {
+    fn from(record: ItemRecord) -> Self {
+        let target = match record.url {
+            Some(url) => MacOsUrl::from(url.as_str()).into_target(record.label),
+            None => Target::unresolved(record.label, ResolutionFailure::NotFound),
+        };
+        let item = SidebarItem::new(target)
+            .with_visibility(Visibility::from(record.hidden))
+            .with_ownership(Ownership::from(record.managed));
+        match record.id {
+            Some(id) => item.with_id(id),
+            None => item,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn journal(dir: &TempDir) -> FileJournal {
+        FileJournal::new(dir.join("journal.jsonl"))
+    }
+
+    fn entry() -> Entry {
+        Entry {
+            action: Action::Do,
+            operation: "remove Projects".to_string(),
+            before: vec![
+                SidebarItem::new(Target::AirDrop).with_id(1),
+                SidebarItem::new(Target::custom("Projects", "/Users/user/Projects"))
+                    .with_id(2)
+                    .with_visibility(Visibility::Hidden),
+                SidebarItem::new(Target::network("NAS", "smb://nas.local/share"))
+                    .with_id(3)
+                    .with_ownership(Ownership::Managed),
+            ],
+            after: vec![SidebarItem::new(Target::AirDrop).with_id(1)],
+            seed: Seed::from(7),
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn should_read_nothing_before_first_entry() {
+        let dir = TempDir::new("journal");
+        assert_eq!(journal(&dir).entries(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn should_read_back_appended_entries() {
+        // Arrange
+        let dir = TempDir::new("journal");
+        let journal = journal(&dir);
+        let undo = Entry {
+            action: Action::Undo,
+            ..entry()
+        };
+
+        // Act
+        journal.append(&entry()).unwrap();
+        journal.append(&undo).unwrap();
+
+        // Assert
+        assert_eq!(journal.entries(), Ok(vec![entry(), undo]));
+    }
+
+    #[test]
+    fn should_keep_only_latest_entries() {
+        // Arrange
+        let dir = TempDir::new("journal");
+        let journal = journal(&dir).with_retention(2);
+        let operations = ["add Work", "remove Work", "add Projects"];
+
+        // Act
+        for operation in operations {
+            journal
+                .append(&Entry {
+                    operation: operation.to_string(),
+                    ..entry()
+                })
+                .unwrap();
+        }
+
+        // Assert
+        let kept: Vec<String> = journal
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.operation)
+            .collect();
+        assert_eq!(kept, vec!["remove Work", "add Projects"]);
+    }
+
+    #[test]
+    fn should_keep_undone_entries_while_they_can_be_redone() {
+        // Arrange
+        let dir = TempDir::new("journal");
+        let journal = journal(&dir).with_retention(1);
+        let actions = [
+            (Action::Do, "add Work"),
+            (Action::Do, "add Projects"),
+            (Action::Undo, "add Projects"),
+        ];
+
+        // Act
+        for (action, operation) in actions {
+            journal
+                .append(&Entry {
+                    action,
+                    operation: operation.to_string(),
+                    ..entry()
+                })
+                .unwrap();
+        }
+
+        // Assert
+        let entries = journal.entries().unwrap();
+        let kept: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.action, entry.operation.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![(Action::Do, "add Projects"), (Action::Undo, "add Projects")]
+        );
+        let history = History::new(entries);
+        assert_eq!(
+            history.redoable().map(|entry| entry.operation.as_str()),
+            Some("add Projects")
+        );
+        assert!(!dir.join("journal.jsonl.tmp").exists());
+    }
+
+    #[test]
+    fn should_drop_whole_undo_chains() {
+        // Arrange
+        let dir = TempDir::new("journal");
+        let journal = journal(&dir).with_retention(2);
+        let actions = [
+            (Action::Do, "add Work"),
+            (Action::Undo, "add Work"),
+            (Action::Do, "add Projects"),
+            (Action::Do, "add Music"),
+        ];
+
+        // Act
+        for (action, operation) in actions {
+            journal
+                .append(&Entry {
+                    action,
+                    operation: operation.to_string(),
+                    ..entry()
+                })
+                .unwrap();
+        }
+
+        // Assert
+        let kept: Vec<String> = journal
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.operation)
+            .collect();
+        assert_eq!(kept, vec!["add Projects", "add Music"]);
+    }
+
+    #[test]
+    fn should_read_back_written_snapshot() {
+        // Arrange
+        let items = entry().before;
+
+        // Act
+        let read = read_snapshot(&write_snapshot(&items));
+
+        // Assert
+        assert_eq!(read.unwrap(), items);
+    }
+
+    #[test]
+    fn should_report_corrupt_line() {
+        // Arrange
+        let dir = TempDir::new("journal");
+        let journal = journal(&dir);
+        journal.append(&entry()).unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .unwrap();
+        writeln!(file, "not json").unwrap();
+
+        // Act
+        let result = journal.entries();
+
+        // Assert
+        assert!(matches!(result, Err(JournalError::Corrupt { line: 2, .. })));
+    }
+}
@@ -1,12 +1,13 @@
 use core_foundation::{
     array::CFArrayRef,
-    base::{CFAllocatorRef, CFTypeRef},
+    base::{CFAllocatorRef, CFTypeRef, OSStatus},
+    dictionary::CFDictionaryRef,
     error::CFErrorRef,
     string::CFStringRef,
     url::CFURLRef,
 };
 use core_services::{
-    LSSharedFileListItemRef, LSSharedFileListRef, LSSharedFileListResolutionFlags,
+    IconRef, LSSharedFileListItemRef, LSSharedFileListRef, LSSharedFileListResolutionFlags,
 };
 
 use crate::system::api::MacOsApi;
@@ -53,4 +54,65 @@ impl MacOsApi for RealMacOsApi {
     ) -> CFURLRef {
         unsafe { core_services::LSSharedFileListItemCopyResolvedURL(item, flags, error) }
     }
+
+    unsafe fn ls_shared_file_list_item_get_id(&self, item: LSSharedFileListItemRef) -> u32 {
+        unsafe { core_services::LSSharedFileListItemGetID(item) }
+    }
+
+    unsafe fn ls_shared_file_list_item_copy_property(
+        &self,
+        item: LSSharedFileListItemRef,
+        name: CFStringRef,
+    ) -> CFTypeRef {
+        unsafe { core_services::LSSharedFileListItemCopyProperty(item, name) }
+    }
+
+    unsafe fn ls_shared_file_list_item_set_property(
+        &self,
+        item: LSSharedFileListItemRef,
+        name: CFStringRef,
+        value: CFTypeRef,
+    ) -> OSStatus {
+        unsafe { core_services::LSSharedFileListItemSetProperty(item, name, value) }
+    }
+
+    unsafe fn ls_shared_file_list_item_remove(
+        &self,
+        list: LSSharedFileListRef,
+        item: LSSharedFileListItemRef,
+    ) -> OSStatus {
+        unsafe { core_services::LSSharedFileListItemRemove(list, item) }
+    }
+
+    unsafe fn ls_shared_file_list_item_move(
+        &self,
+        list: LSSharedFileListRef,
+        item: LSSharedFileListItemRef,
+        after: LSSharedFileListItemRef,
+    ) -> OSStatus {
+        unsafe { core_services::LSSharedFileListItemMove(list, item, after) }
+    }
+
+    unsafe fn ls_shared_file_list_insert_item_url(
+        &self,
+        list: LSSharedFileListRef,
+        after: LSSharedFileListItemRef,
+        name: CFStringRef,
+        icon: IconRef,
+        url: CFURLRef,
+        properties_to_set: CFDictionaryRef,
+        properties_to_clear: CFArrayRef,
+    ) -> LSSharedFileListItemRef {
+        unsafe {
+            core_services::LSSharedFileListInsertItemURL(
+                list,
+                after,
+                name,
+                icon,
+                url,
+                properties_to_set,
+                properties_to_clear,
+            )
+        }
+    }
 }
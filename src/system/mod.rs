@@ -1,7 +1,17 @@
+#[cfg(feature = "serde")]
+pub mod activation;
 pub mod api;
 pub mod core_foundation;
 pub mod favorites;
+#[cfg(feature = "serde")]
+pub mod journal;
 pub mod macos;
+pub mod probe;
 
+#[cfg(feature = "serde")]
+pub use activation::StateFile;
 pub use api::MacOsApi;
+#[cfg(feature = "serde")]
+pub use journal::FileJournal;
 pub use macos::RealMacOsApi;
+pub use probe::SystemProbe;
@@ -0,0 +1,51 @@
+use std::{fs, net::ToSocketAddrs, path::Path};
+
+use crate::finder::doctor::Probe;
+
+/// Checks favorites against the real file system and DNS.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemProbe;
+
+impl Probe for SystemProbe {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_readable(&self, path: &Path) -> bool {
+        if path.is_dir() {
+            fs::read_dir(path).is_ok()
+        } else {
+            fs::File::open(path).is_ok()
+        }
+    }
+
+    fn resolves_host(&self, host: &str) -> bool {
+        (host, 0)
+            .to_socket_addrs()
+            .is_ok_and(|mut addrs| addrs.next().is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_existing_readable_directory() {
+        let dir = std::env::temp_dir();
+        assert!(SystemProbe.exists(&dir));
+        assert!(SystemProbe.is_readable(&dir));
+    }
+
+    #[test]
+    fn should_not_find_missing_path() {
+        let path = std::env::temp_dir().join("favkit-does-not-exist");
+        assert!(!SystemProbe.exists(&path));
+        assert!(!SystemProbe.is_readable(&path));
+    }
+
+    #[test]
+    fn should_resolve_localhost() {
+        assert!(SystemProbe.resolves_host("localhost"));
+    }
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "async")]
+
+use std::time::Duration;
+
+use favkit::finder::{
+    AsyncFinder, Change, ChangeEvent, Finder, FinderError, Position, Result, SidebarItem, Target,
+};
+use pretty_assertions::assert_eq;
+
+#[allow(dead_code)]
+mod mock;
+use mock::{favorites::FavoritesBuilder, mac_os_api::MockMacOsApiBuilder};
+
+const AIRDROP_URL: &str = "nwnode://domain-AirDrop";
+const APPLICATIONS_LABEL: &str = "Applications";
+const APPLICATIONS_URL: &str = "file:///Applications/";
+
+/// How long a test waits for an event before giving up.
+const PATIENCE: Duration = Duration::from_secs(5);
+
+fn spawn_finder() -> Result<AsyncFinder> {
+    AsyncFinder::spawn(|| {
+        let favorites = FavoritesBuilder::new()
+            .add_item(None, AIRDROP_URL)
+            .add_item(Some(APPLICATIONS_LABEL), APPLICATIONS_URL)
+            .build();
+        Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build())
+    })
+}
+
+#[tokio::test]
+async fn should_list_favorites_without_blocking_the_runtime() -> Result<()> {
+    // Arrange
+    let finder = spawn_finder()?;
+
+    // Act
+    let result = finder.get_favorites_list().await?;
+
+    // Assert
+    let labels: Vec<&str> = result.iter().map(SidebarItem::label).collect();
+    assert_eq!(labels, ["AirDrop", APPLICATIONS_LABEL]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_time_out_slow_calls() -> Result<()> {
+    // Arrange
+    let timeout = Duration::from_millis(20);
+    let finder = spawn_finder()?.with_timeout(timeout);
+
+    // Act
+    let result = finder
+        .call(|_| std::thread::sleep(Duration::from_millis(200)))
+        .await;
+
+    // Assert
+    assert_eq!(result, Err(FinderError::TimedOut(timeout)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_stream_changes_made_after_watching_starts() -> Result<()> {
+    // Arrange
+    let finder = spawn_finder()?;
+    let mut changes = finder.changes(Duration::from_millis(5)).await?;
+    let target = Target::custom("Music", "/Users/user/Music");
+
+    // Act
+    let added = finder
+        .call(move |finder| finder.add(target, Position::Last))
+        .await??;
+    let event = tokio::time::timeout(PATIENCE, changes.recv())
+        .await
+        .expect("change event within the timeout")
+        .expect("stream still open")?;
+
+    // Assert
+    assert_eq!(
+        event,
+        ChangeEvent {
+            seed: finder.seed().await?,
+            changes: vec![Change::Added {
+                index: 2,
+                item: added
+            }],
+        }
+    );
+    Ok(())
+}
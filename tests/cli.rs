@@ -0,0 +1,1286 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    ffi::OsString,
+    fs,
+    io::{self, BufRead, BufReader, Cursor, Write},
+    os::unix::{fs::PermissionsExt, net::UnixStream},
+    process::ExitCode,
+    thread,
+    time::Duration,
+};
+
+use clap::Parser;
+use favkit::{
+    Finder,
+    cli::{self, Cli, Config, PROBLEMS_FOUND, Result},
+    finder::{MemoryJournal, ResolutionOptions},
+};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[allow(dead_code)]
+mod mock;
+use mock::{favorites::FavoritesBuilder, mac_os_api::MockMacOsApiBuilder, temp_dir::TempDir};
+
+mod constants {
+    pub const AIRDROP_URL: &str = "nwnode://domain-AirDrop";
+
+    pub const PROJECTS_LABEL: &str = "Projects";
+    pub const PROJECTS_URL: &str = "file:///Users/user/Projects/";
+}
+
+fn finder() -> Finder {
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build())
+}
+
+fn run_with_input(finder: &Finder, args: &[&str], input: &str) -> Result<(ExitCode, String)> {
+    let cli = Cli::parse_from(std::iter::once("favkit").chain(args.iter().copied()));
+    let mut out = Vec::new();
+    let code = cli::run(cli, finder, Cursor::new(input.to_owned()), &mut out)?;
+    Ok((code, String::from_utf8(out).expect("output is valid UTF-8")))
+}
+
+fn run_with_code(finder: &Finder, args: &[&str]) -> Result<(ExitCode, String)> {
+    run_with_input(finder, args, "")
+}
+
+fn run(finder: &Finder, args: &[&str]) -> Result<String> {
+    run_with_code(finder, args).map(|(_, output)| output)
+}
+
+#[test]
+fn should_list_favorites_by_default() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let output = run(&finder, &[])?;
+
+    // Assert
+    assert_eq!(output, "AirDrop\nProjects -> /Users/user/Projects\n");
+    Ok(())
+}
+
+#[test]
+fn should_show_hidden_state_after_hide() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    run(&finder, &["hide", "AirDrop"])?;
+    let output = run(&finder, &["list"])?;
+
+    // Assert
+    assert_eq!(
+        output,
+        "AirDrop (hidden)\nProjects -> /Users/user/Projects\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_show_item_selected_by_id() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    run(&finder, &["hide", "id:2"])?;
+
+    // Act
+    run(&finder, &["show", "id:2"])?;
+    let output = run(&finder, &["list"])?;
+
+    // Assert
+    assert_eq!(output, "AirDrop\nProjects -> /Users/user/Projects\n");
+    Ok(())
+}
+
+#[test]
+fn should_resolve_non_interactively_by_default() {
+    let cli = Cli::parse_from(["favkit", "list"]);
+    assert_eq!(
+        Config::from(&cli).resolution(),
+        ResolutionOptions::non_interactive()
+    );
+}
+
+#[test]
+fn should_accept_resolution_flags() {
+    let cli = Cli::parse_from(["favkit", "list", "--no-mount", "--no-ui"]);
+    assert_eq!(
+        Config::from(&cli).resolution(),
+        ResolutionOptions::non_interactive()
+    );
+}
+
+#[test]
+fn should_allow_ui_when_requested() {
+    let cli = Cli::parse_from(["favkit", "--allow-ui", "list"]);
+    assert_eq!(
+        Config::from(&cli).resolution(),
+        ResolutionOptions::default().without_mounting()
+    );
+}
+
+#[test]
+fn should_mount_volumes_when_requested() {
+    let cli = Cli::parse_from(["favkit", "list", "--no-mount", "--mount", "--allow-ui"]);
+    assert_eq!(
+        Config::from(&cli).resolution(),
+        ResolutionOptions::default()
+    );
+}
+
+#[test]
+fn should_fail_doctor_when_target_is_missing() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some("Gone"), "file:///favkit/does/not/exist/")
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let (code, output) = run_with_code(&finder, &["doctor"])?;
+
+    // Assert
+    assert_eq!(code, ExitCode::from(PROBLEMS_FOUND));
+    assert_eq!(
+        output,
+        "error    Gone -> /favkit/does/not/exist: target does not exist\n\
+         1 problem(s) found: 1 error(s), 0 warning(s)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_pass_doctor_for_existing_folder_with_space_in_name() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new("cli-doctor");
+    let folder = dir.join("My Projects");
+    fs::create_dir(&folder).expect("folder can be created");
+    let url = format!("file://{}/", folder.display()).replace(' ', "%20");
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some("My Projects"), &url)
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let (code, output) = run_with_code(&finder, &["doctor"])?;
+
+    // Assert
+    assert_eq!(code, ExitCode::SUCCESS);
+    assert_eq!(output, "No problems found\n");
+    Ok(())
+}
+
+#[test]
+fn should_pass_doctor_for_built_in_items() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let (code, output) = run_with_code(&finder, &["doctor", "--strict"])?;
+
+    // Assert
+    assert_eq!(code, ExitCode::SUCCESS);
+    assert_eq!(output, "No problems found\n");
+    Ok(())
+}
+
+#[test]
+fn should_list_what_prune_would_remove() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some("Gone"), "file:///favkit/does/not/exist/")
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let dry_run = run(&finder, &["prune", "--dry-run"])?;
+    let pruned = run(&finder, &["prune"])?;
+    let remaining = run(&finder, &["prune"])?;
+
+    // Assert
+    assert_eq!(dry_run, "Would remove Gone -> /favkit/does/not/exist\n");
+    assert_eq!(pruned, "Removed Gone -> /favkit/does/not/exist\n");
+    assert_eq!(remaining, "Nothing to prune\n");
+    Ok(())
+}
+
+#[test]
+fn should_not_prune_existing_folder_with_space_in_name() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new("cli-prune");
+    let folder = dir.join("My Projects");
+    fs::create_dir(&folder).expect("folder can be created");
+    let url = format!("file://{}/", folder.display()).replace(' ', "%20");
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some("My Projects"), &url)
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let pruned = run(&finder, &["prune"])?;
+
+    // Assert
+    assert_eq!(pruned, "Nothing to prune\n");
+    Ok(())
+}
+
+#[test]
+fn should_dedupe_keeping_preferred_label() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(Some("Work"), constants::PROJECTS_URL)
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let output = run(&finder, &["dedupe", "--prefer", "Work"])?;
+    let remaining = run(&finder, &["list"])?;
+
+    // Assert
+    assert_eq!(output, "Removed Projects -> /Users/user/Projects\n");
+    assert_eq!(remaining, "Work -> /Users/user/Projects\n");
+    Ok(())
+}
+
+#[test]
+fn should_print_sort_moves_on_dry_run() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let output = run(
+        &finder,
+        &["sort", "--priority", "Projects, AirDrop", "--dry-run"],
+    )?;
+    let listed = run(&finder, &["list"])?;
+
+    // Assert
+    assert_eq!(
+        output,
+        "Would move Projects -> /Users/user/Projects to the top\n"
+    );
+    assert_eq!(listed, "AirDrop\nProjects -> /Users/user/Projects\n");
+    Ok(())
+}
+
+#[test]
+fn should_list_favorites_like_mysides() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let output = run(&finder, &["mysides", "list"])?;
+
+    // Assert
+    assert_eq!(
+        output,
+        "AirDrop -> nwnode://domain-AirDrop\nProjects -> file:///Users/user/Projects/\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_add_and_remove_like_mysides() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let added = run(
+        &finder,
+        &["mysides", "add", "Work", "file:///Users/user/Work/"],
+    )?;
+    let after_add = run(&finder, &["mysides", "list"])?;
+    let removed = run(&finder, &["mysides", "remove", "Projects"])?;
+    let after_remove = run(&finder, &["mysides", "list"])?;
+
+    // Assert
+    assert_eq!(added, "Added sidebar item with name: Work\n");
+    assert_eq!(
+        after_add,
+        "AirDrop -> nwnode://domain-AirDrop\n\
+         Projects -> file:///Users/user/Projects/\n\
+         Work -> file:///Users/user/Work/\n"
+    );
+    assert_eq!(removed, "Removed sidebar item with name: Projects\n");
+    assert_eq!(
+        after_remove,
+        "AirDrop -> nwnode://domain-AirDrop\nWork -> file:///Users/user/Work/\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_print_mysides_usage_on_bad_arguments() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let (code, output) = run_with_code(&finder, &["mysides", "add", "Work"])?;
+
+    // Assert
+    assert_eq!(code, ExitCode::FAILURE);
+    assert_eq!(
+        output,
+        "Usage: mysides [list|add <name> <uri>|remove <name>]\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_act_as_mysides_when_invoked_under_that_name() {
+    // Arrange
+    let args = ["/usr/local/bin/mysides", "list"].map(OsString::from);
+
+    // Act
+    let args = cli::personality_args(args);
+
+    // Assert
+    assert_eq!(
+        args,
+        ["/usr/local/bin/mysides", "mysides", "list"].map(OsString::from)
+    );
+}
+
+#[test]
+fn should_keep_arguments_when_invoked_as_favkit() {
+    let args = ["favkit", "list"].map(OsString::from);
+    assert_eq!(cli::personality_args(args.clone()), args);
+}
+
+#[test]
+fn should_list_favorites_with_template() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let output = run(
+        &finder,
+        &["list", "--template", "{index}\\t{path|kind}\\t{id}"],
+    )?;
+
+    // Assert
+    assert_eq!(output, "1\tairdrop\t1\n2\t/Users/user/Projects\t2\n");
+    Ok(())
+}
+
+#[test]
+fn should_list_only_items_matching_filter() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let output = run(
+        &finder,
+        &[
+            "list",
+            "--where",
+            "kind=custom",
+            "--match",
+            "Proj*",
+            "--template",
+            "{index} {label}",
+        ],
+    )?;
+
+    // Assert
+    assert_eq!(output, "2 Projects\n");
+    Ok(())
+}
+
+#[test]
+fn should_remove_items_matching_filter() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let dry_run = run(&finder, &["remove", "--where", "kind=custom", "--dry-run"])?;
+    let removed = run(&finder, &["remove", "--match", "Proj*"])?;
+    let remaining = run(&finder, &["remove", "--where", "kind=custom"])?;
+
+    // Assert
+    assert_eq!(dry_run, "Would remove Projects -> /Users/user/Projects\n");
+    assert_eq!(removed, "Removed Projects -> /Users/user/Projects\n");
+    assert_eq!(remaining, "No matching items\n");
+    Ok(())
+}
+
+#[test]
+fn should_mark_and_remove_only_managed_items() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let (_, _) = run_with_input(&finder, &["batch"], "add Work /Users/user/Work\n")?;
+
+    // Act
+    let listed = run(&finder, &[])?;
+    let projects = run(&finder, &["remove", "Projects", "--managed-only"])?;
+    let removed = run(&finder, &["remove", "--managed-only", "--all"])?;
+
+    // Assert
+    assert_eq!(
+        listed,
+        "AirDrop\nProjects -> /Users/user/Projects\nWork -> /Users/user/Work (managed)\n"
+    );
+    assert_eq!(projects, "No matching items\n");
+    assert_eq!(removed, "Removed Work -> /Users/user/Work (managed)\n");
+    Ok(())
+}
+
+#[test]
+fn should_require_selector_or_filter_for_remove() {
+    // Act
+    let result = Cli::try_parse_from(["favkit", "remove"]);
+
+    // Assert
+    assert!(result.is_err());
+}
+
+#[test]
+fn should_require_all_to_remove_every_managed_item() {
+    // Act
+    let managed_only = Cli::try_parse_from(["favkit", "remove", "--managed-only"]);
+    let all = Cli::try_parse_from(["favkit", "remove", "--all"]);
+    let selected = Cli::try_parse_from(["favkit", "remove", "Projects", "--managed-only", "--all"]);
+
+    // Assert
+    assert!(managed_only.is_err());
+    assert!(all.is_err());
+    assert!(selected.is_err());
+}
+
+#[test]
+fn should_apply_batch_from_stdin() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let input = "add Work /Users/user/Work first\nrename Projects Code\n";
+
+    // Act
+    let (_, dry_run) = run_with_input(&finder, &["batch", "--dry-run"], input)?;
+    let (_, applied) = run_with_input(&finder, &["batch"], input)?;
+    let listed = run(&finder, &["list", "--template", "{label}"])?;
+
+    // Assert
+    assert_eq!(
+        dry_run,
+        "Would add Work /Users/user/Work first\nWould rename Projects Code\n"
+    );
+    assert_eq!(applied, "Applied 2 operations\n");
+    assert_eq!(listed, "Work\nAirDrop\nCode\n");
+    Ok(())
+}
+
+#[test]
+fn should_apply_json_batch() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let input = r#"[{"op": "move", "item": "Projects", "to": "first"}]"#;
+
+    // Act
+    let (_, applied) = run_with_input(&finder, &["batch"], input)?;
+    let listed = run(&finder, &["list", "--template", "{label}"])?;
+
+    // Assert
+    assert_eq!(applied, "Applied 1 operation\n");
+    assert_eq!(listed, "Projects\nAirDrop\n");
+    Ok(())
+}
+
+#[test]
+fn should_reject_invalid_batch_without_changes() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let input = "remove Projects\nremove Projects\n";
+
+    // Act
+    let result = run_with_input(&finder, &["batch"], input);
+
+    // Assert
+    assert_eq!(
+        result.map_err(|error| error.to_string()),
+        Err("operation 2 (remove Projects): no sidebar item matches 'Projects'".to_string())
+    );
+    assert_eq!(
+        run(&finder, &["list", "--template", "{label}"])?,
+        "AirDrop\nProjects\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_undo_and_redo_last_change() -> Result<()> {
+    // Arrange
+    let finder = finder().with_journal(MemoryJournal::default());
+    run(&finder, &["remove", constants::PROJECTS_LABEL])?;
+
+    // Act
+    let undone = run(&finder, &["undo"])?;
+    let restored = run(&finder, &["list", "--template", "{label}"])?;
+    let redone = run(&finder, &["redo"])?;
+    let nothing = run(&finder, &["redo"])?;
+
+    // Assert
+    assert_eq!(undone, "Undid remove Projects\n");
+    assert_eq!(restored, "AirDrop\nProjects\n");
+    assert_eq!(redone, "Redid remove Projects\n");
+    assert_eq!(nothing, "Nothing to redo\n");
+    Ok(())
+}
+
+fn serve(finder: &Finder, requests: &[&str]) -> Result<Vec<serde_json::Value>> {
+    let input = requests.join("\n");
+    let (_, output) = run_with_input(finder, &["serve", "--stdio"], &input)?;
+    Ok(output
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("every line is a JSON message"))
+        .collect())
+}
+
+#[test]
+fn should_answer_json_rpc_requests() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let messages = serve(
+        &finder,
+        &[
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "list", "params": {"where": ["kind=custom"]}}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "rename", "params": {"item": "Projects", "label": "Code"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "move", "params": {"item": "Code", "to": "first"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 4, "method": "list", "params": {"match": "C*"}}"#,
+        ],
+    )?;
+
+    // Assert
+    assert_eq!(
+        messages,
+        vec![
+            json!({"jsonrpc": "2.0", "id": 1, "result": [{
+                "id": 2, "kind": "custom", "label": "Projects", "path": "/Users/user/Projects",
+                "url": "file:///Users/user/Projects/", "hidden": false,
+            }]}),
+            json!({"jsonrpc": "2.0", "id": 2, "result": null}),
+            json!({"jsonrpc": "2.0", "id": 3, "result": null}),
+            json!({"jsonrpc": "2.0", "id": 4, "result": [{
+                "id": 3, "kind": "custom", "label": "Code", "path": "/Users/user/Projects",
+                "url": "file:///Users/user/Projects/", "hidden": false,
+            }]}),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_notify_watchers_of_changes() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let messages = serve(
+        &finder,
+        &[
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "watch"}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "remove", "params": {"item": "Projects"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "list"}"#,
+        ],
+    )?;
+
+    // Assert
+    let methods: Vec<_> = messages
+        .iter()
+        .map(|message| message.get("method").or(message.get("id")).cloned())
+        .collect();
+    assert_eq!(
+        methods,
+        vec![
+            Some(json!(1)),
+            Some(json!(2)),
+            Some(json!("changed")),
+            Some(json!(3))
+        ]
+    );
+    assert_eq!(
+        messages[2]["params"]["items"].as_array().map(Vec::len),
+        Some(1)
+    );
+    assert_eq!(messages[1]["result"]["label"], json!("Projects"));
+    Ok(())
+}
+
+#[test]
+fn should_report_json_rpc_errors() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let messages = serve(
+        &finder,
+        &[
+            "not json",
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "copy"}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "remove", "params": {}}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "remove", "params": {"item": "Gone"}}"#,
+            r#"{"jsonrpc": "2.0", "method": "remove", "params": {"item": "Gone"}}"#,
+        ],
+    )?;
+
+    // Assert
+    let codes: Vec<_> = messages
+        .iter()
+        .map(|message| message["error"]["code"].clone())
+        .collect();
+    assert_eq!(
+        codes,
+        vec![json!(-32700), json!(-32601), json!(-32602), json!(-32000)]
+    );
+    assert_eq!(
+        messages[3]["error"]["message"],
+        json!("no sidebar item matches 'Gone'")
+    );
+    Ok(())
+}
+
+#[test]
+fn should_answer_null_ids_and_refuse_batches() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let messages = serve(
+        &finder,
+        &[
+            r#"{"jsonrpc": "2.0", "id": null, "method": "unwatch"}"#,
+            r#"{"jsonrpc": "2.0", "method": "unwatch"}"#,
+            r#"[{"jsonrpc": "2.0", "id": 1, "method": "unwatch"}]"#,
+            r#"{"jsonrpc": "2.0", "id": [1], "method": "unwatch"}"#,
+        ],
+    )?;
+
+    // Assert
+    assert_eq!(
+        messages[0],
+        json!({"jsonrpc": "2.0", "id": null, "result": null})
+    );
+    assert_eq!(
+        messages[1],
+        json!({"jsonrpc": "2.0", "id": null, "error": {
+            "code": -32600, "message": "batch requests are not supported",
+        }})
+    );
+    assert_eq!(messages[2]["error"]["code"], json!(-32600));
+    assert_eq!(messages.len(), 3);
+    Ok(())
+}
+
+/// Output that refuses every write, like a closed pipe.
+struct ClosedOutput;
+
+impl Write for ClosedOutput {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+#[test]
+fn should_stop_serving_when_output_fails_while_input_stays_open() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let (input, _writer) = io::pipe()?;
+    let cli = Cli::parse_from(["favkit", "serve", "--stdio", "--poll", "1"]);
+
+    // Act
+    let result = cli::run(cli, &finder, BufReader::new(input), &mut ClosedOutput);
+
+    // Assert
+    assert!(matches!(result, Err(cli::CliError::Output(_))));
+    Ok(())
+}
+
+#[test]
+fn should_require_stdio_transport_for_serve() {
+    // Arrange
+    let finder = finder();
+    let mut cli = Cli::parse_from(["favkit", "serve", "--stdio"]);
+    if let Some(cli::Command::Serve { stdio, .. }) = &mut cli.command {
+        *stdio = false;
+    }
+
+    // Act
+    let parsed = Cli::try_parse_from(["favkit", "serve"]);
+    let result = cli::run(cli, &finder, io::empty(), &mut Vec::new());
+
+    // Assert
+    assert!(parsed.is_err());
+    assert!(matches!(result, Err(cli::CliError::NoTransport)));
+}
+
+/// Runs the daemon on a socket in the temporary directory while a client sends the requests
+/// followed by `shutdown`, and returns everything the client received.
+fn daemon(finder: &Finder, requests: &[&str]) -> Result<Vec<serde_json::Value>> {
+    let dir = TempDir::new("cli-daemon");
+    let socket = dir.join("favkit.sock");
+
+    thread::scope(|scope| {
+        let client = scope.spawn(|| {
+            let mut stream = (0..100)
+                .find_map(|_| {
+                    thread::sleep(Duration::from_millis(20));
+                    UnixStream::connect(&socket).ok()
+                })
+                .expect("daemon is listening");
+            let mode = fs::metadata(&socket)
+                .expect("socket exists")
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600, "socket is private to the user");
+            let reader = BufReader::new(stream.try_clone().expect("socket can be cloned"));
+            for request in requests {
+                writeln!(stream, "{}", request).expect("request is sent");
+            }
+            writeln!(
+                stream,
+                r#"{{"jsonrpc": "2.0", "id": 0, "method": "shutdown"}}"#
+            )
+            .expect("request is sent");
+
+            reader
+                .lines()
+                .map(|line| serde_json::from_str(&line.expect("line is read")).expect("JSON"))
+                .collect::<Vec<serde_json::Value>>()
+        });
+
+        let socket = socket.to_str().expect("socket path is valid UTF-8");
+        let output = run(finder, &["daemon", "--socket", socket])?;
+        assert_eq!(output, format!("Listening on {}\n", socket));
+        Ok(client.join().expect("client finishes"))
+    })
+}
+
+#[test]
+fn should_not_replace_a_file_at_the_socket_path() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let dir = TempDir::new("cli-daemon-file");
+    let socket = dir.join("favkit.sock");
+    fs::write(&socket, "not a socket").expect("file is written");
+
+    // Act
+    let result = run(
+        &finder,
+        &["daemon", "--socket", socket.to_str().expect("UTF-8 path")],
+    );
+
+    // Assert
+    assert!(matches!(result, Err(cli::CliError::Listen { .. })));
+    assert_eq!(
+        fs::read_to_string(&socket).ok().as_deref(),
+        Some("not a socket")
+    );
+    Ok(())
+}
+
+#[test]
+fn should_serve_cached_sidebar_and_push_changes_over_socket() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let messages = daemon(
+        &finder,
+        &[
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "subscribe"}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "list", "params": {"where": ["kind=custom"]}}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "move", "params": {"item": "Projects", "to": "first"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 4, "method": "list", "params": {"match": "Air*"}}"#,
+        ],
+    )?;
+
+    // Assert
+    let projects = json!({
+        "id": 2, "kind": "custom", "label": "Projects", "path": "/Users/user/Projects",
+        "url": "file:///Users/user/Projects/", "hidden": false,
+    });
+    let airdrop = json!({
+        "id": 1, "kind": "airdrop", "label": "AirDrop", "url": "nwnode://domain-AirDrop",
+        "hidden": false,
+    });
+    assert_eq!(
+        messages,
+        vec![
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"seed": 1, "items": [airdrop, projects]}}),
+            json!({"jsonrpc": "2.0", "id": 2, "result": [projects]}),
+            json!({"jsonrpc": "2.0", "id": 3, "result": null}),
+            json!({"jsonrpc": "2.0", "method": "changed", "params": {
+                "seed": 2, "changes": [{"change": "moved", "index": 0, "item": projects}],
+            }}),
+            json!({"jsonrpc": "2.0", "id": 4, "result": [airdrop]}),
+            json!({"jsonrpc": "2.0", "id": 0, "result": null}),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_apply_manifest_from_stdin() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let manifest = r#"
+        [[item]]
+        label = "Projects"
+        location = "/Users/user/Projects"
+
+        [[item]]
+        label = "AirDrop"
+        location = "nwnode://domain-AirDrop"
+        hidden = true
+    "#;
+
+    // Act
+    let (_, planned) = run_with_input(
+        &finder,
+        &["apply", "--manifest", "-", "--dry-run"],
+        manifest,
+    )?;
+    let (_, applied) = run_with_input(&finder, &["apply", "--manifest", "-"], manifest)?;
+    let (_, again) = run_with_input(&finder, &["apply", "--manifest", "-"], manifest)?;
+
+    // Assert
+    assert_eq!(planned, "Would move Projects to 1\nWould hide AirDrop\n");
+    assert_eq!(applied, "Applied 2 changes\n");
+    assert_eq!(again, "Nothing to do\n");
+    assert_eq!(
+        run(&finder, &[])?,
+        "Projects -> /Users/user/Projects\nAirDrop (hidden)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_activate_manifest_and_remove_only_owned_items() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let dir = TempDir::new("cli-activate");
+    let state = dir.join("activation.json");
+    let state = state.to_str().expect("temporary path is UTF-8");
+    let activate = ["activate", "--manifest", "-", "--state", state];
+    let manifest = r#"
+        [[item]]
+        label = "Projects"
+        location = "/Users/user/Projects"
+
+        [[item]]
+        label = "Music"
+        location = "/Users/user/Music"
+    "#;
+    let reduced = r#"
+        [[item]]
+        label = "Projects"
+        location = "/Users/user/Projects"
+    "#;
+
+    // Act
+    let (_, added) = run_with_input(&finder, &activate, manifest)?;
+    let (_, again) = run_with_input(&finder, &activate, manifest)?;
+    let (_, removed) = run_with_input(&finder, &activate, reduced)?;
+    let (_, emptied) = run_with_input(&finder, &activate, "")?;
+
+    // Assert
+    assert_eq!(added, "add Music at 3\n");
+    assert_eq!(again, "");
+    assert_eq!(removed, "remove Music\n");
+    assert_eq!(emptied, "");
+    assert_eq!(
+        run(&finder, &[])?,
+        "AirDrop\nProjects -> /Users/user/Projects\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_own_items_added_before_activation_failed() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .build();
+    let finder = Finder::new(
+        MockMacOsApiBuilder::new()
+            .with_favorites(favorites)
+            .rejecting_url("file:///Users/user/Music/")
+            .build(),
+    );
+    let dir = TempDir::new("cli-activate-failed");
+    let state = dir.join("activation.json");
+    let state = state.to_str().expect("temporary path is UTF-8");
+    let activate = ["activate", "--manifest", "-", "--state", state];
+    let manifest = r#"
+        [[item]]
+        label = "Projects"
+        location = "/Users/user/Projects"
+
+        [[item]]
+        label = "Music"
+        location = "/Users/user/Music"
+    "#;
+
+    // Act
+    let failed = run_with_input(&finder, &activate, manifest);
+    let (_, emptied) = run_with_input(&finder, &activate, "")?;
+
+    // Assert
+    assert!(failed.is_err());
+    assert_eq!(emptied, "remove Projects\n");
+    assert_eq!(run(&finder, &[])?, "AirDrop\n");
+    Ok(())
+}
+
+#[test]
+fn should_apply_manifest_to_managed_items_only() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let manifest = r#"
+        [[item]]
+        label = "Music"
+        location = "/Users/user/Music"
+    "#;
+
+    // Act
+    let (_, planned) = run_with_input(
+        &finder,
+        &["apply", "--manifest", "-", "--managed-only", "--dry-run"],
+        manifest,
+    )?;
+    let (_, applied) = run_with_input(
+        &finder,
+        &["apply", "--manifest", "-", "--managed-only"],
+        manifest,
+    )?;
+    let (_, emptied) =
+        run_with_input(&finder, &["apply", "--manifest", "-", "--managed-only"], "")?;
+
+    // Assert
+    assert_eq!(planned, "Would add Music at 1\n");
+    assert_eq!(applied, "Applied 1 change\n");
+    assert_eq!(emptied, "Applied 1 change\n");
+    assert_eq!(
+        run(&finder, &[])?,
+        "AirDrop\nProjects -> /Users/user/Projects\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_save_list_and_use_profiles() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let dir = TempDir::new("cli-profiles");
+    let profiles = dir.join("profiles");
+    let dir = profiles.to_str().expect("temporary path is UTF-8");
+    run(&finder, &["profile", "--dir", dir, "save", "client-a"])?;
+    run(&finder, &["remove", "Projects"])?;
+    run(&finder, &["profile", "--dir", dir, "save", "client-b"])?;
+
+    // Act
+    let listed = run(&finder, &["profile", "--dir", dir, "list"])?;
+    let planned = run(
+        &finder,
+        &["profile", "--dir", dir, "use", "client-a", "--dry-run"],
+    )?;
+    let used = run(&finder, &["profile", "--dir", dir, "use", "client-a"])?;
+    let switched = run(&finder, &["profile", "--dir", dir, "list"])?;
+
+    // Assert
+    assert_eq!(listed, "client-a\nclient-b (current)\n");
+    assert_eq!(planned, "Would add Projects at 2\n");
+    assert_eq!(used, "Applied 1 change\n");
+    assert_eq!(switched, "client-a (current)\nclient-b\n");
+    assert_eq!(
+        run(&finder, &[])?,
+        "AirDrop\nProjects -> /Users/user/Projects (managed)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_list_the_sidebar_once_for_all_profiles() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new("cli-profiles-listing");
+    let profiles = dir.join("profiles");
+    let dir = profiles.to_str().expect("temporary path is UTF-8");
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let snapshots = mock_api.snapshots();
+    let finder = Finder::new(mock_api);
+    for name in ["client-a", "client-b", "client-c"] {
+        run(&finder, &["profile", "--dir", dir, "save", name])?;
+    }
+    snapshots.set(0);
+
+    // Act
+    let listed = run(&finder, &["profile", "--dir", dir, "list"])?;
+
+    // Assert
+    assert_eq!(
+        listed,
+        "client-a (current)\nclient-b (current)\nclient-c (current)\n"
+    );
+    assert_eq!(snapshots.get(), 1);
+    Ok(())
+}
+
+#[test]
+fn should_not_save_or_use_profiles_over_unresolved_items() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new("cli-profiles-unresolved");
+    let profiles = dir.join("profiles");
+    let dir = profiles.to_str().expect("temporary path is UTF-8");
+    run(&finder(), &["profile", "--dir", dir, "save", "client-a"])?;
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_unresolved_item(Some("NAS"), "NSCocoaErrorDomain", 4)
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let saved = run(&finder, &["profile", "--dir", dir, "save", "client-b"]);
+    let used = run(&finder, &["profile", "--dir", dir, "use", "client-a"]);
+
+    // Assert
+    for result in [saved, used] {
+        assert!(matches!(
+            result,
+            Err(cli::CliError::Profile(cli::ProfileError::Unresolved(label))) if label == "NAS"
+        ));
+    }
+    assert_eq!(
+        run(&finder, &["list", "--template", "{label}"])?,
+        "AirDrop\nNAS\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_take_defaults_from_config_file() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let config = Config::parse(
+        "[output]\ntemplate = \"{label}\"\n\n[manifest]\npath = \"-\"\n",
+        "config.toml".as_ref(),
+    )?;
+    let run_with_config = |args: &[&str], input: &str| -> Result<String> {
+        let cli = Cli::parse_from(std::iter::once("favkit").chain(args.iter().copied()));
+        let mut out = Vec::new();
+        cli::run_with_config(
+            cli,
+            &config,
+            &finder,
+            Cursor::new(input.to_owned()),
+            &mut out,
+        )?;
+        Ok(String::from_utf8(out).expect("output is valid UTF-8"))
+    };
+
+    // Act
+    let listed = run_with_config(&["list"], "")?;
+    let overridden = run_with_config(&["list", "--template", "{kind}"], "")?;
+    let applied = run_with_config(&["apply", "--dry-run"], &run(&finder, &["export"])?)?;
+
+    // Assert
+    assert_eq!(listed, "AirDrop\nProjects\n");
+    assert_eq!(overridden, "airdrop\ncustom\n");
+    assert_eq!(applied, "Nothing to do\n");
+    Ok(())
+}
+
+#[test]
+fn should_validate_manifest_with_located_problems() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let manifest = "[[item]]\nlabel = \"Projects\"\nlocation = \"Projects\"\npinned = true\n";
+
+    // Act
+    let (code, output) = run_with_input(&finder, &["validate", "-"], manifest)?;
+    let (valid, exported) =
+        run_with_input(&finder, &["validate", "-"], &run(&finder, &["export"])?)?;
+    let schema = run(&finder, &["validate", "--schema"])?;
+
+    // Assert
+    assert_eq!(code, ExitCode::from(PROBLEMS_FOUND));
+    assert_eq!(
+        output,
+        "-:4:1: unknown key `pinned`\n  help: expected one of: label, location, hidden, when\n\
+         -:3:12: relative path `Projects`\n  help: use an absolute path or `~/Projects`\n\
+         2 problems found\n"
+    );
+    assert_eq!(
+        (valid, exported.as_str()),
+        (ExitCode::SUCCESS, "- is valid\n")
+    );
+    let schema: serde_json::Value = serde_json::from_str(&schema).expect("schema is JSON");
+    assert_eq!(schema["title"], json!("favkit manifest"));
+    Ok(())
+}
+
+#[test]
+fn should_export_manifest_that_applies_cleanly() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let manifest = run(&finder, &["export"])?;
+    let (_, applied) = run_with_input(&finder, &["apply", "--manifest", "-"], &manifest)?;
+
+    // Assert
+    assert!(manifest.contains("location = \"/Users/user/Projects\""));
+    assert_eq!(applied, "Nothing to do\n");
+    Ok(())
+}
+
+#[test]
+fn should_export_mobileconfig_profile() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let profile = run(
+        &finder,
+        &[
+            "export",
+            "--as",
+            "mobileconfig",
+            "--identifier",
+            "com.example.sidebar",
+        ],
+    )?;
+
+    // Assert
+    let profile = plist::Value::from_reader_xml(profile.as_bytes()).expect("profile is a plist");
+    let profile = profile.as_dictionary().expect("profile is a dictionary");
+    assert_eq!(
+        profile["PayloadIdentifier"].as_string(),
+        Some("com.example.sidebar")
+    );
+    let payload = profile["PayloadContent"].as_array().expect("payloads")[0]
+        .as_dictionary()
+        .expect("payload is a dictionary");
+    assert_eq!(
+        payload["PayloadType"].as_string(),
+        Some("com.apple.ManagedClient.preferences")
+    );
+    Ok(())
+}
+
+#[test]
+fn should_refuse_mobileconfig_identifier_that_is_not_reverse_dns() {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let result = run(
+        &finder,
+        &[
+            "export",
+            "--as",
+            "mobileconfig",
+            "--identifier",
+            "x\"; rm -rf ~; \"",
+        ],
+    );
+
+    // Assert
+    assert!(matches!(result, Err(cli::CliError::InvalidIdentifier(_))));
+}
+
+#[test]
+fn should_report_invalid_manifest() {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let result = run_with_input(
+        &finder,
+        &["apply", "--manifest", "-"],
+        "[[item]]\nlabel = 1\n",
+    );
+
+    // Assert
+    assert!(matches!(
+        result,
+        Err(cli::CliError::Manifest(cli::ManifestError::Invalid { .. }))
+    ));
+}
+
+#[test]
+fn should_converge_item_as_module() -> Result<()> {
+    // Arrange
+    let finder = finder();
+    let present = r#"{"path": "/Users/user/Downloads", "position": "after:AirDrop"}"#;
+    let checked = r#"{"path": "/Users/user/Downloads", "position": "after:AirDrop",
+        "_ansible_check_mode": true, "_ansible_diff": true, "_ansible_verbosity": 0}"#;
+
+    // Act
+    let (_, check) = run_with_input(&finder, &["module"], checked)?;
+    let (_, first) = run_with_input(&finder, &["module"], present)?;
+    let (_, second) = run_with_input(&finder, &["module"], present)?;
+    let (_, absent) = run_with_input(
+        &finder,
+        &["module"],
+        r#"{"path": "/Users/user/tmp/../Projects/", "state": "absent"}"#,
+    )?;
+
+    // Assert
+    let check: serde_json::Value = serde_json::from_str(&check).expect("result is JSON");
+    assert_eq!(
+        check,
+        json!({"changed": true, "diff": {
+            "before": "AirDrop\nProjects -> /Users/user/Projects\n",
+            "after": "AirDrop\nDownloads -> /Users/user/Downloads (managed)\nProjects -> /Users/user/Projects\n",
+        }})
+    );
+    assert_eq!(first, "{\"changed\":true}\n");
+    assert_eq!(second, "{\"changed\":false}\n");
+    assert_eq!(absent, "{\"changed\":true}\n");
+    assert_eq!(
+        run(&finder, &[])?,
+        "AirDrop\nDownloads -> /Users/user/Downloads (managed)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn should_report_module_failure_as_json() -> Result<()> {
+    // Arrange
+    let finder = finder();
+
+    // Act
+    let (code, output) = run_with_input(
+        &finder,
+        &["module"],
+        r#"{"path": "/Users/user/Downloads", "position": "after:Desktop"}"#,
+    )?;
+
+    // Assert
+    assert_eq!(code, ExitCode::FAILURE);
+    let output: serde_json::Value = serde_json::from_str(&output).expect("result is JSON");
+    assert_eq!(
+        output,
+        json!({"changed": false, "failed": true, "msg": "no sidebar item matches 'Desktop'"})
+    );
+    Ok(())
+}
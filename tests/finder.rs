@@ -1,11 +1,20 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use core_foundation::{base::TCFType, string::CFString};
+use core_services::{kLSSharedFileListDoNotMountVolumes, kLSSharedFileListNoUserInteraction};
 use favkit::{
-    finder::{Finder, FinderError, Result, SidebarItem, Target},
-    system::favorites::FavoritesError,
+    finder::{
+        BatchError, Change, Condition, DedupePolicy, Destination, Filter, Finder, FinderError,
+        ItemId, Kind, Manifest, MemoryJournal, Operation, Ownership, Position, PropertyValue,
+        PruneOptions, ResolutionFailure, ResolutionOptions, Result, Scope, Selector, SharedFinder,
+        SidebarItem, SortKey, SortOrder, Target, Visibility, favorites::FavoritesApi,
+    },
+    system::favorites::{Favorites, FavoritesError, MANAGED_PROPERTY},
 };
 use pretty_assertions::assert_eq;
 
 mod mock;
-use mock::{favorites::FavoritesBuilder, mac_os_api::MockMacOsApiBuilder};
+use mock::{favorites::FavoritesBuilder, mac_os_api::MockMacOsApiBuilder, temp_dir::TempDir};
 
 mod constants {
     // AirDrop
@@ -23,6 +32,21 @@ mod constants {
     pub const PROJECTS_LABEL: &str = "Projects";
     pub const PROJECTS_PATH: &str = "/Users/user/Projects";
     pub const PROJECTS_URL: &str = "file:///Users/user/Projects/";
+
+    // Network share
+    pub const NAS_LABEL: &str = "NAS";
+    pub const NAS_URL: &str = "smb://nas.local/share";
+
+    // Locations that no longer exist
+    pub const GONE_LABEL: &str = "Gone";
+    pub const GONE_PATH: &str = "/favkit/does/not/exist";
+    pub const GONE_URL: &str = "file:///favkit/does/not/exist/";
+    pub const ARCHIVE_LABEL: &str = "Archive";
+    pub const COCOA_ERROR_DOMAIN: &str = "NSCocoaErrorDomain";
+    pub const NO_SUCH_FILE_ERROR: isize = 4;
+
+    // Custom properties
+    pub const MANAGED_PROPERTY: &str = "com.example.favkit.managed";
 }
 
 #[test]
@@ -40,6 +64,81 @@ fn should_fail_when_list_handle_is_null() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn should_create_one_list_and_release_it_with_the_finder() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let lists = mock_api.lists();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    finder.seed()?;
+    finder.get_favorites_list()?;
+    finder.add(
+        Target::custom(constants::GONE_LABEL, constants::GONE_PATH),
+        Position::Last,
+    )?;
+    finder.seed()?;
+    drop(finder);
+
+    // Assert
+    let lists = lists.borrow();
+    assert_eq!(lists.len(), 1);
+    assert_eq!(lists[0].retain_count(), 1);
+    Ok(())
+}
+
+#[test]
+fn should_release_inserted_items() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new().build();
+    let mock_api = MockMacOsApiBuilder::new()
+        .with_favorites(favorites.clone())
+        .build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    finder.add(
+        Target::network(constants::NAS_LABEL, constants::NAS_URL),
+        Position::Last,
+    )?;
+    drop(finder);
+
+    // Assert
+    let items = favorites.items.borrow();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].handle.retain_count(), 1);
+    Ok(())
+}
+
+#[test]
+fn should_end_batch_session_when_batch_panics() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let snapshots = mock_api.snapshots();
+    let favorites = Favorites::new(mock_api);
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+        favorites.batch(|favorites| {
+            favorites.list_items().unwrap();
+            panic!("batch failed");
+        })
+    }));
+
+    // Act
+    favorites.item_property(ItemId::from(1), MANAGED_PROPERTY)?;
+
+    // Assert
+    assert!(panicked.is_err());
+    assert_eq!(snapshots.get(), 2);
+    Ok(())
+}
+
 #[test]
 fn should_fail_when_snapshot_handle_is_null() -> Result<()> {
     // Arrange
@@ -73,7 +172,7 @@ fn should_return_empty_list_when_no_favorites() -> Result<()> {
 #[test]
 fn should_handle_airdrop_item() -> Result<()> {
     // Arrange
-    let expected_result = vec![SidebarItem::new(Target::AirDrop)];
+    let expected_result = vec![SidebarItem::new(Target::AirDrop).with_id(1)];
     let favorites = FavoritesBuilder::new()
         .add_item(None, constants::AIRDROP_URL)
         .build();
@@ -91,7 +190,7 @@ fn should_handle_airdrop_item() -> Result<()> {
 #[test]
 fn should_handle_recents_item() -> Result<()> {
     // Arrange
-    let expected_result = vec![SidebarItem::new(Target::Recents)];
+    let expected_result = vec![SidebarItem::new(Target::Recents).with_id(1)];
     let favorites = FavoritesBuilder::new()
         .add_item(Some(constants::RECENTS_LABEL), constants::RECENTS_URL)
         .build();
@@ -109,7 +208,7 @@ fn should_handle_recents_item() -> Result<()> {
 #[test]
 fn should_handle_applications_item() -> Result<()> {
     // Arrange
-    let expected_result = vec![SidebarItem::new(Target::Applications)];
+    let expected_result = vec![SidebarItem::new(Target::Applications).with_id(1)];
     let favorites = FavoritesBuilder::new()
         .add_item(
             Some(constants::APPLICATIONS_LABEL),
@@ -131,12 +230,13 @@ fn should_handle_applications_item() -> Result<()> {
 fn should_handle_multiple_favorites() -> Result<()> {
     // Arrange
     let expected_result = vec![
-        SidebarItem::new(Target::AirDrop),
-        SidebarItem::new(Target::Applications),
+        SidebarItem::new(Target::AirDrop).with_id(1),
+        SidebarItem::new(Target::Applications).with_id(2),
         SidebarItem::new(Target::Custom {
             label: constants::PROJECTS_LABEL.to_string(),
             path: constants::PROJECTS_PATH.to_string(),
-        }),
+        })
+        .with_id(3),
     ];
     let favorites = FavoritesBuilder::new()
         .add_item(None, constants::AIRDROP_URL)
@@ -157,18 +257,307 @@ fn should_handle_multiple_favorites() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn should_report_positions_of_favorites() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let result = finder.get_favorites_list()?;
+
+    // Assert
+    let details: Vec<_> = result
+        .iter()
+        .map(|item| (item.position(), item.label(), item.path(), item.kind()))
+        .collect();
+    assert_eq!(
+        details,
+        vec![
+            (Some(0), "AirDrop", None, Kind::AirDrop),
+            (
+                Some(1),
+                constants::PROJECTS_LABEL,
+                Some(constants::PROJECTS_PATH),
+                Kind::Custom
+            ),
+        ]
+    );
+    Ok(())
+}
+
 #[test]
 fn should_handle_custom_location() -> Result<()> {
     // Arrange
-    let expected_result = vec![SidebarItem::new(Target::Custom {
-        label: constants::PROJECTS_LABEL.to_string(),
-        path: constants::PROJECTS_PATH.to_string(),
-    })];
+    let expected_result = vec![
+        SidebarItem::new(Target::Custom {
+            label: constants::PROJECTS_LABEL.to_string(),
+            path: constants::PROJECTS_PATH.to_string(),
+        })
+        .with_id(1),
+    ];
+
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.get_favorites_list()?;
+
+    // Assert
+    assert_eq!(result, expected_result);
+    Ok(())
+}
+
+#[test]
+fn should_report_hidden_items() -> Result<()> {
+    // Arrange
+    let expected_result = vec![
+        SidebarItem::new(Target::AirDrop)
+            .with_id(1)
+            .with_visibility(Visibility::Hidden),
+        SidebarItem::new(Target::Applications).with_id(2),
+    ];
+    let favorites = FavoritesBuilder::new()
+        .add_hidden_item(None, constants::AIRDROP_URL)
+        .add_item(
+            Some(constants::APPLICATIONS_LABEL),
+            constants::APPLICATIONS_URL,
+        )
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.get_favorites_list()?;
+
+    // Assert
+    assert_eq!(result, expected_result);
+    Ok(())
+}
+
+#[test]
+fn should_read_custom_item_property() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .with_property(constants::MANAGED_PROPERTY, CFString::new("favkit"))
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.get_item_property(ItemId::from(1), constants::MANAGED_PROPERTY)?;
+
+    // Assert
+    assert_eq!(result, Some(PropertyValue::String("favkit".to_string())));
+    Ok(())
+}
+
+#[test]
+fn should_return_none_for_missing_item_property() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.get_item_property(ItemId::from(1), constants::MANAGED_PROPERTY)?;
+
+    // Assert
+    assert_eq!(result, None);
+    Ok(())
+}
+
+#[test]
+fn should_fail_to_read_property_of_unknown_item() -> Result<()> {
+    // Arrange
+    let expected_error = Err(FinderError::ItemNotFound(ItemId::from(42)));
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.get_item_property(ItemId::from(42), constants::MANAGED_PROPERTY);
+
+    // Assert
+    assert_eq!(result, expected_error);
+    Ok(())
+}
+
+#[test]
+fn should_hide_item_by_label() -> Result<()> {
+    // Arrange
+    let expected_result = vec![
+        SidebarItem::new(Target::AirDrop)
+            .with_id(1)
+            .with_visibility(Visibility::Hidden),
+        SidebarItem::new(Target::Applications).with_id(2),
+    ];
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(
+            Some(constants::APPLICATIONS_LABEL),
+            constants::APPLICATIONS_URL,
+        )
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    finder.hide(&Selector::Label("AirDrop".to_string()))?;
+
+    // Assert
+    assert_eq!(finder.get_favorites_list()?, expected_result);
+    Ok(())
+}
+
+#[test]
+fn should_show_hidden_item_by_id() -> Result<()> {
+    // Arrange
+    let expected_result = vec![SidebarItem::new(Target::AirDrop).with_id(1)];
+    let favorites = FavoritesBuilder::new()
+        .add_hidden_item(None, constants::AIRDROP_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    finder.show(&Selector::Id(ItemId::from(1)))?;
+
+    // Assert
+    assert_eq!(finder.get_favorites_list()?, expected_result);
+    Ok(())
+}
+
+#[test]
+fn should_fail_to_hide_unknown_item() -> Result<()> {
+    // Arrange
+    let selector = Selector::Label("Downloads".to_string());
+    let expected_error = Err(FinderError::NoMatch(selector.clone()));
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.hide(&selector);
+
+    // Assert
+    assert_eq!(result, expected_error);
+    Ok(())
+}
+
+#[test]
+fn should_refuse_ambiguous_selector() -> Result<()> {
+    // Arrange
+    let selector = Selector::Label(constants::PROJECTS_LABEL.to_string());
+    let expected_error = Err(FinderError::AmbiguousSelector(selector.clone()));
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(
+            Some(constants::PROJECTS_LABEL),
+            "file:///Volumes/Work/Projects/",
+        )
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.hide(&selector);
+
+    // Assert
+    assert_eq!(result, expected_error);
+    Ok(())
+}
+
+#[test]
+fn should_resolve_with_default_flags() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let resolution_flags = mock_api.resolution_flags();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    finder.get_favorites_list()?;
 
+    // Assert
+    assert_eq!(resolution_flags.get(), Some(0));
+    Ok(())
+}
+
+#[test]
+fn should_resolve_without_ui_or_mounting_when_non_interactive() -> Result<()> {
+    // Arrange
     let favorites = FavoritesBuilder::new()
         .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
         .build();
     let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let resolution_flags = mock_api.resolution_flags();
+    let finder = Finder::new(mock_api).with_resolution(ResolutionOptions::non_interactive());
+
+    // Act
+    finder.get_favorites_list()?;
+
+    // Assert
+    assert_eq!(
+        resolution_flags.get(),
+        Some(kLSSharedFileListNoUserInteraction | kLSSharedFileListDoNotMountVolumes)
+    );
+    Ok(())
+}
+
+#[test]
+fn should_handle_network_share() -> Result<()> {
+    // Arrange
+    let expected_result = vec![
+        SidebarItem::new(Target::network(constants::NAS_LABEL, constants::NAS_URL)).with_id(1),
+    ];
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.get_favorites_list()?;
+
+    // Assert
+    assert_eq!(result, expected_result);
+    Ok(())
+}
+
+#[test]
+fn should_report_items_that_fail_to_resolve() -> Result<()> {
+    // Arrange
+    let expected_result = vec![
+        SidebarItem::new(Target::unresolved(
+            constants::ARCHIVE_LABEL,
+            ResolutionFailure::NotFound,
+        ))
+        .with_id(1),
+    ];
+    let favorites = FavoritesBuilder::new()
+        .add_unresolved_item(
+            Some(constants::ARCHIVE_LABEL),
+            constants::COCOA_ERROR_DOMAIN,
+            constants::NO_SUCH_FILE_ERROR,
+        )
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
     let finder = Finder::new(mock_api);
 
     // Act
@@ -178,3 +567,749 @@ fn should_handle_custom_location() -> Result<()> {
     assert_eq!(result, expected_result);
     Ok(())
 }
+
+fn finder_with_stale_items() -> Finder {
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::GONE_LABEL), constants::GONE_URL)
+        .add_unresolved_item(
+            Some(constants::ARCHIVE_LABEL),
+            constants::COCOA_ERROR_DOMAIN,
+            constants::NO_SUCH_FILE_ERROR,
+        )
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .build();
+    Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build())
+}
+
+#[test]
+fn should_prune_items_whose_targets_are_gone() -> Result<()> {
+    // Arrange
+    let expected_removed = vec![
+        SidebarItem::new(Target::custom(constants::GONE_LABEL, constants::GONE_PATH)).with_id(2),
+        SidebarItem::new(Target::unresolved(
+            constants::ARCHIVE_LABEL,
+            ResolutionFailure::NotFound,
+        ))
+        .with_id(3),
+    ];
+    let expected_remaining = vec![
+        SidebarItem::new(Target::AirDrop).with_id(1),
+        SidebarItem::new(Target::network(constants::NAS_LABEL, constants::NAS_URL)).with_id(4),
+    ];
+    let finder = finder_with_stale_items();
+
+    // Act
+    let removed = finder.prune(PruneOptions::default())?;
+
+    // Assert
+    assert_eq!(removed, expected_removed);
+    assert_eq!(finder.get_favorites_list()?, expected_remaining);
+    Ok(())
+}
+
+#[test]
+fn should_keep_existing_folders_with_encoded_urls_when_pruning() -> Result<()> {
+    // Arrange
+    let dir = TempDir::new("finder-prune");
+    let folder = dir.join("My Projects");
+    std::fs::create_dir(&folder).expect("folder can be created");
+    let path = folder.to_str().expect("temporary path is UTF-8");
+    let url = format!("file://{}/", path.replace(' ', "%20"));
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some("My Projects"), &url)
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+
+    // Act
+    let removed = finder.prune(PruneOptions::default())?;
+
+    // Assert
+    assert_eq!(removed, vec![]);
+    assert_eq!(
+        finder.get_favorites_list()?,
+        vec![SidebarItem::new(Target::custom("My Projects", path)).with_id(1)]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_leave_sidebar_untouched_on_dry_run() -> Result<()> {
+    // Arrange
+    let finder = finder_with_stale_items();
+    let before = finder.get_favorites_list()?;
+
+    // Act
+    let removed = finder.prune(PruneOptions::default().dry_run())?;
+
+    // Assert
+    assert_eq!(removed.len(), 2);
+    assert_eq!(finder.get_favorites_list()?, before);
+    Ok(())
+}
+
+#[test]
+fn should_dedupe_keeping_order_of_survivors() -> Result<()> {
+    // Arrange
+    let expected_removed = vec![
+        SidebarItem::new(Target::custom("Work", constants::PROJECTS_PATH)).with_id(3),
+        SidebarItem::new(Target::AirDrop).with_id(4),
+    ];
+    let expected_remaining = vec![
+        SidebarItem::new(Target::custom(
+            constants::PROJECTS_LABEL,
+            constants::PROJECTS_PATH,
+        ))
+        .with_id(1),
+        SidebarItem::new(Target::AirDrop).with_id(2),
+        SidebarItem::new(Target::Applications).with_id(5),
+    ];
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some("Work"), constants::PROJECTS_URL)
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(
+            Some(constants::APPLICATIONS_LABEL),
+            constants::APPLICATIONS_URL,
+        )
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let removed = finder.dedupe(&DedupePolicy::First)?;
+
+    // Assert
+    assert_eq!(removed, expected_removed);
+    assert_eq!(finder.get_favorites_list()?, expected_remaining);
+    Ok(())
+}
+
+#[test]
+fn should_sort_sidebar_alphabetically() -> Result<()> {
+    // Arrange
+    let expected_result = vec![
+        SidebarItem::new(Target::AirDrop).with_id(3),
+        SidebarItem::new(Target::Applications).with_id(1),
+        SidebarItem::new(Target::custom(
+            constants::PROJECTS_LABEL,
+            constants::PROJECTS_PATH,
+        ))
+        .with_id(2),
+    ];
+    let favorites = FavoritesBuilder::new()
+        .add_item(
+            Some(constants::APPLICATIONS_LABEL),
+            constants::APPLICATIONS_URL,
+        )
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(None, constants::AIRDROP_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let moves = finder.sort(&SortOrder::new(SortKey::Label))?;
+
+    // Assert
+    assert_eq!(moves.len(), 1);
+    assert_eq!(finder.get_favorites_list()?, expected_result);
+    Ok(())
+}
+
+#[test]
+fn should_sort_by_priority_list() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(
+            Some(constants::APPLICATIONS_LABEL),
+            constants::APPLICATIONS_URL,
+        )
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+    let order = SortOrder::new(SortKey::Label)
+        .with_priority([constants::PROJECTS_LABEL, constants::APPLICATIONS_LABEL]);
+
+    // Act
+    finder.sort(&order)?;
+
+    // Assert
+    let labels: Vec<_> = finder
+        .get_favorites_list()?
+        .iter()
+        .map(|item| item.target().label().to_string())
+        .collect();
+    assert_eq!(labels, vec!["Projects", "Applications", "AirDrop"]);
+    Ok(())
+}
+
+#[test]
+fn should_add_item_after_another() -> Result<()> {
+    // Arrange
+    let expected_result = vec![
+        SidebarItem::new(Target::AirDrop).with_id(1),
+        SidebarItem::new(Target::custom(
+            constants::PROJECTS_LABEL,
+            constants::PROJECTS_PATH,
+        ))
+        .with_id(3)
+        .with_ownership(Ownership::Managed),
+        SidebarItem::new(Target::Applications).with_id(2),
+    ];
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(
+            Some(constants::APPLICATIONS_LABEL),
+            constants::APPLICATIONS_URL,
+        )
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+    let target = Target::custom(constants::PROJECTS_LABEL, constants::PROJECTS_PATH);
+
+    // Act
+    let added = finder.add(target, Position::After(ItemId::from(1)))?;
+
+    // Assert
+    assert_eq!(added, expected_result[1]);
+    assert_eq!(finder.get_favorites_list()?, expected_result);
+    Ok(())
+}
+
+#[test]
+fn should_remove_selected_item() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let removed = finder.remove(&Selector::Path(constants::PROJECTS_PATH.to_string()))?;
+
+    // Assert
+    assert_eq!(removed.target().label(), constants::PROJECTS_LABEL);
+    assert_eq!(
+        finder.get_favorites_list()?,
+        vec![SidebarItem::new(Target::AirDrop).with_id(1)]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_remove_items_matching_filter() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .add_item(Some(constants::GONE_LABEL), constants::GONE_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+    let filter = Filter::new().with(Condition::Kind(Kind::Custom));
+
+    // Act
+    let removed = finder.remove_matching(&filter)?;
+
+    // Assert
+    assert_eq!(
+        removed,
+        vec![
+            SidebarItem::new(Target::custom(
+                constants::PROJECTS_LABEL,
+                constants::PROJECTS_PATH
+            ))
+            .with_id(2),
+            SidebarItem::new(Target::custom(constants::GONE_LABEL, constants::GONE_PATH))
+                .with_id(4),
+        ]
+    );
+    assert_eq!(
+        finder.get_favorites_list()?,
+        vec![
+            SidebarItem::new(Target::AirDrop).with_id(1),
+            SidebarItem::new(Target::network(constants::NAS_LABEL, constants::NAS_URL)).with_id(3),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_apply_batch_in_order() -> std::result::Result<(), BatchError> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+    let operations = [
+        Operation::add(constants::NAS_LABEL, constants::NAS_URL, Destination::First),
+        Operation::Move {
+            item: Selector::Label(constants::PROJECTS_LABEL.to_string()),
+            to: Destination::After(Selector::Label(constants::NAS_LABEL.to_string())),
+        },
+        Operation::Remove(Selector::Id(ItemId::from(1))),
+    ];
+
+    // Act
+    finder.batch(&operations)?;
+
+    // Assert
+    assert_eq!(
+        finder.get_favorites_list()?,
+        vec![
+            SidebarItem::new(Target::network(constants::NAS_LABEL, constants::NAS_URL))
+                .with_id(3)
+                .with_ownership(Ownership::Managed),
+            SidebarItem::new(Target::custom(
+                constants::PROJECTS_LABEL,
+                constants::PROJECTS_PATH
+            ))
+            .with_id(2),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_list_the_sidebar_once_per_batch() -> std::result::Result<(), BatchError> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let snapshots = mock_api.snapshots();
+    let finder = Finder::new(mock_api);
+    let operations = [
+        Operation::add(constants::NAS_LABEL, constants::NAS_URL, Destination::First),
+        Operation::Move {
+            item: Selector::Label(constants::PROJECTS_LABEL.to_string()),
+            to: Destination::After(Selector::Label(constants::NAS_LABEL.to_string())),
+        },
+        Operation::Rename {
+            item: Selector::Label(constants::PROJECTS_LABEL.to_string()),
+            label: "Code".to_string(),
+        },
+        Operation::Remove(Selector::Id(ItemId::from(1))),
+    ];
+
+    // Act
+    finder.batch(&operations)?;
+
+    // Assert
+    assert_eq!(snapshots.get(), 1);
+    Ok(())
+}
+
+#[test]
+fn should_list_the_sidebar_once_per_bulk_change() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .add_item(Some("Music"), "file:///Users/user/Music/")
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let snapshots = mock_api.snapshots();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let moves = finder.sort(&SortOrder::new(SortKey::Label))?;
+    let sorted = snapshots.get();
+    let removed = finder.remove_matching(&Filter::new().with(Condition::Kind(Kind::Custom)))?;
+
+    // Assert
+    assert!(moves.len() > 1);
+    assert_eq!(removed.len(), 2);
+    assert_eq!((sorted, snapshots.get()), (1, 2));
+    Ok(())
+}
+
+#[test]
+fn should_roll_back_batch_when_step_fails() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new()
+        .with_favorites(favorites)
+        .rejecting_url(constants::NAS_URL)
+        .build();
+    let finder = Finder::new(mock_api);
+    let operations = [
+        Operation::Remove(Selector::Label(constants::PROJECTS_LABEL.to_string())),
+        Operation::add(constants::NAS_LABEL, constants::NAS_URL, Destination::Last),
+    ];
+
+    // Act
+    let result = finder.batch(&operations);
+
+    // Assert
+    assert!(matches!(
+        result,
+        Err(BatchError::RolledBack { number: 2, .. })
+    ));
+    let labels: Vec<_> = finder
+        .get_favorites_list()?
+        .iter()
+        .map(|item| item.target().label().to_string())
+        .collect();
+    assert_eq!(labels, vec!["AirDrop", constants::PROJECTS_LABEL]);
+    Ok(())
+}
+
+fn labels(finder: &Finder) -> Result<Vec<String>> {
+    Ok(finder
+        .get_favorites_list()?
+        .iter()
+        .map(|item| item.target().label().to_string())
+        .collect())
+}
+
+#[test]
+fn should_restore_listed_folders_with_spaces_in_their_paths() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some("My Projects"), "file:///Users/user/My%20Projects/")
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+    let snapshot = finder.get_favorites_list()?;
+    finder.remove(&Selector::Label("My Projects".to_string()))?;
+
+    // Act
+    finder.restore(&snapshot)?;
+
+    // Assert
+    let targets = |items: Vec<SidebarItem>| -> Vec<Target> {
+        items.iter().map(|item| item.target().clone()).collect()
+    };
+    assert_eq!(
+        targets(finder.get_favorites_list()?),
+        vec![
+            Target::AirDrop,
+            Target::custom("My Projects", "/Users/user/My Projects"),
+        ]
+    );
+    assert_eq!(targets(finder.get_favorites_list()?), targets(snapshot));
+    Ok(())
+}
+
+#[test]
+fn should_undo_and_redo_recorded_changes() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api).with_journal(MemoryJournal::default());
+    finder.remove(&Selector::Label(constants::PROJECTS_LABEL.to_string()))?;
+    finder.hide(&Selector::Label(constants::NAS_LABEL.to_string()))?;
+
+    // Act
+    let undone_hide = finder.undo(false)?;
+    let undone_remove = finder.undo(false)?;
+    let after_undo = labels(&finder)?;
+    let nothing_left = finder.undo(false)?;
+    let redone = finder.redo(false)?;
+
+    // Assert
+    let operation = |entry: Option<favkit::finder::Entry>| entry.map(|entry| entry.operation);
+    assert_eq!(operation(undone_hide), Some("hide NAS".to_string()));
+    assert_eq!(
+        operation(undone_remove),
+        Some("remove Projects".to_string())
+    );
+    assert_eq!(
+        after_undo,
+        vec!["AirDrop", constants::PROJECTS_LABEL, constants::NAS_LABEL]
+    );
+    assert_eq!(nothing_left, None);
+    assert_eq!(operation(redone), Some("remove Projects".to_string()));
+    assert_eq!(labels(&finder)?, vec!["AirDrop", constants::NAS_LABEL]);
+    assert!(!finder.get_favorites_list()?[1].is_hidden());
+    Ok(())
+}
+
+#[test]
+fn should_refuse_undo_after_outside_change() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .build();
+    let outside = Finder::new(
+        MockMacOsApiBuilder::new()
+            .with_favorites(favorites.clone())
+            .build(),
+    );
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build())
+        .with_journal(MemoryJournal::default());
+    finder.remove(&Selector::Label(constants::PROJECTS_LABEL.to_string()))?;
+    outside.hide(&Selector::Label(constants::NAS_LABEL.to_string()))?;
+
+    // Act
+    let refused = finder.undo(false);
+    let forced = finder.undo(true)?;
+
+    // Assert
+    assert!(matches!(
+        refused,
+        Err(FinderError::ChangedSinceJournal { .. })
+    ));
+    assert!(forced.is_some());
+    assert_eq!(
+        labels(&finder)?,
+        vec!["AirDrop", constants::PROJECTS_LABEL, constants::NAS_LABEL]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_fail_undo_without_journal() {
+    // Arrange
+    let mock_api = MockMacOsApiBuilder::new()
+        .with_favorites(FavoritesBuilder::new().build())
+        .build();
+    let finder = Finder::new(mock_api);
+
+    // Act
+    let result = finder.undo(false);
+
+    // Assert
+    assert_eq!(result, Err(FinderError::NoJournal));
+}
+
+#[test]
+fn should_apply_manifest_once() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+    let manifest = Manifest::new([
+        SidebarItem::new(Target::custom(
+            constants::PROJECTS_LABEL,
+            constants::PROJECTS_PATH,
+        )),
+        SidebarItem::new(Target::AirDrop).with_visibility(Visibility::Hidden),
+        SidebarItem::new(Target::Applications),
+    ]);
+
+    // Act
+    let planned = finder.apply_changes(&manifest, Scope::All)?;
+    let applied = finder.apply(&manifest, Scope::All)?;
+    let again = finder.apply(&manifest, Scope::All)?;
+
+    // Assert
+    assert_eq!(planned, applied);
+    let descriptions: Vec<String> = applied.iter().map(Change::to_string).collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            "remove NAS",
+            "move Projects to 1",
+            "hide AirDrop",
+            "add Applications at 3"
+        ]
+    );
+    assert_eq!(again, Vec::new());
+    assert_eq!(
+        labels(&finder)?,
+        vec![
+            constants::PROJECTS_LABEL,
+            "AirDrop",
+            constants::APPLICATIONS_LABEL
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_only_move_items_out_of_order_when_applying() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::PROJECTS_LABEL), constants::PROJECTS_URL)
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .add_item(
+            Some(constants::APPLICATIONS_LABEL),
+            constants::APPLICATIONS_URL,
+        )
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+    let manifest = Manifest::new([
+        SidebarItem::new(Target::Applications),
+        SidebarItem::new(Target::AirDrop),
+        SidebarItem::new(Target::custom(
+            constants::PROJECTS_LABEL,
+            constants::PROJECTS_PATH,
+        )),
+        SidebarItem::new(Target::network(constants::NAS_LABEL, constants::NAS_URL)),
+    ]);
+    let before = u32::from(finder.seed()?);
+
+    // Act
+    let applied = finder.apply(&manifest, Scope::All)?;
+
+    // Assert
+    let descriptions: Vec<String> = applied.iter().map(Change::to_string).collect();
+    assert_eq!(descriptions, vec!["move Applications to 1"]);
+    assert_eq!(u32::from(finder.seed()?), before + 1);
+    let ids: Vec<_> = finder
+        .get_favorites_list()?
+        .iter()
+        .filter_map(SidebarItem::id)
+        .collect();
+    assert_eq!(ids, [4, 1, 2, 3].map(ItemId::from));
+    Ok(())
+}
+
+#[test]
+fn should_apply_manifest_with_spaces_in_paths_once() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some("My Projects"), "file:///Users/user/My%20Projects/")
+        .build();
+    let finder = Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build());
+    let manifest = Manifest::new([
+        SidebarItem::new(Target::AirDrop),
+        SidebarItem::new(Target::custom("My Projects", "/Users/user/My Projects")),
+        SidebarItem::new(Target::custom("Client Files", "/Users/user/Client Files")),
+    ]);
+
+    // Act
+    let applied = finder.apply(&manifest, Scope::All)?;
+    let again = finder.apply(&manifest, Scope::All)?;
+
+    // Assert
+    let descriptions: Vec<String> = applied.iter().map(Change::to_string).collect();
+    assert_eq!(descriptions, vec!["add Client Files at 3"]);
+    assert_eq!(again, Vec::new());
+    let listed = finder.get_favorites_list()?;
+    assert_eq!(listed[1].id(), Some(ItemId::from(2)));
+    assert_eq!(
+        listed[2].target(),
+        &Target::custom("Client Files", "/Users/user/Client Files")
+    );
+    Ok(())
+}
+
+#[test]
+fn should_tag_added_items_as_managed() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+    let target = Target::custom(constants::PROJECTS_LABEL, constants::PROJECTS_PATH);
+
+    // Act
+    let added = finder.add(target, Position::Last)?;
+    let id = added.id().expect("added item has an id");
+
+    // Assert
+    assert!(added.is_managed());
+    assert_eq!(
+        finder.get_item_property(id, MANAGED_PROPERTY)?,
+        Some(PropertyValue::Bool(true))
+    );
+    let managed: Vec<bool> = finder
+        .get_favorites_list()?
+        .iter()
+        .map(SidebarItem::is_managed)
+        .collect();
+    assert_eq!(managed, vec![false, true]);
+    Ok(())
+}
+
+#[test]
+fn should_apply_manifest_to_managed_items_only() -> Result<()> {
+    // Arrange
+    let favorites = FavoritesBuilder::new()
+        .add_item(None, constants::AIRDROP_URL)
+        .add_item(Some(constants::NAS_LABEL), constants::NAS_URL)
+        .build();
+    let mock_api = MockMacOsApiBuilder::new().with_favorites(favorites).build();
+    let finder = Finder::new(mock_api);
+    finder.add(
+        Target::custom(constants::PROJECTS_LABEL, constants::PROJECTS_PATH),
+        Position::Last,
+    )?;
+    let manifest = Manifest::new([SidebarItem::new(Target::Applications)]);
+
+    // Act
+    let applied = finder.apply(&manifest, Scope::Managed)?;
+
+    // Assert
+    let descriptions: Vec<String> = applied.iter().map(Change::to_string).collect();
+    assert_eq!(
+        descriptions,
+        vec!["remove Projects", "add Applications at 1"]
+    );
+    assert_eq!(
+        labels(&finder)?,
+        vec![
+            constants::APPLICATIONS_LABEL,
+            "AirDrop",
+            constants::NAS_LABEL
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_serialise_calls_from_many_threads() -> Result<()> {
+    // Arrange
+    let finder = SharedFinder::spawn(|| {
+        let favorites = FavoritesBuilder::new()
+            .add_item(None, constants::AIRDROP_URL)
+            .build();
+        Finder::new(MockMacOsApiBuilder::new().with_favorites(favorites).build())
+    })?;
+    let labels = ["Music", "Photos", "Work"];
+
+    // Act
+    let added: Vec<Result<Result<SidebarItem>>> = std::thread::scope(|scope| {
+        let workers = labels.map(|label| {
+            let finder = finder.clone();
+            scope.spawn(move || {
+                let target = Target::custom(label, format!("/Users/user/{}", label));
+                finder.call(move |finder| finder.add(target, Position::Last))
+            })
+        });
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("worker thread finished"))
+            .collect()
+    });
+    let result = finder.get_favorites_list()?;
+
+    // Assert
+    for item in added {
+        item??;
+    }
+    let mut listed: Vec<&str> = result.iter().skip(1).map(SidebarItem::label).collect();
+    listed.sort();
+    assert_eq!(listed, labels);
+    Ok(())
+}
@@ -1,18 +1,25 @@
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use core_foundation::{
-    array::CFArray,
-    base::TCFType,
+    base::{CFIndex, CFType, TCFType, kCFAllocatorDefault},
+    boolean::CFBoolean,
+    error::{CFError, CFErrorCreate},
+    number::{CFNumber, CFNumberRef},
     string::CFString,
-    url::{CFURL, kCFURLPOSIXPathStyle},
 };
-use core_services::OpaqueLSSharedFileListItemRef;
-use favkit::system::favorites::{DisplayName, Snapshot, Url};
+use core_services::{LSSharedFileListItemRef, kLSSharedFileListItemHidden};
+use favkit::system::favorites::{DisplayName, Url};
+
+/// Item property as stored by the mock: name and Core Foundation value
+pub type MockProperty = (String, CFType);
 
 /// Builder for creating test data
 #[derive(Default)]
 pub struct FavoritesBuilder {
-    items: Vec<(Option<&'static str>, &'static str)>,
+    items: Vec<FavoriteItem>,
 }
 
 impl FavoritesBuilder {
@@ -20,84 +27,141 @@ impl FavoritesBuilder {
         Self::default()
     }
 
-    pub fn add_item(mut self, name: Option<&'static str>, url: &'static str) -> Self {
-        self.items.push((name, url));
+    pub fn add_item(mut self, name: Option<&'static str>, url: &str) -> Self {
+        let id = self.next_id();
+        self.items.push(FavoriteItem::new(id, name, url));
+        self
+    }
+
+    pub fn add_hidden_item(self, name: Option<&'static str>, url: &'static str) -> Self {
+        let hidden_key = unsafe { CFString::wrap_under_get_rule(kLSSharedFileListItemHidden) };
+        self.add_item(name, url)
+            .with_property(&hidden_key.to_string(), CFBoolean::true_value())
+    }
+
+    /// Adds an item whose URL resolution fails with the given error
+    pub fn add_unresolved_item(
+        mut self,
+        name: Option<&'static str>,
+        domain: &'static str,
+        code: CFIndex,
+    ) -> Self {
+        let id = self.next_id();
+        self.items
+            .push(FavoriteItem::unresolved(id, name, domain, code));
+        self
+    }
+
+    /// Attaches a property to the most recently added item
+    pub fn with_property(mut self, name: &str, value: impl TCFType) -> Self {
+        if let Some(item) = self.items.last_mut() {
+            item.properties.push((name.to_string(), value.as_CFType()));
+        }
         self
     }
 
     pub fn build(self) -> Favorites {
-        let items = self
-            .items
-            .into_iter()
-            .map(|(name, url)| FavoriteItem::new(name, url))
-            .collect();
-        Favorites::new(items)
+        Favorites::new(self.items)
+    }
+
+    fn next_id(&self) -> u32 {
+        self.items.len() as u32 + 1
     }
 }
 
 /// Represents a favorite item with its Core Foundation data
 #[derive(Debug)]
-struct FavoriteItem {
-    display_name: DisplayName,
-    url: Url,
+pub struct FavoriteItem {
+    pub(crate) id: u32,
+    /// Core Foundation object handed out as the item's reference, holding its id
+    pub(crate) handle: CFNumber,
+    pub(crate) display_name: DisplayName,
+    pub(crate) resolution: std::result::Result<Url, CFError>,
+    pub(crate) properties: Vec<MockProperty>,
 }
 
 impl FavoriteItem {
-    fn new(display_name: Option<&str>, url: &str) -> Self {
-        let display_name = {
-            let name = display_name.unwrap_or_default();
-            let cf_string = CFString::new(name);
-            DisplayName::try_from(cf_string.as_concrete_TypeRef()).unwrap()
-        };
+    fn new(id: u32, display_name: Option<&str>, url: &str) -> Self {
+        let url = Url::try_from(url).expect("mock favorites use valid URLs");
 
-        let url = {
-            let is_dir = url.ends_with('/');
-            let file_path = CFString::new(url);
-            let url_cf = CFURL::from_file_system_path(file_path, kCFURLPOSIXPathStyle, is_dir);
-            Url::try_from(url_cf.as_concrete_TypeRef()).unwrap()
-        };
+        Self {
+            id,
+            handle: CFNumber::from(i64::from(id)),
+            display_name: Self::display_name(display_name),
+            resolution: Ok(url),
+            properties: Vec::new(),
+        }
+    }
 
-        Self { display_name, url }
+    /// Item created through the API, e.g. by `LSSharedFileListInsertItemURL`
+    pub(crate) fn inserted(id: u32, display_name: DisplayName, url: Url) -> Self {
+        Self {
+            id,
+            handle: CFNumber::from(i64::from(id)),
+            display_name,
+            resolution: Ok(url),
+            properties: Vec::new(),
+        }
     }
-}
 
-/// Collection of favorite items with their snapshot
-#[derive(Debug)]
-pub struct Favorites {
-    pub(crate) snapshot: Rc<Option<Snapshot>>,
-    pub(crate) display_names: Rc<Vec<DisplayName>>,
-    pub(crate) urls: Rc<Vec<Url>>,
-}
+    fn unresolved(id: u32, display_name: Option<&str>, domain: &str, code: CFIndex) -> Self {
+        let error = {
+            let domain = CFString::new(domain);
+            unsafe {
+                let error_ref = CFErrorCreate(
+                    kCFAllocatorDefault,
+                    domain.as_concrete_TypeRef(),
+                    code,
+                    std::ptr::null(),
+                );
+                CFError::wrap_under_create_rule(error_ref)
+            }
+        };
 
-impl Default for Favorites {
-    fn default() -> Self {
         Self {
-            snapshot: Rc::new(None),
-            display_names: Rc::new(Vec::new()),
-            urls: Rc::new(Vec::new()),
+            id,
+            handle: CFNumber::from(i64::from(id)),
+            display_name: Self::display_name(display_name),
+            resolution: Err(error),
+            properties: Vec::new(),
         }
     }
+
+    /// Reference to the item, as found in snapshots
+    pub(crate) fn item_ref(&self) -> LSSharedFileListItemRef {
+        self.handle.as_concrete_TypeRef() as LSSharedFileListItemRef
+    }
+
+    /// Id of the item behind a reference, also after it left the list
+    pub(crate) fn id_of(item_ref: LSSharedFileListItemRef) -> u32 {
+        let handle = unsafe { CFNumber::wrap_under_get_rule(item_ref as CFNumberRef) };
+        handle.to_i64().expect("item references hold their id") as u32
+    }
+
+    fn display_name(name: Option<&str>) -> DisplayName {
+        let cf_string = CFString::new(name.unwrap_or_default());
+        DisplayName::try_from(cf_string.as_concrete_TypeRef()).unwrap()
+    }
+}
+
+/// Shared, mutable list of favorite items backing the mock API
+///
+/// Clones share the same list, so several mock APIs can act on one sidebar.
+#[derive(Debug, Default, Clone)]
+pub struct Favorites {
+    pub(crate) items: Rc<RefCell<Vec<FavoriteItem>>>,
+    /// List version reported with every snapshot, bumped by each change
+    pub(crate) seed: Rc<Cell<u32>>,
+    pub(crate) next_id: Rc<Cell<u32>>,
 }
 
 impl Favorites {
     fn new(items: Vec<FavoriteItem>) -> Self {
-        let snapshot = {
-            let snapshot_items: Vec<_> = (1..=items.len())
-                .map(|i| (i as i32) as *mut OpaqueLSSharedFileListItemRef)
-                .collect();
-            let array = CFArray::from_copyable(&snapshot_items);
-            Rc::new(Some(
-                Snapshot::try_from(array.as_concrete_TypeRef()).unwrap(),
-            ))
-        };
-
-        let display_names = Rc::new(items.iter().map(|item| item.display_name.clone()).collect());
-        let urls = Rc::new(items.iter().map(|item| item.url.clone()).collect());
-
+        let next_id = items.iter().map(|item| item.id).max().unwrap_or(0) + 1;
         Self {
-            snapshot,
-            display_names,
-            urls,
+            items: Rc::new(RefCell::new(items)),
+            seed: Rc::new(Cell::new(1)),
+            next_id: Rc::new(Cell::new(next_id)),
         }
     }
 }
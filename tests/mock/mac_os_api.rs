@@ -1,29 +1,30 @@
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use core_foundation::{
     array::{CFArray, CFArrayRef},
-    base::{CFAllocatorRef, CFTypeRef, TCFType},
+    base::{CFAllocatorRef, CFType, CFTypeRef, OSStatus, TCFType},
+    dictionary::CFDictionaryRef,
     error::CFErrorRef,
-    string::CFStringRef,
+    number::CFNumber,
+    string::{CFString, CFStringRef},
     url::CFURLRef,
 };
 use core_services::{
-    LSSharedFileListItemRef, LSSharedFileListRef, LSSharedFileListResolutionFlags,
-    OpaqueLSSharedFileListItemRef,
+    IconRef, LSSharedFileListItemRef, LSSharedFileListRef, LSSharedFileListResolutionFlags,
+    OpaqueLSSharedFileListItemRef, kLSSharedFileListItemBeforeFirst, kLSSharedFileListItemLast,
 };
-use favkit::system::favorites::{DisplayName, Snapshot, Url};
+use favkit::system::favorites::{DisplayName, Url};
 
-use super::favorites::Favorites;
+use super::favorites::{FavoriteItem, Favorites};
 
-/// Index type for safer array access
-#[derive(Debug, Clone, Copy)]
-pub struct ItemIndex(pub(crate) usize);
+/// `paramErr`, returned by operations the mock was not configured for
+const PARAM_ERR: OSStatus = -50;
 
-impl From<LSSharedFileListItemRef> for ItemIndex {
-    fn from(raw: LSSharedFileListItemRef) -> Self {
-        Self((raw as i32 - 1) as usize)
-    }
-}
+/// `fnfErr`, returned when an operation targets an item that is no longer in the list
+const FNF_ERR: OSStatus = -43;
 
 type ListHandle = LSSharedFileListRef;
 type SnapshotArray = CFArrayRef;
@@ -33,9 +34,24 @@ pub mod handlers {
     use super::*;
 
     pub type CreateListFn = Box<dyn Fn() -> ListHandle>;
-    pub type GetSnapshotFn = Box<dyn Fn(ListHandle) -> SnapshotArray>;
+    pub type GetSnapshotFn = Box<dyn Fn(ListHandle, *mut u32) -> SnapshotArray>;
     pub type GetDisplayNameFn = Box<dyn Fn(LSSharedFileListItemRef) -> CFStringRef>;
-    pub type GetUrlFn = Box<dyn Fn(LSSharedFileListItemRef) -> CFURLRef>;
+    pub type GetUrlFn = Box<dyn Fn(LSSharedFileListItemRef, *mut CFErrorRef) -> CFURLRef>;
+    pub type GetIdFn = Box<dyn Fn(LSSharedFileListItemRef) -> u32>;
+    pub type GetPropertyFn = Box<dyn Fn(LSSharedFileListItemRef, CFStringRef) -> CFTypeRef>;
+    pub type SetPropertyFn =
+        Box<dyn Fn(LSSharedFileListItemRef, CFStringRef, CFTypeRef) -> OSStatus>;
+    pub type RemoveItemFn = Box<dyn Fn(ListHandle, LSSharedFileListItemRef) -> OSStatus>;
+    pub type InsertItemFn = Box<
+        dyn Fn(
+            ListHandle,
+            LSSharedFileListItemRef,
+            CFStringRef,
+            CFURLRef,
+        ) -> LSSharedFileListItemRef,
+    >;
+    pub type MoveItemFn =
+        Box<dyn Fn(ListHandle, LSSharedFileListItemRef, LSSharedFileListItemRef) -> OSStatus>;
 }
 use handlers::*;
 
@@ -51,26 +67,29 @@ pub struct MockMacOsApiBuilder<State = Uninitialized> {
     snapshot_fn: Option<GetSnapshotFn>,
     display_name_fn: Option<GetDisplayNameFn>,
     resolved_url_fn: Option<GetUrlFn>,
+    property_fn: Option<GetPropertyFn>,
+    set_property_fn: Option<SetPropertyFn>,
+    remove_item_fn: Option<RemoveItemFn>,
+    move_item_fn: Option<MoveItemFn>,
+    insert_item_fn: Option<InsertItemFn>,
     _state: std::marker::PhantomData<State>,
 }
 
 impl Default for MockMacOsApiBuilder<Uninitialized> {
     fn default() -> Self {
-        let raw_list = 1 as ListHandle;
         let empty_snapshot =
             CFArray::from_copyable(&Vec::<*mut OpaqueLSSharedFileListItemRef>::new());
-        let snapshot = Rc::new(Some(
-            Snapshot::try_from(empty_snapshot.as_concrete_TypeRef()).unwrap(),
-        ));
 
         Self {
-            list_create_fn: Some(Box::new(move || raw_list)),
-            snapshot_fn: Some(Box::new(move |_| {
-                let snapshot = snapshot.as_ref().as_ref().unwrap();
-                snapshot.into()
-            })),
+            list_create_fn: Some(Box::new(Self::create_list)),
+            snapshot_fn: Some(Box::new(move |_, _| empty_snapshot.as_concrete_TypeRef())),
             display_name_fn: None,
             resolved_url_fn: None,
+            property_fn: None,
+            set_property_fn: None,
+            remove_item_fn: None,
+            move_item_fn: None,
+            insert_item_fn: None,
             _state: std::marker::PhantomData,
         }
     }
@@ -81,35 +100,251 @@ impl MockMacOsApiBuilder<Uninitialized> {
         Self::default()
     }
 
+    /// A new Core Foundation object standing in for a list, owned by the caller like one from
+    /// `LSSharedFileListCreate`
+    fn create_list() -> ListHandle {
+        let list = CFString::new("favorites list");
+        let list_ref = list.as_concrete_TypeRef() as ListHandle;
+        std::mem::forget(list);
+        list_ref
+    }
+
+    /// Runs `f` on the item behind `item_ref`, if it is still in the list
+    fn with_item<T>(
+        items: &RefCell<Vec<FavoriteItem>>,
+        item_ref: LSSharedFileListItemRef,
+        f: impl FnOnce(&mut FavoriteItem) -> T,
+    ) -> Option<T> {
+        items
+            .borrow_mut()
+            .iter_mut()
+            .find(|item| item.item_ref() == item_ref)
+            .map(f)
+    }
+
+    fn copy_snapshot(
+        favorites: &Favorites,
+        snapshot: &RefCell<Option<CFArray<CFNumber>>>,
+        seed: *mut u32,
+    ) -> SnapshotArray {
+        if !seed.is_null() {
+            unsafe { *seed = favorites.seed.get() };
+        }
+        let handles: Vec<_> = favorites
+            .items
+            .borrow()
+            .iter()
+            .map(|item| item.handle.clone())
+            .collect();
+        // Retains the items, like the arrays Core Services returns
+        let array = CFArray::from_CFTypes(&handles);
+        let array_ref = array.as_concrete_TypeRef();
+        // Keep the array alive until the next snapshot, like a borrowed Core Foundation reference
+        snapshot.replace(Some(array));
+        array_ref
+    }
+
     fn get_display_name(
-        display_names: &[DisplayName],
+        items: &RefCell<Vec<FavoriteItem>>,
         item_ref: LSSharedFileListItemRef,
     ) -> CFStringRef {
-        let idx: ItemIndex = item_ref.into();
-        (&display_names[idx.0]).into()
+        Self::with_item(items, item_ref, |item| (&item.display_name).into())
+            .unwrap_or(std::ptr::null())
+    }
+
+    fn get_url(
+        items: &RefCell<Vec<FavoriteItem>>,
+        item_ref: LSSharedFileListItemRef,
+        error: *mut CFErrorRef,
+    ) -> CFURLRef {
+        Self::with_item(items, item_ref, |item| match &item.resolution {
+            Ok(url) => url.into(),
+            Err(cf_error) => {
+                if !error.is_null() {
+                    // The caller owns the error, like one Core Services reports
+                    let owned = cf_error.clone();
+                    unsafe { *error = owned.as_concrete_TypeRef() };
+                    std::mem::forget(owned);
+                }
+                std::ptr::null()
+            }
+        })
+        .unwrap_or(std::ptr::null())
+    }
+
+    fn get_property(
+        items: &RefCell<Vec<FavoriteItem>>,
+        item_ref: LSSharedFileListItemRef,
+        name_ref: CFStringRef,
+    ) -> CFTypeRef {
+        let name = unsafe { CFString::wrap_under_get_rule(name_ref) }.to_string();
+        Self::with_item(items, item_ref, |item| {
+            item.properties
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map_or(std::ptr::null(), |(_, value)| value.as_CFTypeRef())
+        })
+        .unwrap_or(std::ptr::null())
     }
 
-    fn get_url(urls: &[Url], item_ref: LSSharedFileListItemRef) -> CFURLRef {
-        let idx: ItemIndex = item_ref.into();
-        (&urls[idx.0]).into()
+    fn set_property(
+        items: &RefCell<Vec<FavoriteItem>>,
+        item_ref: LSSharedFileListItemRef,
+        name_ref: CFStringRef,
+        value_ref: CFTypeRef,
+    ) -> OSStatus {
+        let name = unsafe { CFString::wrap_under_get_rule(name_ref) }.to_string();
+        Self::with_item(items, item_ref, |item| {
+            item.properties.retain(|(key, _)| *key != name);
+            if !value_ref.is_null() {
+                let value = unsafe { CFType::wrap_under_get_rule(value_ref) };
+                item.properties.push((name, value));
+            }
+            0
+        })
+        .unwrap_or(FNF_ERR)
+    }
+
+    fn remove_item(
+        items: &RefCell<Vec<FavoriteItem>>,
+        item_ref: LSSharedFileListItemRef,
+    ) -> OSStatus {
+        let mut items = items.borrow_mut();
+        match items.iter().position(|item| item.item_ref() == item_ref) {
+            Some(index) => {
+                items.remove(index);
+                0
+            }
+            None => FNF_ERR,
+        }
+    }
+
+    fn move_item(
+        items: &RefCell<Vec<FavoriteItem>>,
+        item_ref: LSSharedFileListItemRef,
+        after_ref: LSSharedFileListItemRef,
+    ) -> OSStatus {
+        let mut items = items.borrow_mut();
+        let Some(from) = items.iter().position(|item| item.item_ref() == item_ref) else {
+            return FNF_ERR;
+        };
+        let item = items.remove(from);
+
+        let to = if after_ref == unsafe { kLSSharedFileListItemBeforeFirst } {
+            Some(0)
+        } else if after_ref == unsafe { kLSSharedFileListItemLast } {
+            Some(items.len())
+        } else {
+            items
+                .iter()
+                .position(|item| item.item_ref() == after_ref)
+                .map(|index| index + 1)
+        };
+
+        match to {
+            Some(to) => {
+                items.insert(to, item);
+                0
+            }
+            None => {
+                items.insert(from, item);
+                FNF_ERR
+            }
+        }
+    }
+
+    fn insert_item(
+        items: &RefCell<Vec<FavoriteItem>>,
+        next_id: &Cell<u32>,
+        after_ref: LSSharedFileListItemRef,
+        name_ref: CFStringRef,
+        url_ref: CFURLRef,
+    ) -> LSSharedFileListItemRef {
+        let (Ok(display_name), Ok(url)) = (DisplayName::try_from(name_ref), Url::try_from(url_ref))
+        else {
+            return std::ptr::null_mut();
+        };
+        let mut items = items.borrow_mut();
+
+        let to = if after_ref == unsafe { kLSSharedFileListItemBeforeFirst } {
+            Some(0)
+        } else if after_ref == unsafe { kLSSharedFileListItemLast } {
+            Some(items.len())
+        } else {
+            items
+                .iter()
+                .position(|item| item.item_ref() == after_ref)
+                .map(|index| index + 1)
+        };
+        let Some(to) = to else {
+            return std::ptr::null_mut();
+        };
+
+        let id = next_id.get();
+        next_id.set(id + 1);
+        let item = FavoriteItem::inserted(id, display_name, url);
+        let item_ref = item.item_ref();
+        // The caller owns the returned reference, like one from `LSSharedFileListInsertItemURL`
+        std::mem::forget(item.handle.clone());
+        items.insert(to, item);
+        item_ref
+    }
+
+    /// Bumps the list seed when a change succeeded
+    fn changed(seed: &Cell<u32>, status: OSStatus) -> OSStatus {
+        if status == 0 {
+            seed.set(seed.get() + 1);
+        }
+        status
     }
 
     pub fn with_favorites(self, favorites: Favorites) -> MockMacOsApiBuilder<WithList> {
-        let raw_list = 1 as ListHandle;
-        let snapshot = Rc::clone(&favorites.snapshot);
-        let display_names = Rc::clone(&favorites.display_names);
-        let urls = Rc::clone(&favorites.urls);
+        let items = Rc::clone(&favorites.items);
+        let seed = Rc::clone(&favorites.seed);
+        let next_id = Rc::clone(&favorites.next_id);
+        let snapshot = RefCell::new(None);
 
         MockMacOsApiBuilder {
-            list_create_fn: Some(Box::new(move || raw_list)),
-            snapshot_fn: Some(Box::new(move |_| {
-                let snapshot = snapshot.as_ref().as_ref().unwrap();
-                snapshot.into()
+            list_create_fn: Some(Box::new(Self::create_list)),
+            snapshot_fn: Some(Box::new(move |_, seed_ref| {
+                Self::copy_snapshot(&favorites, &snapshot, seed_ref)
+            })),
+            display_name_fn: Some(Box::new({
+                let items = Rc::clone(&items);
+                move |item_ref| Self::get_display_name(&items, item_ref)
             })),
-            display_name_fn: Some(Box::new(move |item_ref| {
-                Self::get_display_name(&display_names, item_ref)
+            resolved_url_fn: Some(Box::new({
+                let items = Rc::clone(&items);
+                move |item_ref, error| Self::get_url(&items, item_ref, error)
+            })),
+            property_fn: Some(Box::new({
+                let items = Rc::clone(&items);
+                move |item_ref, name_ref| Self::get_property(&items, item_ref, name_ref)
+            })),
+            set_property_fn: Some(Box::new({
+                let (items, seed) = (Rc::clone(&items), Rc::clone(&seed));
+                move |item_ref, name_ref, value_ref| {
+                    let status = Self::set_property(&items, item_ref, name_ref, value_ref);
+                    Self::changed(&seed, status)
+                }
+            })),
+            remove_item_fn: Some(Box::new({
+                let (items, seed) = (Rc::clone(&items), Rc::clone(&seed));
+                move |_, item_ref| Self::changed(&seed, Self::remove_item(&items, item_ref))
+            })),
+            move_item_fn: Some(Box::new({
+                let (items, seed) = (Rc::clone(&items), Rc::clone(&seed));
+                move |_, item_ref, after_ref| {
+                    Self::changed(&seed, Self::move_item(&items, item_ref, after_ref))
+                }
+            })),
+            insert_item_fn: Some(Box::new(move |_, after_ref, name_ref, url_ref| {
+                let item_ref = Self::insert_item(&items, &next_id, after_ref, name_ref, url_ref);
+                if !item_ref.is_null() {
+                    Self::changed(&seed, 0);
+                }
+                item_ref
             })),
-            resolved_url_fn: Some(Box::new(move |item_ref| Self::get_url(&urls, item_ref))),
             _state: std::marker::PhantomData,
         }
     }
@@ -120,22 +355,49 @@ impl MockMacOsApiBuilder<Uninitialized> {
             snapshot_fn: None,
             display_name_fn: None,
             resolved_url_fn: None,
+            property_fn: None,
+            set_property_fn: None,
+            remove_item_fn: None,
+            move_item_fn: None,
+            insert_item_fn: None,
             _state: std::marker::PhantomData,
         }
     }
 
     pub fn with_null_snapshot(self) -> MockMacOsApiBuilder<WithNullSnapshot> {
-        let raw_list = 1 as ListHandle;
         MockMacOsApiBuilder {
-            list_create_fn: Some(Box::new(move || raw_list)),
-            snapshot_fn: Some(Box::new(|_| std::ptr::null())),
+            list_create_fn: Some(Box::new(Self::create_list)),
+            snapshot_fn: Some(Box::new(|_, _| std::ptr::null())),
             display_name_fn: None,
             resolved_url_fn: None,
+            property_fn: None,
+            set_property_fn: None,
+            remove_item_fn: None,
+            move_item_fn: None,
+            insert_item_fn: None,
             _state: std::marker::PhantomData,
         }
     }
 }
 
+impl MockMacOsApiBuilder<WithList> {
+    /// Makes inserting an item pointing at `url` fail, as Core Services does for unusable URLs
+    pub fn rejecting_url(mut self, url: &'static str) -> Self {
+        let insert = self
+            .insert_item_fn
+            .take()
+            .expect("favorites support inserting");
+        self.insert_item_fn =
+            Some(Box::new(
+                move |list, after_ref, name_ref, url_ref| match Url::try_from(url_ref) {
+                    Ok(inserted) if inserted.to_string() == url => std::ptr::null_mut(),
+                    _ => insert(list, after_ref, name_ref, url_ref),
+                },
+            ));
+        self
+    }
+}
+
 // Implement build() for each final state
 impl<State> MockMacOsApiBuilder<State> {
     pub fn build(self) -> MockMacOsApi {
@@ -145,13 +407,32 @@ impl<State> MockMacOsApiBuilder<State> {
                 .unwrap_or_else(|| Box::new(std::ptr::null_mut)),
             snapshot_fn: self
                 .snapshot_fn
-                .unwrap_or_else(|| Box::new(|_| std::ptr::null())),
+                .unwrap_or_else(|| Box::new(|_, _| std::ptr::null())),
             display_name_fn: self
                 .display_name_fn
                 .unwrap_or_else(|| Box::new(|_| std::ptr::null_mut())),
             resolved_url_fn: self
                 .resolved_url_fn
-                .unwrap_or_else(|| Box::new(|_| std::ptr::null_mut())),
+                .unwrap_or_else(|| Box::new(|_, _| std::ptr::null_mut())),
+            id_fn: Box::new(FavoriteItem::id_of),
+            lists: Rc::new(RefCell::new(Vec::new())),
+            snapshots: Rc::new(Cell::new(0)),
+            resolution_flags: Rc::new(Cell::new(None)),
+            property_fn: self
+                .property_fn
+                .unwrap_or_else(|| Box::new(|_, _| std::ptr::null())),
+            set_property_fn: self
+                .set_property_fn
+                .unwrap_or_else(|| Box::new(|_, _, _| PARAM_ERR)),
+            remove_item_fn: self
+                .remove_item_fn
+                .unwrap_or_else(|| Box::new(|_, _| PARAM_ERR)),
+            move_item_fn: self
+                .move_item_fn
+                .unwrap_or_else(|| Box::new(|_, _, _| PARAM_ERR)),
+            insert_item_fn: self
+                .insert_item_fn
+                .unwrap_or_else(|| Box::new(|_, _, _, _| std::ptr::null_mut())),
         }
     }
 }
@@ -162,6 +443,32 @@ pub struct MockMacOsApi {
     snapshot_fn: GetSnapshotFn,
     display_name_fn: GetDisplayNameFn,
     resolved_url_fn: GetUrlFn,
+    id_fn: GetIdFn,
+    property_fn: GetPropertyFn,
+    set_property_fn: SetPropertyFn,
+    remove_item_fn: RemoveItemFn,
+    move_item_fn: MoveItemFn,
+    insert_item_fn: InsertItemFn,
+    lists: Rc<RefCell<Vec<CFString>>>,
+    snapshots: Rc<Cell<usize>>,
+    resolution_flags: Rc<Cell<Option<LSSharedFileListResolutionFlags>>>,
+}
+
+impl MockMacOsApi {
+    /// Shared handle to the flags passed to the latest URL resolution
+    pub fn resolution_flags(&self) -> Rc<Cell<Option<LSSharedFileListResolutionFlags>>> {
+        Rc::clone(&self.resolution_flags)
+    }
+
+    /// Shared handle to every list created so far, to check how many were made and released
+    pub fn lists(&self) -> Rc<RefCell<Vec<CFString>>> {
+        Rc::clone(&self.lists)
+    }
+
+    /// Shared count of the snapshots copied so far
+    pub fn snapshots(&self) -> Rc<Cell<usize>> {
+        Rc::clone(&self.snapshots)
+    }
 }
 
 impl favkit::system::MacOsApi for MockMacOsApi {
@@ -171,15 +478,21 @@ impl favkit::system::MacOsApi for MockMacOsApi {
         _list_type: CFStringRef,
         _list_options: CFTypeRef,
     ) -> LSSharedFileListRef {
-        (self.list_create_fn)()
+        let list_ref = (self.list_create_fn)();
+        if !list_ref.is_null() {
+            let list = unsafe { CFString::wrap_under_get_rule(list_ref as CFStringRef) };
+            self.lists.borrow_mut().push(list);
+        }
+        list_ref
     }
 
     unsafe fn ls_shared_file_list_copy_snapshot(
         &self,
-        _list: LSSharedFileListRef,
-        _seed: *mut u32,
+        list: LSSharedFileListRef,
+        seed: *mut u32,
     ) -> CFArrayRef {
-        (self.snapshot_fn)(_list)
+        self.snapshots.set(self.snapshots.get() + 1);
+        (self.snapshot_fn)(list, seed)
     }
 
     unsafe fn ls_shared_file_list_item_copy_display_name(
@@ -192,9 +505,61 @@ impl favkit::system::MacOsApi for MockMacOsApi {
     unsafe fn ls_shared_file_list_item_copy_resolved_url(
         &self,
         item: LSSharedFileListItemRef,
-        _flags: LSSharedFileListResolutionFlags,
-        _error: *mut CFErrorRef,
+        flags: LSSharedFileListResolutionFlags,
+        error: *mut CFErrorRef,
     ) -> CFURLRef {
-        (self.resolved_url_fn)(item)
+        self.resolution_flags.set(Some(flags));
+        (self.resolved_url_fn)(item, error)
+    }
+
+    unsafe fn ls_shared_file_list_item_get_id(&self, item: LSSharedFileListItemRef) -> u32 {
+        (self.id_fn)(item)
+    }
+
+    unsafe fn ls_shared_file_list_item_copy_property(
+        &self,
+        item: LSSharedFileListItemRef,
+        name: CFStringRef,
+    ) -> CFTypeRef {
+        (self.property_fn)(item, name)
+    }
+
+    unsafe fn ls_shared_file_list_item_set_property(
+        &self,
+        item: LSSharedFileListItemRef,
+        name: CFStringRef,
+        value: CFTypeRef,
+    ) -> OSStatus {
+        (self.set_property_fn)(item, name, value)
+    }
+
+    unsafe fn ls_shared_file_list_item_remove(
+        &self,
+        list: LSSharedFileListRef,
+        item: LSSharedFileListItemRef,
+    ) -> OSStatus {
+        (self.remove_item_fn)(list, item)
+    }
+
+    unsafe fn ls_shared_file_list_item_move(
+        &self,
+        list: LSSharedFileListRef,
+        item: LSSharedFileListItemRef,
+        after: LSSharedFileListItemRef,
+    ) -> OSStatus {
+        (self.move_item_fn)(list, item, after)
+    }
+
+    unsafe fn ls_shared_file_list_insert_item_url(
+        &self,
+        list: LSSharedFileListRef,
+        after: LSSharedFileListItemRef,
+        name: CFStringRef,
+        _icon: IconRef,
+        url: CFURLRef,
+        _properties_to_set: CFDictionaryRef,
+        _properties_to_clear: CFArrayRef,
+    ) -> LSSharedFileListItemRef {
+        (self.insert_item_fn)(list, after, name, url)
     }
 }
@@ -1,2 +1,3 @@
 pub mod favorites;
 pub mod mac_os_api;
+pub mod temp_dir;